@@ -2,7 +2,7 @@ use std::ffi::OsString;
 
 use anyhow::Context as _;
 
-use ethers::prelude::Chain;
+use ethers::prelude::{Address, Chain, Middleware, U256};
 use ethers::signers::{HDPath, Ledger};
 
 use radicle_common::args::{Args, Error, Help};
@@ -76,3 +76,11 @@ pub async fn run(opts: Options) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Get the balance of an account, in wei.
+pub async fn balance<M: Middleware>(provider: &M, address: Address) -> anyhow::Result<U256> {
+    provider
+        .get_balance(address, None)
+        .await
+        .map_err(|err| anyhow::anyhow!("couldn't get the balance of {:?}: {}", address, err))
+}
@@ -19,7 +19,7 @@ pub const HELP: Help = Help {
     usage: r#"
 Usage
 
-    rad auth [--init | --active] [<options>...]
+    rad auth [--init | --active | --switch <id>] [<options>...]
 
     If `--init` is used, name and passphrase may be given via the `--name`
     and `--passphrase` option. Using these disables the respective input prompt.
@@ -28,6 +28,7 @@ Options
 
     --init                  Initialize a new identity
     --active                Authenticate with the currently active profile
+    --switch <id>           Activate the given profile, and authenticate with it
     --name <name>           Use given name (default: none)
     --passphrase <phrase>   Use given passphrase (default: none)
     --help                  Print help
@@ -38,6 +39,7 @@ Options
 pub struct Options {
     pub init: bool,
     pub active: bool,
+    pub switch: Option<String>,
     pub name: Option<String>,
     pub passphrase: Option<String>,
 }
@@ -48,6 +50,7 @@ impl Args for Options {
 
         let mut init = false;
         let mut active = false;
+        let mut switch = None;
         let mut name = None;
         let mut passphrase = None;
         let mut parser = lexopt::Parser::from_args(args);
@@ -60,6 +63,15 @@ impl Args for Options {
                 Long("active") => {
                     active = true;
                 }
+                Long("switch") if switch.is_none() => {
+                    let val = parser
+                        .value()?
+                        .to_str()
+                        .ok_or(anyhow::anyhow!("invalid profile specified with `--switch`"))?
+                        .to_owned();
+
+                    switch = Some(val);
+                }
                 Long("name") if init && name.is_none() => {
                     let val = parser
                         .value()?
@@ -92,10 +104,15 @@ impl Args for Options {
             }
         }
 
+        if init && switch.is_some() {
+            anyhow::bail!("`--switch` can't be used with `--init`");
+        }
+
         Ok((
             Options {
                 init,
                 active,
+                switch,
                 name,
                 passphrase,
             },
@@ -193,11 +210,33 @@ pub fn init(options: Options) -> anyhow::Result<()> {
 }
 
 pub fn authenticate(profiles: &[profile::Profile], options: Options) -> anyhow::Result<()> {
+    if let Some(id) = &options.switch {
+        let selection = profiles
+            .iter()
+            .find(|p| p.id().to_string() == *id)
+            .ok_or_else(|| {
+                let ids = profiles
+                    .iter()
+                    .map(|p| p.id().to_string())
+                    .collect::<Vec<_>>();
+
+                anyhow::anyhow!(
+                    "Could not find radicle profile '{}', available profiles are: {}",
+                    id,
+                    ids.join(", ")
+                )
+            })?;
+        profile::set(selection.id())?;
+
+        term::success!("Profile {} activated", id);
+    }
+
     let profile = match profile::default() {
         Ok(profile) => profile,
         Err(_) => {
             anyhow::bail!(
                 "Active profile could not be loaded.\n\
+                To activate one of your profiles, run `rad auth --switch <id>`.\n\
                 To create a new profile, run `rad auth --init`."
             )
         }
@@ -210,7 +249,7 @@ pub fn authenticate(profiles: &[profile::Profile], options: Options) -> anyhow::
         );
     }
 
-    let selection = if profiles.len() > 1 && !options.active {
+    let selection = if profiles.len() > 1 && !options.active && options.switch.is_none() {
         if let Some(p) = term::profile_select(profiles, &profile) {
             p
         } else {
@@ -288,6 +327,7 @@ mod tests {
         Options {
             active: false,
             init: true,
+            switch: None,
             name: Some(name.to_owned()),
             passphrase: Some(test::USER_PASS.to_owned()),
         }
@@ -305,4 +345,14 @@ mod tests {
         assert_eq!(profile::count().unwrap(), 1);
         assert_eq!(profile::name(None).unwrap(), "user");
     }
+
+    #[test]
+    fn switch_can_be_given() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+
+        let (options, _) = Options::from_args(args(&["--switch", "hyn"])).unwrap();
+        assert_eq!(options.switch.as_deref(), Some("hyn"));
+
+        assert!(Options::from_args(args(&["--init", "--switch", "hyn"])).is_err());
+    }
 }
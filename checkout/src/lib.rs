@@ -1,10 +1,13 @@
+use std::convert::TryFrom;
 use std::ffi::OsString;
 use std::path::PathBuf;
 
 use anyhow::anyhow;
 use anyhow::Context as _;
 
+use librad::git::identities::any;
 use librad::git::Urn;
+use librad::identities::SomeIdentity;
 
 use radicle_common::args::{Args, Error, Help};
 use radicle_common::{fmt, keys, profile, project};
@@ -21,12 +24,23 @@ Usage
 
 Options
 
-    --help    Print help
+    --dry-run       Print what the checkout would do, without doing it
+    --no-signing    Do not set up signing with your radicle key, eg. for read-only checkouts
+    --no-remotes    Do not set up remotes and tracking branches for the project delegates
+    --quiet         Only print essential output, without headlines, spinners or banners.
+                    Must be given before the <urn>
+    --help          Print help
 "#,
 };
 
 pub struct Options {
     pub urn: Urn,
+    /// Only print the checkout plan: path, peer and remotes.
+    pub dry_run: bool,
+    /// Set up signing of commits and tags with the radicle key.
+    pub signing: bool,
+    /// Set up a remote and tracking branch for each project delegate.
+    pub remotes: bool,
 }
 
 impl Args for Options {
@@ -36,9 +50,15 @@ impl Args for Options {
 
         let mut parser = lexopt::Parser::from_args(args);
         let mut urn = None;
+        let mut dry_run = false;
+        let mut signing = true;
+        let mut remotes = true;
 
         while let Some(arg) = parser.next()? {
             match arg {
+                Long("dry-run") => dry_run = true,
+                Long("no-signing") => signing = false,
+                Long("no-remotes") => remotes = false,
                 Long("help") => return Err(Error::Help.into()),
                 Value(val) if urn.is_none() => {
                     let val = val.to_string_lossy();
@@ -53,6 +73,9 @@ impl Args for Options {
         Ok((
             Options {
                 urn: urn.ok_or_else(|| anyhow!("a project URN to checkout must be provided"))?,
+                dry_run,
+                signing,
+                remotes,
             },
             vec![],
         ))
@@ -60,8 +83,13 @@ impl Args for Options {
 }
 
 pub fn run(options: Options) -> anyhow::Result<()> {
+    let dry_run = options.dry_run;
     let path = execute(options)?;
 
+    if dry_run {
+        return Ok(());
+    }
+
     term::headline(&format!(
         "🌱 Project checkout successful under ./{}",
         term::format::highlight(path.file_name().unwrap_or_default().to_string_lossy())
@@ -74,8 +102,17 @@ pub fn execute(options: Options) -> anyhow::Result<PathBuf> {
     let profile = profile::default()?;
     let signer = term::signer(&profile)?;
     let storage = keys::storage(&profile, signer.clone())?;
-    let project = project::get(&storage, &options.urn)?
-        .context("project could not be found in local storage")?;
+    let project = match any::get(&storage, &options.urn)? {
+        Some(SomeIdentity::Project(project)) => project::Metadata::try_from(project)?,
+        Some(_) => anyhow::bail!(
+            "the URN {} does not refer to a project, only projects can be checked out",
+            options.urn
+        ),
+        None => anyhow::bail!(
+            "project {} could not be found in local storage",
+            options.urn
+        ),
+    };
     let path = PathBuf::from(project.name.clone());
 
     if path.exists() {
@@ -100,6 +137,20 @@ pub fn execute(options: Options) -> anyhow::Result<PathBuf> {
     } else if project.remotes.len() > 1 {
         anyhow::bail!("project has more than one delegate, please specify which one you would like to checkout");
     } else if let Some(delegate) = project.remotes.iter().next() {
+        if project::get_head(
+            &storage,
+            &options.urn,
+            Some(delegate),
+            &project.default_branch,
+        )?
+        .is_none()
+        {
+            anyhow::bail!(
+                "delegate {} has no {} branch, cannot checkout",
+                fmt::peer(delegate),
+                project.default_branch
+            );
+        }
         term::success!(
             "Remote {} branch found via {}...",
             project.default_branch,
@@ -110,7 +161,45 @@ pub fn execute(options: Options) -> anyhow::Result<PathBuf> {
         anyhow::bail!("project has no delegates, cannot checkout");
     };
 
-    let spinner = term::spinner("Performing checkout...");
+    if options.dry_run {
+        let remotes = project
+            .remotes
+            .iter()
+            .filter(|peer| options.remotes && *peer != storage.peer_id())
+            .collect::<Vec<_>>();
+
+        term::blank();
+        term::info!(
+            "Would check out into ./{}",
+            term::format::highlight(path.display())
+        );
+        match peer {
+            Some(peer) => term::info!(
+                "Would check out the {} branch of {}",
+                project.default_branch,
+                term::format::tertiary(fmt::peer(&peer))
+            ),
+            None => term::info!(
+                "Would check out the local {} branch",
+                project.default_branch
+            ),
+        }
+        if !options.signing {
+            term::info!("Signing would not be set up");
+        }
+        if remotes.is_empty() {
+            term::info!("No remotes would be set up");
+        }
+        for peer in remotes {
+            term::info!(
+                "Would set up remote {} with a tracking branch",
+                term::format::tertiary(fmt::peer(peer))
+            );
+        }
+        return Ok(path);
+    }
+
+    let mut spinner = term::spinner("Performing checkout...");
     match project::checkout(
         &storage,
         profile.paths().clone(),
@@ -118,6 +207,7 @@ pub fn execute(options: Options) -> anyhow::Result<PathBuf> {
         &options.urn,
         peer,
         path.clone(),
+        |progress| spinner.message(progress.to_string()),
     ) {
         Err(err) => {
             spinner.failed();
@@ -126,11 +216,17 @@ pub fn execute(options: Options) -> anyhow::Result<PathBuf> {
             return Err(err);
         }
         Ok(repo) => {
+            spinner.message("Project checkout complete.".to_owned());
             spinner.finish();
 
             // Setup signing.
-            if let Err(err) = rad_init::setup_signing(storage.peer_id(), &repo) {
-                term::warning(&format!("Warning: Could not setup signing: {:#}", err));
+            if options.signing {
+                if let Err(err) = rad_init::setup_signing(storage.peer_id(), &repo) {
+                    term::warning(&format!("Warning: Could not setup signing: {:#}", err));
+                }
+            }
+            if !options.remotes {
+                return Ok(path);
             }
 
             // Setup a remote and tracking branch for all project delegates except yourself.
@@ -143,12 +239,24 @@ pub fn execute(options: Options) -> anyhow::Result<PathBuf> {
             };
             for peer in &project.remotes {
                 if peer != storage.peer_id() {
-                    if let Some(upstream) = setup.run(peer, &profile, &storage)? {
-                        term::success!(
-                            "Remote-tracking branch {} created for {}",
-                            term::format::highlight(&upstream),
-                            term::format::tertiary(fmt::peer(peer))
-                        );
+                    let mut spinner = term::spinner(&format!(
+                        "Fetching remote {}...",
+                        term::format::tertiary(fmt::peer(peer))
+                    ));
+                    match setup.run(peer, &profile, &storage) {
+                        Ok(Some(upstream)) => {
+                            spinner.message(format!(
+                                "Remote-tracking branch {} created for {}",
+                                term::format::highlight(&upstream),
+                                term::format::tertiary(fmt::peer(peer))
+                            ));
+                            spinner.finish();
+                        }
+                        Ok(None) => spinner.clear(),
+                        Err(err) => {
+                            spinner.failed();
+                            return Err(err);
+                        }
                     }
                 }
             }
@@ -118,7 +118,12 @@ pub fn clone_project(urn: Urn, seed: Option<seed::Address>) -> anyhow::Result<()
         push_self: false,
         verbose: false,
     })?;
-    let path = rad_checkout::execute(rad_checkout::Options { urn: urn.clone() })?;
+    let path = rad_checkout::execute(rad_checkout::Options {
+        urn: urn.clone(),
+        dry_run: false,
+        signing: true,
+        remotes: true,
+    })?;
 
     if let Some(seed_url) = seed.map(|s| s.url()) {
         seed::set_seed(&seed_url, seed::Scope::Local(&path))?;
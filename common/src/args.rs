@@ -1,3 +1,4 @@
+use std::env;
 use std::ffi::OsString;
 use std::str::FromStr;
 
@@ -19,6 +20,17 @@ pub enum Error {
     },
 }
 
+/// Environment variable holding the profile to use instead of the active one. This is set
+/// by the global `--profile <id>` option, and inherited by sub-commands.
+pub const PROFILE_ENV: &str = "RAD_PROFILE";
+/// Environment variable set by the global `--quiet` option, and inherited by sub-commands.
+/// When set, decorative output such as headlines, spinners and success messages is not
+/// shown.
+pub const QUIET_ENV: &str = "RAD_QUIET";
+/// Environment variable holding the level of diagnostic logs to print, eg. `debug`. Logging
+/// is off if it isn't set.
+pub const LOG_ENV: &str = "RAD_LOG";
+
 pub struct Help {
     pub name: &'static str,
     pub description: &'static str,
@@ -29,6 +41,7 @@ pub struct Help {
 pub trait Args: Sized {
     fn from_env() -> anyhow::Result<Self> {
         let args = std::env::args_os().into_iter().skip(1).collect();
+        let args = self::globals(args)?;
 
         match Self::from_args(args) {
             Ok((opts, unparsed)) => {
@@ -62,6 +75,45 @@ pub fn format(arg: lexopt::Arg) -> OsString {
     }
 }
 
+/// Extract the global options, which are accepted by all commands: `--profile <id>`, which is
+/// recorded under [`PROFILE_ENV`], and `--quiet`, which is recorded under [`QUIET_ENV`].
+/// Returns the remaining arguments.
+///
+/// Like with `rad` itself, global options are only accepted before the first positional
+/// argument, eg. an operation like `rad patch review`, since they could otherwise be the value
+/// of another option, as in `rad patch review <id> -m --quiet`.
+pub fn globals(args: Vec<OsString>) -> anyhow::Result<Vec<OsString>> {
+    let mut unparsed = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let positional = arg == "-" || !arg.to_string_lossy().starts_with('-');
+
+        if arg == "--" || positional {
+            unparsed.push(arg);
+            unparsed.extend(args);
+            break;
+        } else if arg == "--profile" {
+            let id = args
+                .next()
+                .ok_or_else(|| anyhow!("a profile id must be specified with '--profile'"))?;
+            env::set_var(PROFILE_ENV, id);
+        } else if let Some(id) = arg.to_str().and_then(|a| a.strip_prefix("--profile=")) {
+            env::set_var(PROFILE_ENV, id);
+        } else if arg == "--quiet" {
+            env::set_var(QUIET_ENV, "1");
+        } else {
+            unparsed.push(arg);
+        }
+    }
+    Ok(unparsed)
+}
+
+/// Whether decorative output should be suppressed, see [`QUIET_ENV`].
+pub fn quiet() -> bool {
+    matches!(env::var(QUIET_ENV).as_deref(), Ok(v) if !v.is_empty() && v != "0")
+}
+
 pub fn finish(unparsed: Vec<OsString>) -> anyhow::Result<()> {
     if let Some(arg) = unparsed.first() {
         return Err(anyhow::anyhow!(
@@ -71,3 +123,28 @@ pub fn finish(unparsed: Vec<OsString>) -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_globals() {
+        assert_eq!(
+            globals(args(&["--quiet", "--list"])).unwrap(),
+            args(&["--list"])
+        );
+        assert!(quiet());
+
+        // After a positional argument, they may be the values of other options.
+        let unparsed = args(&["review", "patch", "-m", "--quiet", "--profile", "id"]);
+        assert_eq!(globals(unparsed.clone()).unwrap(), unparsed);
+
+        let unparsed = args(&["--", "--quiet"]);
+        assert_eq!(globals(unparsed.clone()).unwrap(), unparsed);
+    }
+}
@@ -1,5 +1,6 @@
 pub mod issue;
 pub mod patch;
+pub mod seen;
 pub mod shared;
 
 pub use shared::*;
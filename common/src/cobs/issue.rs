@@ -188,9 +188,11 @@ impl TryFrom<Automerge> for Issue {
         // Labels.
         let mut labels = HashSet::new();
         for key in doc.keys(&labels_id) {
-            let label = Label::new(key).unwrap();
-
-            labels.insert(label);
+            // Skip labels that were written before normalization was enforced,
+            // and can't be normalized.
+            if let Ok(label) = Label::new(key) {
+                labels.insert(label);
+            }
         }
 
         let author = shared::author(author)?;
@@ -477,7 +479,7 @@ mod events {
 
                     let labels_id = tx.put_object(&issue, "labels", ObjType::Map)?;
                     for label in labels {
-                        tx.put(&labels_id, label.name().trim(), true)?;
+                        tx.put(&labels_id, label.name(), true)?;
                     }
 
                     // Nb. The top-level comment doesn't have a `replies` field.
@@ -567,7 +569,7 @@ mod events {
                     let (_, labels_id) = tx.get(&obj_id, "labels")?.unwrap();
 
                     for label in labels {
-                        tx.put(&labels_id, label.name().trim(), true)?;
+                        tx.put(&labels_id, label.name(), true)?;
                     }
                     Ok(())
                 },
@@ -1,8 +1,12 @@
 #![allow(clippy::too_many_arguments)]
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
-use std::ops::{ControlFlow, RangeInclusive};
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{ControlFlow, Deref, RangeInclusive};
+use std::panic;
 use std::str::FromStr;
+use std::thread;
 
 use automerge::{Automerge, AutomergeError, ObjType, ScalarValue, Value};
 use lazy_static::lazy_static;
@@ -10,9 +14,12 @@ use nonempty::NonEmpty;
 use serde::{Deserialize, Serialize};
 
 use librad::collaborative_objects::{
-    CollaborativeObjects, EntryContents, History, NewObjectSpec, ObjectId, TypeName,
+    CollaborativeObject, CollaborativeObjects, EntryContents, History, NewObjectSpec, ObjectId,
+    TypeName, UpdateObjectSpec,
 };
 use librad::git::identities::local::LocalIdentity;
+use librad::git::refs::Refs;
+use librad::git::storage::ReadOnly;
 use librad::git::Storage;
 use librad::git::Urn;
 use librad::paths::Paths;
@@ -22,20 +29,47 @@ use radicle_git_ext as git;
 
 use crate::cobs::shared;
 use crate::cobs::shared::*;
-use crate::project;
 
 lazy_static! {
+    /// Type name of patch COBs, for use with `librad`'s collaborative objects API.
+    ///
+    /// This is stable: it identifies all patches ever created, and won't change.
     pub static ref TYPENAME: TypeName = FromStr::from_str("xyz.radicle.patch").unwrap();
+    /// JSON schema that patch COBs are validated against.
+    ///
+    /// This may evolve with the patch format, but only in backwards-compatible ways.
     pub static ref SCHEMA: serde_json::Value =
         serde_json::from_slice(include_bytes!("patch.json")).unwrap();
 }
 
+/// Name of the blob holding the contents of a change, in the tree of a change commit of a
+/// collaborative object.
+const CHANGE_BLOB: &str = "change";
+
+/// Maximum number of threads used to load patches, see [`PatchesReadOnly::all`].
+pub const MAX_LOAD_THREADS: usize = 8;
+
 /// Identifier for a patch.
 pub type PatchId = ObjectId;
 
 /// Identifier for a revision.
 pub type RevisionId = usize;
 
+/// Reactions to the comments of a patch, with the identities that reacted, once per
+/// reaction to a comment.
+pub type Reactions = HashMap<Reaction, Vec<Urn>>;
+
+/// Normalize a patch title to a single line, as it is the subject line of the patch tag
+/// message: lines are trimmed and joined with spaces, and blank lines are dropped.
+pub fn normalize_title(title: &str) -> String {
+    title
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Create error: {0}")]
@@ -47,6 +81,24 @@ pub enum Error {
     #[error("Retrieve error: {0}")]
     Retrieve(String),
 
+    #[error("Update error: {0}")]
+    Update(String),
+
+    #[error("Remove error: {0}")]
+    Remove(String),
+
+    #[error("only the author of a patch can remove it")]
+    NotAuthor,
+
+    #[error("comment was not found")]
+    CommentNotFound,
+
+    #[error("only the author of a comment can edit or delete it")]
+    NotCommentAuthor,
+
+    #[error("patch {0} could not be loaded: {1}")]
+    Load(PatchId, String),
+
     #[error(transparent)]
     Automerge(#[from] AutomergeError),
 }
@@ -94,9 +146,11 @@ impl TryFrom<Automerge> for Patch {
         // Labels.
         let mut labels = HashSet::new();
         for key in doc.keys(&labels_id) {
-            let label = Label::new(key).unwrap();
-
-            labels.insert(label);
+            // Skip labels that were written before normalization was enforced,
+            // and can't be normalized.
+            if let Ok(label) = Label::new(key) {
+                labels.insert(label);
+            }
         }
 
         let author = shared::author(author)?;
@@ -119,49 +173,544 @@ impl TryFrom<Automerge> for Patch {
     }
 }
 
-impl TryFrom<&History> for Patch {
-    type Error = anyhow::Error;
+impl Patch {
+    /// Latest revision of the patch. This is the one that would be merged.
+    pub fn latest_revision(&self) -> &Revision {
+        self.revisions.last()
+    }
 
-    fn try_from(history: &History) -> Result<Self, Self::Error> {
-        let doc = history.traverse(Automerge::new(), |mut doc, entry| {
-            match entry.contents() {
-                EntryContents::Automerge(bytes) => {
-                    match automerge::Change::from_bytes(bytes.clone()) {
-                        Ok(change) => {
-                            doc.apply_changes([change]).ok();
-                        }
-                        Err(_err) => {
-                            // Ignore
-                        }
+    /// Whether the given identity reviewed the latest revision of the patch.
+    pub fn is_reviewed_by(&self, reviewer: &Urn) -> bool {
+        self.latest_revision().reviews.contains_key(reviewer)
+    }
+
+    /// Current head of the target branch in the given working copy, as found under
+    /// `rad/<target>`. Returns `None` if the branch doesn't exist there.
+    pub fn target_oid(
+        &self,
+        repo: &git2::Repository,
+    ) -> Result<Option<git2::Oid>, crate::patch::Error> {
+        crate::patch::branch_head(repo, &self.target.to_string())
+    }
+
+    /// Number of revisions of the patch. There is always at least one.
+    pub fn revision_count(&self) -> usize {
+        self.revisions.len()
+    }
+
+    /// Time of the latest comment or review on any revision of the patch, if there
+    /// are any.
+    pub fn last_activity(&self) -> Option<Timestamp> {
+        self.revisions
+            .iter()
+            .flat_map(|revision| {
+                let comments = revision.discussion.iter().flat_map(|comment| {
+                    std::iter::once(comment.timestamp)
+                        .chain(comment.replies.iter().map(|reply| reply.timestamp))
+                });
+                let reviews = revision.reviews.values().map(|review| review.timestamp);
+
+                comments.chain(reviews)
+            })
+            .max()
+    }
+
+    /// Construct a patch from the raw automerge changes of its history.
+    ///
+    /// Changes that can't be decoded or applied are skipped, and reported with a warning,
+    /// since the resulting patch may be incomplete. If the patch object itself is missing
+    /// as a result, an error is returned.
+    fn from_changes<'a>(
+        changes: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Result<Self, anyhow::Error> {
+        let mut doc = Automerge::new();
+        let mut failed = 0;
+
+        for bytes in changes {
+            match automerge::Change::from_bytes(bytes.to_vec()) {
+                Ok(change) => {
+                    if doc.apply_changes([change]).is_err() {
+                        failed += 1;
                     }
                 }
+                Err(_) => {
+                    failed += 1;
+                }
             }
-            ControlFlow::Continue(doc)
-        });
+        }
+        if failed > 0 {
+            log::warn!("{} patch change(s) could not be applied", failed);
+        }
+        if doc.get(automerge::ObjId::Root, "patch")?.is_none() {
+            anyhow::bail!(
+                "patch object not found in history ({} change(s) could not be applied)",
+                failed
+            );
+        }
         let patch = Patch::try_from(doc)?;
 
         Ok(patch)
     }
 }
 
+impl TryFrom<&History> for Patch {
+    type Error = anyhow::Error;
+
+    fn try_from(history: &History) -> Result<Self, Self::Error> {
+        let changes = changes(history);
+
+        Patch::from_changes(changes.iter().map(|bytes| bytes.as_slice()))
+    }
+}
+
+/// Raw changes of a patch history, in traversal order.
+fn changes(history: &History) -> Vec<Vec<u8>> {
+    let changes = history.traverse(Vec::new(), |mut changes, entry| {
+        match entry.contents() {
+            EntryContents::Automerge(bytes) => {
+                changes.push(bytes.clone());
+            }
+        }
+        ControlFlow::Continue(changes)
+    });
+    log::trace!("traversed {} patch change(s)", changes.len());
+
+    changes
+}
+
+/// Construct a patch from the raw changes of its history, see [`Patch::from_changes`].
+/// Malformed patches can panic while being constructed; such panics are caught, so that
+/// they only fail the one patch.
+fn load(id: PatchId, changes: &[Vec<u8>]) -> Result<(PatchId, Patch), Error> {
+    let result = panic::catch_unwind(|| Patch::from_changes(changes.iter().map(|c| c.as_slice())));
+
+    match result {
+        Ok(Ok(patch)) => Ok((id, patch)),
+        Ok(Err(err)) => Err(Error::Load(id, err.to_string())),
+        Err(_) => Err(Error::Load(id, String::from("malformed patch"))),
+    }
+}
+
+/// Check whether a patch of a project exists, ie. whether this peer or one of the peers it
+/// replicated the patch from has a ref for it. The patch object itself isn't loaded, so
+/// this works with [`ReadOnly`] storage.
+pub fn exists(storage: &ReadOnly, project: &Urn, id: &PatchId) -> Result<bool, Error> {
+    let repo =
+        git2::Repository::open_bare(storage.path()).map_err(|e| Error::Retrieve(e.to_string()))?;
+    let refs = patch_refs(&repo, project, Some(id)).map_err(|e| Error::Retrieve(e.to_string()))?;
+
+    Ok(!refs.is_empty())
+}
+
+/// Patches of a project, as returned by [`PatchesReadOnly::all`].
+#[derive(Debug, Default)]
+pub struct Loaded {
+    /// Patches that were constructed from their history, oldest first.
+    pub patches: Vec<(PatchId, Patch)>,
+    /// Ids of the patches that couldn't be constructed from their history.
+    pub failed: Vec<PatchId>,
+}
+
+impl FromIterator<Result<(PatchId, Patch), Error>> for Loaded {
+    fn from_iter<I: IntoIterator<Item = Result<(PatchId, Patch), Error>>>(iter: I) -> Self {
+        let mut loaded = Self::default();
+
+        for result in iter {
+            match result {
+                Ok(patch) => loaded.patches.push(patch),
+                Err(Error::Load(id, err)) => {
+                    log::warn!("patch {} could not be loaded: {}", id, err);
+                    loaded.failed.push(id);
+                }
+                Err(err) => log::warn!("patch could not be loaded: {}", err),
+            }
+        }
+        loaded.patches.sort_by_key(|(_, p)| p.timestamp);
+        loaded
+    }
+}
+
+/// A change made to a patch, as found in its history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Id of the entry. This is the id of the commit holding the change.
+    pub id: String,
+    /// Author of the change, if known.
+    pub author: Option<Urn>,
+    /// Description of the change, eg. "Update patch".
+    pub message: String,
+}
+
+/// The state of the histories of a project's patches at some point, used to detect the
+/// patches that changed since, see [`PatchesReadOnly::changed_since`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Tips of the patch refs of each peer, including this one, by patch id.
+    tips: HashMap<String, BTreeSet<String>>,
+}
+
+impl Snapshot {
+    /// Tips of the refs of the given patch, if it has any.
+    pub fn tips(&self, id: &PatchId) -> Option<&BTreeSet<String>> {
+        self.tips.get(&id.to_string())
+    }
+}
+
+/// Read-only access to patches. Doesn't require a local identity, nor the signing key:
+/// patches are read from their refs and change commits in [`ReadOnly`] storage.
+///
+/// Nb. `librad`'s collaborative objects can only be read through signing [`Storage`], which
+/// also verifies the signatures of the changes. Changes read here aren't verified; patches
+/// are only ever updated through [`Patches`], which reads them through `librad`.
+pub struct PatchesReadOnly<'a> {
+    storage: &'a ReadOnly,
+    repo: git2::Repository,
+}
+
+impl<'a> PatchesReadOnly<'a> {
+    pub fn new(storage: &'a ReadOnly) -> Result<Self, Error> {
+        let repo = git2::Repository::open_bare(storage.path())
+            .map_err(|e| Error::Retrieve(e.to_string()))?;
+
+        Ok(Self { storage, repo })
+    }
+
+    pub fn get(&self, project: &Urn, id: &PatchId) -> Result<Option<Patch>, Error> {
+        log::debug!("retrieving patch {} of {}", id, project);
+
+        match self.changes(project, id)? {
+            Some(changes) => {
+                let (_, patch) = load(*id, &changes)?;
+                Ok(Some(patch))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Iterate over the patches of a project, in no particular order. Patches that can't be
+    /// read or constructed yield [`Error::Load`], without ending the iteration.
+    ///
+    /// Nb. The changes of the patches are all read from storage before iterating. Only
+    /// constructing the patches from their changes, which is the expensive part, happens
+    /// as the iterator advances, so that eg. finding a single patch doesn't require
+    /// constructing all of them.
+    pub fn iter(
+        &self,
+        project: &Urn,
+    ) -> Result<impl Iterator<Item = Result<(PatchId, Patch), Error>>, Error> {
+        let patches = self.changes_all(project)?;
+
+        Ok(patches
+            .into_iter()
+            .map(|result| result.and_then(|(id, changes)| load(id, &changes))))
+    }
+
+    /// Get all patches of a project, oldest first, along with the ids of the patches that
+    /// couldn't be constructed from their history, eg. because peers diverged. A single
+    /// such patch doesn't prevent listing the others.
+    ///
+    /// This is the same as collecting [`PatchesReadOnly::iter`] into [`Loaded`], except
+    /// that patches are constructed on up to [`MAX_LOAD_THREADS`] threads, bounded by the
+    /// available parallelism.
+    pub fn all(&self, project: &Urn) -> Result<Loaded, Error> {
+        let mut patches = Vec::new();
+        let mut unreadable: Vec<Result<(PatchId, Patch), Error>> = Vec::new();
+        for result in self.changes_all(project)? {
+            match result {
+                Ok(patch) => patches.push(patch),
+                Err(err) => unreadable.push(Err(err)),
+            }
+        }
+
+        // Reading the changes is cheap, but applying them isn't, so only the latter is
+        // spread across threads.
+        let threads = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_LOAD_THREADS)
+            .min(patches.len())
+            .max(1);
+        log::debug!(
+            "loading {} patch(es) of {} on {} thread(s)",
+            patches.len(),
+            project,
+            threads
+        );
+        let mut batches = vec![Vec::new(); threads];
+        for (i, patch) in patches.into_iter().enumerate() {
+            batches[i % threads].push(patch);
+        }
+        let handles = batches
+            .into_iter()
+            .map(|batch: Vec<(PatchId, Vec<Vec<u8>>)>| {
+                thread::spawn(move || {
+                    batch
+                        .into_iter()
+                        .map(|(id, changes)| load(id, &changes))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Nb. Each patch is loaded on its own, so a thread can't fail as a whole.
+        let loaded = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("patches are loaded without panicking"))
+            .chain(unreadable)
+            .collect::<Loaded>();
+        log::debug!(
+            "loaded {} patch(es) of {}, {} failed",
+            loaded.patches.len(),
+            project,
+            loaded.failed.len()
+        );
+
+        Ok(loaded)
+    }
+
+    /// Get the reactions to all comments of a patch, across revisions: top-level comments,
+    /// discussions and reviews. Returns `None` if the patch doesn't exist.
+    ///
+    /// Only the patch COB is read, so this doesn't require the patch commits.
+    pub fn reactions(&self, project: &Urn, id: &PatchId) -> Result<Option<Reactions>, Error> {
+        let doc = match self.get_raw(project, id)? {
+            Some(doc) => doc,
+            None => return Ok(None),
+        };
+        let reactions = lookup::reactions(&doc)?;
+
+        Ok(Some(reactions))
+    }
+
+    /// Get the patches of a project that were created or changed since the given snapshot,
+    /// along with a new snapshot to detect later changes with. With an empty snapshot, all
+    /// patches are returned.
+    ///
+    /// Only the tips of the patch refs are compared, without traversing the patch histories
+    /// or constructing the patches, so this is cheap enough to poll. It is meant as the
+    /// building block of user interfaces that refresh when patches change.
+    pub fn changed_since(
+        &self,
+        project: &Urn,
+        since: &Snapshot,
+    ) -> Result<(Vec<PatchId>, Snapshot), Error> {
+        let snapshot = self.snapshot(project)?;
+
+        let mut changed = Vec::new();
+        for (id, tips) in &snapshot.tips {
+            if since.tips.get(id) == Some(tips) {
+                continue;
+            }
+            match git2::Oid::from_str(id) {
+                Ok(oid) => changed.push(PatchId::from(oid)),
+                Err(_) => log::warn!("skipping patch ref with invalid id {:?}", id),
+            }
+        }
+        Ok((changed, snapshot))
+    }
+
+    /// Get the current state of the histories of a project's patches, ie. the tips of their
+    /// refs, without traversing the histories.
+    pub fn snapshot(&self, project: &Urn) -> Result<Snapshot, Error> {
+        let refs = patch_refs(&self.repo, project, None).map_err(|e| Error::List(e.to_string()))?;
+        let tips = refs
+            .into_iter()
+            .map(|(id, tips)| (id, tips.iter().map(|tip| tip.to_string()).collect()))
+            .collect();
+
+        Ok(Snapshot { tips })
+    }
+
+    /// Check whether a patch exists, without loading it, see [`exists`].
+    pub fn exists(&self, project: &Urn, id: &PatchId) -> Result<bool, Error> {
+        exists(self.storage, project, id)
+    }
+
+    pub fn get_raw(&self, project: &Urn, id: &PatchId) -> Result<Option<Automerge>, Error> {
+        log::debug!("retrieving raw patch {} of {}", id, project);
+
+        match self.changes(project, id)? {
+            Some(changes) => Ok(Some(Automerge::load(&changes.concat())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the raw changes of a patch, or `None` if it doesn't exist.
+    fn changes(&self, project: &Urn, id: &PatchId) -> Result<Option<Vec<Vec<u8>>>, Error> {
+        let mut refs = patch_refs(&self.repo, project, Some(id))
+            .map_err(|e| Error::Retrieve(e.to_string()))?;
+
+        match refs.remove(&id.to_string()) {
+            Some(tips) => read_changes(&self.repo, &tips)
+                .map(Some)
+                .map_err(|e| Error::Retrieve(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the raw changes of all patches of a project. Patches whose changes can't be
+    /// read are returned as [`Error::Load`].
+    fn changes_all(
+        &self,
+        project: &Urn,
+    ) -> Result<Vec<Result<(PatchId, Vec<Vec<u8>>), Error>>, Error> {
+        let refs = patch_refs(&self.repo, project, None).map_err(|e| Error::List(e.to_string()))?;
+        let mut patches = Vec::with_capacity(refs.len());
+
+        for (id, tips) in refs {
+            let id = match git2::Oid::from_str(&id) {
+                Ok(oid) => PatchId::from(oid),
+                Err(_) => {
+                    log::warn!("skipping patch ref with invalid id {:?}", id);
+                    continue;
+                }
+            };
+            patches.push(
+                read_changes(&self.repo, &tips)
+                    .map(|changes| (id, changes))
+                    .map_err(|e| Error::Load(id, e.to_string())),
+            );
+        }
+        Ok(patches)
+    }
+}
+
+/// Tips of the patch refs of this peer, and of the peers it replicated patches from, by
+/// patch id. If an `id` is given, only the refs of that patch are read.
+fn patch_refs(
+    repo: &git2::Repository,
+    project: &Urn,
+    id: Option<&PatchId>,
+) -> Result<HashMap<String, BTreeSet<git2::Oid>>, git2::Error> {
+    let namespace = format!("refs/namespaces/{}/refs", project.encode_id());
+    let id = id.map_or_else(|| String::from("*"), |id| id.to_string());
+    let globs = [
+        format!("{}/cobs/{}/{}", namespace, *TYPENAME, id),
+        format!("{}/remotes/*/cobs/{}/{}", namespace, *TYPENAME, id),
+    ];
+
+    let mut tips = HashMap::<_, BTreeSet<_>>::new();
+    for glob in &globs {
+        for r in repo.references_glob(glob)? {
+            let r = r?;
+            let (name, tip) = match (r.name(), r.target()) {
+                (Some(name), Some(tip)) => (name, tip),
+                _ => continue,
+            };
+            if let Some((_, id)) = name.rsplit_once('/') {
+                tips.entry(id.to_owned()).or_default().insert(tip);
+            }
+        }
+    }
+    Ok(tips)
+}
+
+/// Read the raw changes of a collaborative object from its change commits, starting at the
+/// tips of its refs. Changes are returned in the order they can be applied in, ie. parents
+/// first. Commits that aren't changes, eg. the identity commits changes refer to, are
+/// skipped.
+fn read_changes(
+    repo: &git2::Repository,
+    tips: &BTreeSet<git2::Oid>,
+) -> Result<Vec<Vec<u8>>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    for tip in tips {
+        walk.push(*tip)?;
+    }
+
+    let mut changes = Vec::new();
+    for oid in walk {
+        let tree = repo.find_commit(oid?)?.tree()?;
+
+        if let Some(entry) = tree.get_name(CHANGE_BLOB) {
+            changes.push(entry.to_object(repo)?.peel_to_blob()?.content().to_vec());
+        }
+    }
+    Ok(changes)
+}
+
 pub struct Patches<'a> {
+    reader: PatchesReadOnly<'a>,
     store: CollaborativeObjects<'a>,
+    storage: &'a Storage,
     whoami: LocalIdentity,
     peer_id: PeerId,
 }
 
+impl<'a> Deref for Patches<'a> {
+    type Target = PatchesReadOnly<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reader
+    }
+}
+
 impl<'a> Patches<'a> {
     pub fn new(whoami: LocalIdentity, paths: &Paths, storage: &'a Storage) -> Result<Self, Error> {
+        let reader = PatchesReadOnly::new(storage.as_ref())?;
         let store = storage.collaborative_objects(Some(paths.cob_cache_dir().to_path_buf()));
         let peer_id = *storage.peer_id();
 
         Ok(Self {
+            reader,
             store,
+            storage,
             whoami,
             peer_id,
         })
     }
 
+    /// Get the underlying collaborative object of a patch, eg. to inspect its history. Unlike
+    /// the patches read through [`PatchesReadOnly`], its changes are verified.
+    pub fn get_object(
+        &self,
+        project: &Urn,
+        id: &PatchId,
+    ) -> Result<Option<CollaborativeObject>, Error> {
+        self.store
+            .retrieve(project, &TYPENAME, id)
+            .map_err(|e| Error::Retrieve(e.to_string()))
+    }
+
+    /// Get a patch as an automerge document, to update it. Unlike
+    /// [`PatchesReadOnly::get_raw`], its changes are verified.
+    pub fn get_raw(&self, project: &Urn, id: &PatchId) -> Result<Option<Automerge>, Error> {
+        log::debug!("retrieving patch {} of {} for update", id, project);
+        let cob = match self.get_object(project, id)? {
+            Some(cob) => cob,
+            None => return Ok(None),
+        };
+        let doc = Automerge::load(&changes(cob.history()).concat())?;
+
+        Ok(Some(doc))
+    }
+
+    /// Get the history of a patch, ie. the changes made to it, such as updates and reviews,
+    /// in the order they were applied. Returns `None` if the patch doesn't exist.
+    pub fn history(&self, project: &Urn, id: &PatchId) -> Result<Option<Vec<HistoryEntry>>, Error> {
+        let cob = match self.get_object(project, id)? {
+            Some(cob) => cob,
+            None => return Ok(None),
+        };
+        let entries = cob.history().traverse(Vec::new(), |mut entries, entry| {
+            let message = match entry.contents() {
+                EntryContents::Automerge(bytes) => automerge::Change::from_bytes(bytes.clone())
+                    .ok()
+                    .and_then(|change| change.message()),
+            };
+            entries.push(HistoryEntry {
+                id: entry.id().to_string(),
+                author: entry.author().clone(),
+                message: message.unwrap_or_default(),
+            });
+            ControlFlow::Continue(entries)
+        });
+
+        Ok(Some(entries))
+    }
+
+    /// Create a patch. If an `author` is given, the patch is attributed to them,
+    /// while this peer is recorded as the one who published it.
     pub fn create(
         &self,
         project: &Urn,
@@ -169,9 +718,11 @@ impl<'a> Patches<'a> {
         description: &str,
         target: &git::OneLevel,
         commit: &git::Oid,
+        base: Option<&git::Oid>,
         labels: &[Label],
+        author: Option<&Urn>,
     ) -> Result<PatchId, Error> {
-        let author = self.whoami.urn();
+        let author = author.cloned().unwrap_or_else(|| self.whoami.urn());
         let timestamp = Timestamp::now();
         let history = events::create(
             &author,
@@ -180,6 +731,7 @@ impl<'a> Patches<'a> {
             description,
             target,
             commit,
+            base,
             timestamp,
             labels,
         )?;
@@ -187,34 +739,307 @@ impl<'a> Patches<'a> {
         cobs::create(history, project, &self.whoami, &self.store)
     }
 
-    pub fn get(&self, project: &Urn, id: &PatchId) -> Result<Option<Patch>, Error> {
-        let cob = self
+    /// Publish a new revision of a patch. The revision is authored by the local identity,
+    /// which needn't be the author of the patch, eg. when relaying a contribution.
+    pub fn update(
+        &self,
+        project: &Urn,
+        patch_id: &PatchId,
+        comment: &str,
+        commit: &git::Oid,
+        base: Option<&git::Oid>,
+    ) -> Result<RevisionId, Error> {
+        let author = self.whoami.urn();
+        let mut patch = self
+            .get_raw(project, patch_id)?
+            .ok_or_else(|| Error::Retrieve(format!("patch {} was not found", patch_id)))?;
+        let timestamp = Timestamp::now();
+        let (revision, changes) = events::update(
+            &mut patch,
+            &author,
+            &self.peer_id,
+            comment,
+            commit,
+            base,
+            timestamp,
+        )?;
+        let _cob = self
             .store
-            .retrieve(project, &TYPENAME, id)
-            .map_err(|e| Error::Retrieve(e.to_string()))?;
+            .update(
+                &self.whoami,
+                project,
+                UpdateObjectSpec {
+                    object_id: *patch_id,
+                    typename: TYPENAME.clone(),
+                    message: Some("Update patch".to_owned()),
+                    changes,
+                },
+            )
+            .map_err(|e| Error::Update(e.to_string()))?;
 
-        if let Some(cob) = cob {
-            let patch = Patch::try_from(cob.history()).unwrap();
-            Ok(Some(patch))
-        } else {
-            Ok(None)
+        Ok(revision)
+    }
+
+    /// Remove a patch from this peer's storage. Only the author of the patch may remove it.
+    ///
+    /// Nb. Peers who have already replicated the patch keep their copy of it, so the removal
+    /// may not propagate to them.
+    pub fn remove(&self, project: &Urn, patch_id: &PatchId) -> Result<(), Error> {
+        let patch = self
+            .get(project, patch_id)?
+            .ok_or_else(|| Error::Remove(format!("patch {} was not found", patch_id)))?;
+
+        if patch.author.urn() != &self.whoami.urn() {
+            return Err(Error::NotAuthor);
         }
+
+        // Nb. The collaborative objects API has no way of removing an object, so we remove
+        // its ref, and update our signed refs so that it is no longer advertised.
+        let name = format!(
+            "refs/namespaces/{}/refs/cobs/{}/{}",
+            project.encode_id(),
+            *TYPENAME,
+            patch_id
+        );
+        let repo = git2::Repository::open_bare(self.storage.path())
+            .map_err(|e| Error::Remove(e.to_string()))?;
+        repo.find_reference(&name)
+            .and_then(|mut reference| reference.delete())
+            .map_err(|e| Error::Remove(e.to_string()))?;
+
+        Refs::update(self.storage, project).map_err(|e| Error::Remove(e.to_string()))?;
+
+        Ok(())
     }
 
-    pub fn all(&self, project: &Urn) -> Result<Vec<(PatchId, Patch)>, Error> {
-        let cobs = self
+    pub fn review(
+        &self,
+        project: &Urn,
+        patch_id: &PatchId,
+        revision: RevisionId,
+        verdict: Verdict,
+        comment: &str,
+    ) -> Result<(), Error> {
+        let author = self.whoami.urn();
+        let mut patch = self
+            .get_raw(project, patch_id)?
+            .ok_or_else(|| Error::Retrieve(format!("patch {} was not found", patch_id)))?;
+        let timestamp = Timestamp::now();
+        let changes = events::review(&mut patch, revision, &author, verdict, comment, timestamp)?;
+        let _cob = self
             .store
-            .list(project, &TYPENAME)
-            .map_err(|e| Error::List(e.to_string()))?;
+            .update(
+                &self.whoami,
+                project,
+                UpdateObjectSpec {
+                    object_id: *patch_id,
+                    typename: TYPENAME.clone(),
+                    message: Some("Review patch".to_owned()),
+                    changes,
+                },
+            )
+            .map_err(|e| Error::Update(e.to_string()))?;
 
-        let mut patches = Vec::new();
-        for cob in cobs {
-            let patch: Result<Patch, _> = cob.history().try_into();
-            patches.push((*cob.id(), patch.unwrap()));
+        Ok(())
+    }
+
+    /// Comment on a patch revision. The comment is added to the revision's discussion, and
+    /// its id is returned, see [`Patches::react`].
+    pub fn comment(
+        &self,
+        project: &Urn,
+        patch_id: &PatchId,
+        revision: RevisionId,
+        body: &str,
+    ) -> Result<CommentId, Error> {
+        let author = self.whoami.urn();
+        let mut patch = self
+            .get_raw(project, patch_id)?
+            .ok_or_else(|| Error::Retrieve(format!("patch {} was not found", patch_id)))?;
+        let timestamp = Timestamp::now();
+        let (comment_id, changes) =
+            events::comment(&mut patch, revision, &author, body, timestamp)?;
+        let _cob = self
+            .store
+            .update(
+                &self.whoami,
+                project,
+                UpdateObjectSpec {
+                    object_id: *patch_id,
+                    typename: TYPENAME.clone(),
+                    message: Some("Add comment".to_owned()),
+                    changes,
+                },
+            )
+            .map_err(|e| Error::Update(e.to_string()))?;
+
+        Ok(comment_id)
+    }
+
+    /// Replace the body of a comment of a patch revision. Only the author of the comment
+    /// may edit it, and deleted comments can't be edited.
+    pub fn edit_comment(
+        &self,
+        project: &Urn,
+        patch_id: &PatchId,
+        revision: RevisionId,
+        comment_id: CommentId,
+        body: &str,
+    ) -> Result<(), Error> {
+        self.authorize_comment(project, patch_id, revision, comment_id)?;
+
+        let mut patch = self
+            .get_raw(project, patch_id)?
+            .ok_or_else(|| Error::Retrieve(format!("patch {} was not found", patch_id)))?;
+        let changes = events::edit_comment(&mut patch, revision, comment_id, Some(body))?;
+        let _cob = self
+            .store
+            .update(
+                &self.whoami,
+                project,
+                UpdateObjectSpec {
+                    object_id: *patch_id,
+                    typename: TYPENAME.clone(),
+                    message: Some("Edit comment".to_owned()),
+                    changes,
+                },
+            )
+            .map_err(|e| Error::Update(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Delete a comment of a patch revision, leaving a tombstone in its place. Only the
+    /// author of the comment may delete it.
+    pub fn delete_comment(
+        &self,
+        project: &Urn,
+        patch_id: &PatchId,
+        revision: RevisionId,
+        comment_id: CommentId,
+    ) -> Result<(), Error> {
+        self.authorize_comment(project, patch_id, revision, comment_id)?;
+
+        let mut patch = self
+            .get_raw(project, patch_id)?
+            .ok_or_else(|| Error::Retrieve(format!("patch {} was not found", patch_id)))?;
+        let changes = events::edit_comment(&mut patch, revision, comment_id, None)?;
+        let _cob = self
+            .store
+            .update(
+                &self.whoami,
+                project,
+                UpdateObjectSpec {
+                    object_id: *patch_id,
+                    typename: TYPENAME.clone(),
+                    message: Some("Delete comment".to_owned()),
+                    changes,
+                },
+            )
+            .map_err(|e| Error::Update(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Check that a comment exists, isn't deleted, and was authored by the local identity.
+    fn authorize_comment(
+        &self,
+        project: &Urn,
+        patch_id: &PatchId,
+        revision: RevisionId,
+        comment_id: CommentId,
+    ) -> Result<(), Error> {
+        let patch = self
+            .get(project, patch_id)?
+            .ok_or_else(|| Error::Retrieve(format!("patch {} was not found", patch_id)))?;
+        let revision = patch
+            .revisions
+            .get(revision)
+            .ok_or(Error::CommentNotFound)?;
+        let (author, deleted) = match usize::from(comment_id) {
+            0 => (revision.comment.author.urn(), revision.comment.deleted),
+            ix => revision
+                .discussion
+                .get(ix - 1)
+                .map(|comment| (comment.author.urn(), comment.deleted))
+                .ok_or(Error::CommentNotFound)?,
+        };
+        if deleted {
+            return Err(Error::CommentNotFound);
         }
-        patches.sort_by_key(|(_, p)| p.timestamp);
+        if author != &self.whoami.urn() {
+            return Err(Error::NotCommentAuthor);
+        }
+        Ok(())
+    }
 
-        Ok(patches)
+    /// React to a comment of a patch revision. The root comment is the revision's
+    /// top-level comment, and the others are those of its discussion, in order.
+    pub fn react(
+        &self,
+        project: &Urn,
+        patch_id: &PatchId,
+        revision: RevisionId,
+        comment_id: CommentId,
+        reaction: Reaction,
+    ) -> Result<(), Error> {
+        let author = self.whoami.urn();
+        let mut patch = self
+            .get_raw(project, patch_id)?
+            .ok_or_else(|| Error::Retrieve(format!("patch {} was not found", patch_id)))?;
+        let changes = events::react(&mut patch, revision, comment_id, &author, &[reaction])?;
+        let _cob = self
+            .store
+            .update(
+                &self.whoami,
+                project,
+                UpdateObjectSpec {
+                    object_id: *patch_id,
+                    typename: TYPENAME.clone(),
+                    message: Some("React".to_owned()),
+                    changes,
+                },
+            )
+            .map_err(|e| Error::Update(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Record the merge of a patch revision into this peer's repository, at the given
+    /// base branch commit.
+    pub fn merge(
+        &self,
+        project: &Urn,
+        patch_id: &PatchId,
+        revision: RevisionId,
+        commit: git::Oid,
+    ) -> Result<Merge, Error> {
+        let timestamp = Timestamp::now();
+        let merge = Merge {
+            peer: self.peer_id,
+            revision,
+            commit,
+            timestamp,
+        };
+        let mut patch = self
+            .get_raw(project, patch_id)?
+            .ok_or_else(|| Error::Retrieve(format!("patch {} was not found", patch_id)))?;
+        let changes = events::merge(&mut patch, revision, &self.peer_id, commit, timestamp)?;
+        let _cob = self
+            .store
+            .update(
+                &self.whoami,
+                project,
+                UpdateObjectSpec {
+                    object_id: *patch_id,
+                    typename: TYPENAME.clone(),
+                    message: Some("Merge revision".to_owned()),
+                    changes,
+                },
+            )
+            .map_err(|e| Error::Update(e.to_string()))?;
+
+        Ok(merge)
     }
 }
 
@@ -227,31 +1052,58 @@ pub enum State {
     // TODO: Do we need a "rejected", or is it enough to have "closed" with a rejected review?
 }
 
-impl From<State> for ScalarValue {
-    fn from(state: State) -> Self {
-        match state {
-            State::Open => ScalarValue::from("open"),
-            State::Draft => ScalarValue::from("draft"),
-            State::Closed => ScalarValue::from("closed"),
-        }
-    }
+/// Error parsing the name of a patch state.
+#[derive(Debug, thiserror::Error)]
+pub enum StateError {
+    #[error("invalid patch state '{0}'")]
+    Invalid(String),
 }
 
-impl<'a> TryFrom<Value<'a>> for State {
-    type Error = &'static str;
-
-    fn try_from(value: Value) -> Result<Self, Self::Error> {
-        let state = value.to_str().ok_or("value isn't a string")?;
-
-        match state {
-            "open" => Ok(Self::Open),
-            "draft" => Ok(Self::Draft),
-            "closed" => Ok(Self::Closed),
-            _ => Err("invalid state name"),
+impl State {
+    /// Name of the state, as stored in the patch COB.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Draft => "draft",
+            Self::Closed => "closed",
         }
     }
 }
 
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for State {
+    type Err = StateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Open, Self::Draft, Self::Closed]
+            .iter()
+            .copied()
+            .find(|state| state.as_str() == s)
+            .ok_or_else(|| StateError::Invalid(s.to_owned()))
+    }
+}
+
+impl From<State> for ScalarValue {
+    fn from(state: State) -> Self {
+        ScalarValue::from(state.as_str())
+    }
+}
+
+impl<'a> TryFrom<Value<'a>> for State {
+    type Error = &'static str;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let state = value.to_str().ok_or("value isn't a string")?;
+
+        state.parse().map_err(|_| "invalid state name")
+    }
+}
+
 /// A patch revision.
 #[derive(Debug, Clone, Serialize)]
 pub struct Revision {
@@ -264,6 +1116,9 @@ pub struct Revision {
     pub version: usize,
     /// Reference to the Git object containing the code.
     pub commit: git::Oid,
+    /// Commit the code is based on, eg. the merge-base with the target branch, if known.
+    /// Together with `commit`, this is the range of changes proposed by the revision.
+    pub base: Option<git::Oid>,
     /// "Cover letter" for this changeset.
     pub comment: Comment,
     /// Discussion around this revision.
@@ -279,8 +1134,8 @@ pub struct Revision {
 /// A merged patch revision.
 #[derive(Debug, Clone, Serialize)]
 pub struct Merge {
-    /// Peer information of repository that this patch was merged into.
-    pub peer: project::PeerInfo,
+    /// Peer of the repository that this patch was merged into.
+    pub peer: PeerId,
     /// Revision that was merged.
     pub revision: RevisionId,
     /// Base branch commit that contains the revision.
@@ -290,7 +1145,7 @@ pub struct Merge {
 }
 
 /// A patch review verdict.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Verdict {
     /// Accept patch.
@@ -301,6 +1156,26 @@ pub enum Verdict {
     Pass,
 }
 
+impl Verdict {
+    /// All verdicts, in the order they are offered.
+    pub const ALL: [Verdict; 3] = [Self::Accept, Self::Reject, Self::Pass];
+
+    /// Name of the verdict, as stored in the patch COB.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accept => "accept",
+            Self::Reject => "reject",
+            Self::Pass => "pass",
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl From<Verdict> for ScalarValue {
     fn from(verdict: Verdict) -> Self {
         let s = serde_json::to_string(&verdict).unwrap(); // Cannot fail.
@@ -365,13 +1240,15 @@ mod lookup {
         let (_, revision_id) = doc.get(&revisions_id, ix)?.unwrap();
         let (_, comment_id) = doc.get(&revision_id, "comment")?.unwrap();
         let (_, discussion_id) = doc.get(&revision_id, "discussion")?.unwrap();
-        let (_, _reviews_id) = doc.get(&revision_id, "reviews")?.unwrap();
-        let (_, _merges_id) = doc.get(&revision_id, "merges")?.unwrap();
+        let (_, reviews_id) = doc.get(&revision_id, "reviews")?.unwrap();
+        let (_, merges_id) = doc.get(&revision_id, "merges")?.unwrap();
         let (author, _) = doc.get(&revision_id, "author")?.unwrap();
         let (peer, _) = doc.get(&revision_id, "peer")?.unwrap();
         let (commit, _) = doc.get(&revision_id, "commit")?.unwrap();
         let (version, _) = doc.get(&revision_id, "version")?.unwrap();
         let (timestamp, _) = doc.get(&revision_id, "timestamp")?.unwrap();
+        // Revisions created before the base was recorded don't have one.
+        let base = doc.get(&revision_id, "base")?;
 
         // Top-level comment.
         let comment = shared::lookup::comment(doc, &comment_id)?;
@@ -385,12 +1262,29 @@ mod lookup {
             discussion.push(comment);
         }
 
+        // Reviews, keyed by reviewer.
+        let mut reviews = HashMap::new();
+        for key in doc.keys(&reviews_id) {
+            let (_, review_id) = doc.get(&reviews_id, key)?.unwrap();
+            let review = lookup::review(doc, &review_id)?;
+
+            reviews.insert(review.author.urn().clone(), review);
+        }
+
+        // Merges.
+        let mut merges = Vec::new();
+        for i in 0..doc.length(&merges_id) {
+            let (_, merge_id) = doc.get(&merges_id, i as usize)?.unwrap();
+            let merge = lookup::merge(doc, &merge_id)?;
+
+            merges.push(merge);
+        }
+
         let author = lookup::author(author)?;
         let peer = PeerId::from_str(peer.to_str().unwrap()).unwrap();
         let version = version.to_u64().unwrap() as usize;
         let commit = commit.to_str().unwrap().try_into().unwrap();
-        let reviews = HashMap::new();
-        let merges = Vec::new();
+        let base = base.map(|(base, _)| base.to_str().unwrap().try_into().unwrap());
         let timestamp = Timestamp::try_from(timestamp).unwrap();
 
         assert_eq!(version, ix);
@@ -400,6 +1294,7 @@ mod lookup {
             peer,
             version,
             commit,
+            base,
             comment,
             discussion,
             reviews,
@@ -407,6 +1302,86 @@ mod lookup {
             timestamp,
         })
     }
+
+    pub fn reactions(doc: &Automerge) -> Result<Reactions, AutomergeError> {
+        let (_, obj_id) = doc.get(automerge::ObjId::Root, "patch")?.unwrap();
+        let (_, revisions_id) = doc.get(&obj_id, "revisions")?.unwrap();
+
+        let mut comments = Vec::new();
+        for i in 0..doc.length(&revisions_id) {
+            let (_, revision_id) = doc.get(&revisions_id, i as usize)?.unwrap();
+            let (_, comment_id) = doc.get(&revision_id, "comment")?.unwrap();
+            let (_, discussion_id) = doc.get(&revision_id, "discussion")?.unwrap();
+            let (_, reviews_id) = doc.get(&revision_id, "reviews")?.unwrap();
+
+            comments.push(comment_id);
+            for j in 0..doc.length(&discussion_id) {
+                let (_, comment_id) = doc.get(&discussion_id, j as usize)?.unwrap();
+                let replies = doc.get(&comment_id, "replies")?;
+
+                comments.push(comment_id);
+                if let Some((_, replies_id)) = replies {
+                    for k in 0..doc.length(&replies_id) {
+                        let (_, reply_id) = doc.get(&replies_id, k as usize)?.unwrap();
+                        comments.push(reply_id);
+                    }
+                }
+            }
+            for key in doc.keys(&reviews_id) {
+                let (_, review_id) = doc.get(&reviews_id, key)?.unwrap();
+                let (_, comment_id) = doc.get(&review_id, "comment")?.unwrap();
+
+                comments.push(comment_id);
+            }
+        }
+
+        let mut reactions = Reactions::new();
+        for comment_id in comments {
+            for (reaction, urns) in shared::lookup::reactors(doc, &comment_id)? {
+                reactions.entry(reaction).or_default().extend(urns);
+            }
+        }
+        Ok(reactions)
+    }
+
+    pub fn merge(doc: &Automerge, merge_id: &automerge::ObjId) -> Result<Merge, AutomergeError> {
+        let (peer, _) = doc.get(&merge_id, "peer")?.unwrap();
+        let (revision, _) = doc.get(&merge_id, "revision")?.unwrap();
+        let (commit, _) = doc.get(&merge_id, "commit")?.unwrap();
+        let (timestamp, _) = doc.get(&merge_id, "timestamp")?.unwrap();
+
+        let peer = PeerId::from_str(peer.to_str().unwrap()).unwrap();
+        let revision = revision.to_u64().unwrap() as usize;
+        let commit = commit.to_str().unwrap().try_into().unwrap();
+        let timestamp = Timestamp::try_from(timestamp).unwrap();
+
+        Ok(Merge {
+            peer,
+            revision,
+            commit,
+            timestamp,
+        })
+    }
+
+    pub fn review(doc: &Automerge, review_id: &automerge::ObjId) -> Result<Review, AutomergeError> {
+        let (author, _) = doc.get(&review_id, "author")?.unwrap();
+        let (verdict, _) = doc.get(&review_id, "verdict")?.unwrap();
+        let (_, comment_id) = doc.get(&review_id, "comment")?.unwrap();
+        let (timestamp, _) = doc.get(&review_id, "timestamp")?.unwrap();
+
+        let author = lookup::author(author)?;
+        let verdict = Verdict::try_from(verdict).unwrap();
+        let comment = shared::lookup::comment(doc, &comment_id)?;
+        let timestamp = Timestamp::try_from(timestamp).unwrap();
+
+        Ok(Review {
+            author,
+            verdict,
+            comment,
+            inline: Vec::new(),
+            timestamp,
+        })
+    }
 }
 
 mod cobs {
@@ -430,6 +1405,7 @@ mod cobs {
                 },
             )
             .map_err(|e| Error::Create(e.to_string()))?;
+        log::debug!("created patch {} of {}", cob.id(), project);
 
         Ok(*cob.id())
     }
@@ -449,10 +1425,11 @@ mod events {
         description: &str,
         target: &git::OneLevel,
         commit: &git::Oid,
+        base: Option<&git::Oid>,
         timestamp: Timestamp,
         labels: &[Label],
     ) -> Result<EntryContents, AutomergeError> {
-        let title = title.trim();
+        let title = normalize_title(title);
         // TODO: Return error.
         if title.is_empty() {
             panic!("Empty patch title");
@@ -465,7 +1442,7 @@ mod events {
                 |tx| {
                     let patch_id = tx.put_object(ObjId::Root, "patch", ObjType::Map)?;
 
-                    tx.put(&patch_id, "title", title)?;
+                    tx.put(&patch_id, "title", title.as_str())?;
                     tx.put(&patch_id, "author", author.to_string())?;
                     tx.put(&patch_id, "state", State::Open)?;
                     tx.put(&patch_id, "target", target.to_string())?;
@@ -473,7 +1450,7 @@ mod events {
 
                     let labels_id = tx.put_object(&patch_id, "labels", ObjType::Map)?;
                     for label in labels {
-                        tx.put(&labels_id, label.name().trim(), true)?;
+                        tx.put(&labels_id, label.name(), true)?;
                     }
 
                     let revisions_id = tx.put_object(&patch_id, "revisions", ObjType::List)?;
@@ -484,6 +1461,9 @@ mod events {
                         tx.put(&revision_id, "peer", peer.to_string())?;
                         tx.put(&revision_id, "version", 0)?;
                         tx.put(&revision_id, "commit", commit.to_string())?;
+                        if let Some(base) = base {
+                            tx.put(&revision_id, "base", base.to_string())?;
+                        }
                         {
                             // Top-level comment for first patch revision.
                             // Nb. top-level comment doesn't have a `replies` field.
@@ -509,6 +1489,269 @@ mod events {
 
         Ok(EntryContents::Automerge(doc.save_incremental()))
     }
+
+    pub fn merge(
+        patch: &mut Automerge,
+        revision: RevisionId,
+        peer: &PeerId,
+        commit: git::Oid,
+        timestamp: Timestamp,
+    ) -> Result<EntryContents, AutomergeError> {
+        patch
+            .transact_with::<_, _, AutomergeError, _, ()>(
+                |_| CommitOptions::default().with_message("Merge revision".to_owned()),
+                |tx| {
+                    let (_, obj_id) = tx.get(ObjId::Root, "patch")?.unwrap();
+                    let (_, revisions_id) = tx.get(&obj_id, "revisions")?.unwrap();
+                    let (_, revision_id) = tx.get(&revisions_id, revision)?.unwrap();
+                    let (_, merges_id) = tx.get(&revision_id, "merges")?.unwrap();
+
+                    let length = tx.length(&merges_id);
+                    let merge_id = tx.insert_object(&merges_id, length, ObjType::Map)?;
+
+                    tx.put(&merge_id, "peer", peer.to_string())?;
+                    tx.put(&merge_id, "revision", revision as u64)?;
+                    tx.put(&merge_id, "commit", commit.to_string())?;
+                    tx.put(&merge_id, "timestamp", timestamp)?;
+
+                    Ok(())
+                },
+            )
+            .map_err(|failure| failure.error)?;
+
+        let change = patch.get_last_local_change().unwrap().raw_bytes().to_vec();
+
+        Ok(EntryContents::Automerge(change))
+    }
+
+    pub fn update(
+        patch: &mut Automerge,
+        author: &Urn,
+        peer: &PeerId,
+        comment: &str,
+        commit: &git::Oid,
+        base: Option<&git::Oid>,
+        timestamp: Timestamp,
+    ) -> Result<(RevisionId, EntryContents), AutomergeError> {
+        let revision = patch
+            .transact_with::<_, _, AutomergeError, _, ()>(
+                |_| CommitOptions::default().with_message("Update patch".to_owned()),
+                |tx| {
+                    let (_, obj_id) = tx.get(ObjId::Root, "patch")?.unwrap();
+                    let (_, revisions_id) = tx.get(&obj_id, "revisions")?.unwrap();
+
+                    let version = tx.length(&revisions_id);
+                    let revision_id = tx.insert_object(&revisions_id, version, ObjType::Map)?;
+
+                    tx.put(&revision_id, "author", author.to_string())?;
+                    tx.put(&revision_id, "peer", peer.to_string())?;
+                    tx.put(&revision_id, "version", version as u64)?;
+                    tx.put(&revision_id, "commit", commit.to_string())?;
+                    if let Some(base) = base {
+                        tx.put(&revision_id, "base", base.to_string())?;
+                    }
+                    {
+                        let comment_id = tx.put_object(&revision_id, "comment", ObjType::Map)?;
+
+                        tx.put(&comment_id, "body", comment.trim())?;
+                        tx.put(&comment_id, "author", author.to_string())?;
+                        tx.put(&comment_id, "timestamp", timestamp)?;
+                        tx.put_object(&comment_id, "reactions", ObjType::Map)?;
+                    }
+                    tx.put_object(&revision_id, "discussion", ObjType::List)?;
+                    tx.put_object(&revision_id, "reviews", ObjType::Map)?;
+                    tx.put_object(&revision_id, "merges", ObjType::List)?;
+                    tx.put(&revision_id, "timestamp", timestamp)?;
+
+                    Ok(version)
+                },
+            )
+            .map_err(|failure| failure.error)?
+            .result;
+
+        let change = patch.get_last_local_change().unwrap().raw_bytes().to_vec();
+
+        Ok((revision, EntryContents::Automerge(change)))
+    }
+
+    pub fn review(
+        patch: &mut Automerge,
+        revision: RevisionId,
+        author: &Urn,
+        verdict: Verdict,
+        comment: &str,
+        timestamp: Timestamp,
+    ) -> Result<EntryContents, AutomergeError> {
+        patch
+            .transact_with::<_, _, AutomergeError, _, ()>(
+                |_| CommitOptions::default().with_message("Review patch".to_owned()),
+                |tx| {
+                    let (_, obj_id) = tx.get(ObjId::Root, "patch")?.unwrap();
+                    let (_, revisions_id) = tx.get(&obj_id, "revisions")?.unwrap();
+                    let (_, revision_id) = tx.get(&revisions_id, revision)?.unwrap();
+                    let (_, reviews_id) = tx.get(&revision_id, "reviews")?.unwrap();
+
+                    // Nb. Reviews are keyed by author, so a new review replaces the previous one.
+                    let review_id = tx.put_object(&reviews_id, author.to_string(), ObjType::Map)?;
+
+                    tx.put(&review_id, "author", author.to_string())?;
+                    tx.put(&review_id, "verdict", verdict)?;
+                    tx.put(&review_id, "timestamp", timestamp)?;
+                    tx.put_object(&review_id, "inline", ObjType::List)?;
+                    {
+                        let comment_id = tx.put_object(&review_id, "comment", ObjType::Map)?;
+
+                        tx.put(&comment_id, "body", comment.trim())?;
+                        tx.put(&comment_id, "author", author.to_string())?;
+                        tx.put(&comment_id, "timestamp", timestamp)?;
+                        tx.put_object(&comment_id, "reactions", ObjType::Map)?;
+                    }
+
+                    Ok(())
+                },
+            )
+            .map_err(|failure| failure.error)?;
+
+        let change = patch.get_last_local_change().unwrap().raw_bytes().to_vec();
+
+        Ok(EntryContents::Automerge(change))
+    }
+
+    /// Get the object of a comment of a revision, if the revision and comment exist: its
+    /// top-level comment for the root id, otherwise the comment at that position in its
+    /// discussion.
+    fn comment_object(
+        tx: &impl Transactable,
+        revision: RevisionId,
+        comment_id: CommentId,
+    ) -> Result<Option<ObjId>, AutomergeError> {
+        let (_, obj_id) = tx.get(ObjId::Root, "patch")?.unwrap();
+        let (_, revisions_id) = tx.get(&obj_id, "revisions")?.unwrap();
+        let revision_id = match tx.get(&revisions_id, revision)? {
+            Some((_, revision_id)) => revision_id,
+            None => return Ok(None),
+        };
+        let comment = if comment_id == CommentId::root() {
+            tx.get(&revision_id, "comment")?
+        } else {
+            let (_, discussion_id) = tx.get(&revision_id, "discussion")?.unwrap();
+            tx.get(&discussion_id, usize::from(comment_id) - 1)?
+        };
+        Ok(comment.map(|(_, comment_id)| comment_id))
+    }
+
+    pub fn comment(
+        patch: &mut Automerge,
+        revision: RevisionId,
+        author: &Urn,
+        body: &str,
+        timestamp: Timestamp,
+    ) -> Result<(CommentId, EntryContents), AutomergeError> {
+        let comment_id = patch
+            .transact_with::<_, _, AutomergeError, _, ()>(
+                |_| CommitOptions::default().with_message("Add comment".to_owned()),
+                |tx| {
+                    let (_, obj_id) = tx.get(ObjId::Root, "patch")?.unwrap();
+                    let (_, revisions_id) = tx.get(&obj_id, "revisions")?.unwrap();
+                    let (_, revision_id) = tx.get(&revisions_id, revision)?.unwrap();
+                    let (_, discussion_id) = tx.get(&revision_id, "discussion")?.unwrap();
+
+                    let length = tx.length(&discussion_id);
+                    let comment = tx.insert_object(&discussion_id, length, ObjType::Map)?;
+
+                    tx.put(&comment, "author", author.to_string())?;
+                    tx.put(&comment, "body", body.trim())?;
+                    tx.put(&comment, "timestamp", timestamp)?;
+                    tx.put_object(&comment, "replies", ObjType::List)?;
+                    tx.put_object(&comment, "reactions", ObjType::Map)?;
+
+                    // The root comment is the revision's top-level comment.
+                    Ok(CommentId::from(length + 1))
+                },
+            )
+            .map_err(|failure| failure.error)?
+            .result;
+
+        let change = patch.get_last_local_change().unwrap().raw_bytes().to_vec();
+
+        Ok((comment_id, EntryContents::Automerge(change)))
+    }
+
+    /// Replace the body of a comment, or delete it if no body is given.
+    pub fn edit_comment(
+        patch: &mut Automerge,
+        revision: RevisionId,
+        comment_id: CommentId,
+        body: Option<&str>,
+    ) -> Result<EntryContents, Error> {
+        let message = if body.is_some() {
+            "Edit comment"
+        } else {
+            "Delete comment"
+        };
+        patch
+            .transact_with::<_, _, Error, _, ()>(
+                |_| CommitOptions::default().with_message(message.to_owned()),
+                |tx| {
+                    let comment_id = self::comment_object(tx, revision, comment_id)?
+                        .ok_or(Error::CommentNotFound)?;
+
+                    match body {
+                        Some(body) => tx.put(&comment_id, "body", body.trim())?,
+                        None => {
+                            tx.put(&comment_id, "body", "")?;
+                            tx.put(&comment_id, "deleted", true)?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|failure| failure.error)?;
+
+        let change = patch.get_last_local_change().unwrap().raw_bytes().to_vec();
+
+        Ok(EntryContents::Automerge(change))
+    }
+
+    pub fn react(
+        patch: &mut Automerge,
+        revision: RevisionId,
+        comment_id: CommentId,
+        author: &Urn,
+        reactions: &[Reaction],
+    ) -> Result<EntryContents, Error> {
+        patch
+            .transact_with::<_, _, Error, _, ()>(
+                |_| CommitOptions::default().with_message("React".to_owned()),
+                |tx| {
+                    let comment_id = self::comment_object(tx, revision, comment_id)?
+                        .ok_or(Error::CommentNotFound)?;
+                    let reactions_id = match tx.get(&comment_id, "reactions")? {
+                        Some((_, reactions_id)) => reactions_id,
+                        None => tx.put_object(&comment_id, "reactions", ObjType::Map)?,
+                    };
+
+                    for reaction in reactions {
+                        let key = reaction.emoji.to_string();
+                        let reaction_id = if let Some((_, reaction_id)) =
+                            tx.get(&reactions_id, key)?
+                        {
+                            reaction_id
+                        } else {
+                            tx.put_object(&reactions_id, reaction.emoji.to_string(), ObjType::Map)?
+                        };
+                        tx.put(&reaction_id, author.encode_id(), true)?;
+                    }
+
+                    Ok(())
+                },
+            )
+            .map_err(|failure| failure.error)?;
+
+        let change = patch.get_last_local_change().unwrap().raw_bytes().to_vec();
+
+        Ok(EntryContents::Automerge(change))
+    }
 }
 
 #[cfg(test)]
@@ -516,6 +1759,42 @@ mod test {
     use super::*;
     use crate::test;
 
+    #[test]
+    fn test_state_roundtrip() {
+        for state in [State::Open, State::Draft, State::Closed] {
+            assert_eq!(state.to_string().parse::<State>().unwrap(), state);
+        }
+        assert!("merged".parse::<State>().is_err());
+    }
+
+    #[test]
+    fn test_patch_from_changes() {
+        let author = Urn::new(git2::Oid::zero().into());
+        let peer = PeerId::from(librad::SecretKey::new());
+        let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+        let EntryContents::Automerge(change) = events::create(
+            &author,
+            &peer,
+            "My first patch",
+            "Blah blah blah.",
+            &target,
+            &commit,
+            None,
+            Timestamp::now(),
+            &[],
+        )
+        .unwrap();
+        let garbage = vec![0xde, 0xad, 0xbe, 0xef];
+
+        let patch = Patch::from_changes([change.as_slice(), garbage.as_slice()]).unwrap();
+        assert_eq!(&patch.title, "My first patch");
+
+        // Without the change creating the patch, there's nothing to construct it from.
+        assert!(Patch::from_changes([garbage.as_slice()]).is_err());
+        assert!(Patch::from_changes(Vec::<&[u8]>::new()).is_err());
+    }
+
     #[test]
     fn test_patch_create_and_get() {
         let (storage, profile, whoami, project) = test::setup::profile();
@@ -523,6 +1802,8 @@ mod test {
         let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
         let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
         let commit = git::Oid::from(git2::Oid::zero());
+        let base =
+            git::Oid::from(git2::Oid::hash_object(git2::ObjectType::Commit, b"base").unwrap());
         let patch_id = patches
             .create(
                 &project.urn(),
@@ -530,7 +1811,9 @@ mod test {
                 "Blah blah blah.",
                 &target,
                 &commit,
+                Some(&base),
                 &[],
+                None,
             )
             .unwrap();
         let patch = patches.get(&project.urn(), &patch_id).unwrap().unwrap();
@@ -549,7 +1832,614 @@ mod test {
         assert_eq!(revision.discussion.len(), 0);
         assert_eq!(revision.version, 0);
         assert_eq!(revision.commit, commit);
+        assert_eq!(revision.base, Some(base));
         assert!(revision.reviews.is_empty());
         assert!(revision.merges.is_empty());
     }
+
+    #[test]
+    fn test_patch_exists() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let patch_id = test::setup::create_patch(&patches, &project);
+
+        assert!(patches.exists(&project.urn(), &patch_id).unwrap());
+
+        patches.remove(&project.urn(), &patch_id).unwrap();
+        assert!(!patches.exists(&project.urn(), &patch_id).unwrap());
+    }
+
+    #[test]
+    fn test_patch_read_only() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let patch_id = test::setup::create_patch(&patches, &project);
+        patches
+            .review(&project.urn(), &patch_id, 0, Verdict::Accept, "LGTM")
+            .unwrap();
+
+        // Patches read without the signing key are the same as those read through `librad`.
+        let read_only = crate::profile::read_only(&profile).unwrap();
+        let reader = PatchesReadOnly::new(&read_only).unwrap();
+        let patch = reader.get(&project.urn(), &patch_id).unwrap().unwrap();
+        let verified = patches
+            .get_object(&project.urn(), &patch_id)
+            .unwrap()
+            .map(|cob| Patch::try_from(cob.history()).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            serde_json::to_value(&verified).unwrap()
+        );
+        assert_eq!(reader.all(&project.urn()).unwrap().patches.len(), 1);
+
+        let missing = PatchId::from(git2::Oid::zero());
+        assert!(reader.get(&project.urn(), &missing).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_patch_review() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let author = whoami.urn();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let patch_id = test::setup::create_patch(&patches, &project);
+
+        patches
+            .review(&project.urn(), &patch_id, 0, Verdict::Accept, "LGTM")
+            .unwrap();
+
+        let patch = patches.get(&project.urn(), &patch_id).unwrap().unwrap();
+        let reviews = &patch.revisions.head.reviews;
+        let review = &reviews[&author];
+
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(review.verdict, Verdict::Accept);
+        assert_eq!(review.comment.body, "LGTM");
+        assert_eq!(review.author.urn(), &author);
+        assert!(patch.is_reviewed_by(&author));
+        assert!(!patch.is_reviewed_by(&project.urn()));
+
+        let missing = PatchId::from(git2::Oid::zero());
+        assert!(matches!(
+            patches.review(&project.urn(), &missing, 0, Verdict::Accept, "LGTM"),
+            Err(Error::Retrieve(_))
+        ));
+    }
+
+    #[test]
+    fn test_patch_reactions() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let author = whoami.urn();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let patch_id = test::setup::create_patch(&patches, &project);
+        let party = Reaction::new('🎉').unwrap();
+        let heart = Reaction::new('❤').unwrap();
+
+        let reactions = patches
+            .reactions(&project.urn(), &patch_id)
+            .unwrap()
+            .unwrap();
+        assert!(reactions.is_empty());
+
+        for reaction in [party, heart, party] {
+            patches
+                .react(&project.urn(), &patch_id, 0, CommentId::root(), reaction)
+                .unwrap();
+        }
+
+        let reactions = patches
+            .reactions(&project.urn(), &patch_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(reactions.len(), 2);
+        assert_eq!(reactions[&party], vec![author.clone()]);
+        assert_eq!(reactions[&heart], vec![author]);
+
+        let patch = patches.get(&project.urn(), &patch_id).unwrap().unwrap();
+        assert_eq!(patch.revisions.head.comment.reactions[&party], 1);
+
+        let missing = PatchId::from(git2::Oid::zero());
+        assert!(patches
+            .reactions(&project.urn(), &missing)
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            patches.react(&project.urn(), &missing, 0, CommentId::root(), party),
+            Err(Error::Retrieve(_))
+        ));
+        assert!(matches!(
+            patches.react(&project.urn(), &patch_id, 1, CommentId::root(), party),
+            Err(Error::CommentNotFound)
+        ));
+        assert!(matches!(
+            patches.react(&project.urn(), &patch_id, 0, CommentId::from(1), party),
+            Err(Error::CommentNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_patch_edit_and_delete_comment() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+        let contributor = Urn::try_from_id("hnrkbjg7r54q48sqsaho1n4qfxhi4nbmdh51y").unwrap();
+        let create = |author: Option<&Urn>| {
+            patches
+                .create(
+                    &project.urn(),
+                    "My first patch",
+                    "Blah blah blah.",
+                    &target,
+                    &commit,
+                    None,
+                    &[],
+                    author,
+                )
+                .unwrap()
+        };
+        let patch_id = create(None);
+
+        let first = patches
+            .comment(&project.urn(), &patch_id, 0, "First!")
+            .unwrap();
+        let second = patches
+            .comment(&project.urn(), &patch_id, 0, "Second.")
+            .unwrap();
+        assert_eq!(usize::from(first), 1);
+        assert_eq!(usize::from(second), 2);
+
+        patches
+            .edit_comment(&project.urn(), &patch_id, 0, first, "First, edited.")
+            .unwrap();
+        patches
+            .edit_comment(&project.urn(), &patch_id, 0, CommentId::root(), "Blah.")
+            .unwrap();
+        patches
+            .delete_comment(&project.urn(), &patch_id, 0, second)
+            .unwrap();
+
+        let patch = patches.get(&project.urn(), &patch_id).unwrap().unwrap();
+        let revision = &patch.revisions.head;
+        assert_eq!(revision.comment.body, "Blah.");
+        assert_eq!(revision.discussion.len(), 2);
+        assert_eq!(revision.discussion[0].body, "First, edited.");
+        assert!(!revision.discussion[0].deleted);
+        assert_eq!(revision.discussion[1].body, "");
+        assert!(revision.discussion[1].deleted);
+
+        // Deleted comments are gone, and missing ones were never there.
+        assert!(matches!(
+            patches.edit_comment(&project.urn(), &patch_id, 0, second, "Back."),
+            Err(Error::CommentNotFound)
+        ));
+        assert!(matches!(
+            patches.delete_comment(&project.urn(), &patch_id, 0, CommentId::from(3)),
+            Err(Error::CommentNotFound)
+        ));
+
+        // Comments authored by others can't be edited nor deleted.
+        let theirs = create(Some(&contributor));
+        assert!(matches!(
+            patches.edit_comment(&project.urn(), &theirs, 0, CommentId::root(), "Mine."),
+            Err(Error::NotCommentAuthor)
+        ));
+        assert!(matches!(
+            patches.delete_comment(&project.urn(), &theirs, 0, CommentId::root()),
+            Err(Error::NotCommentAuthor)
+        ));
+
+        // Comments of missing patches are errors too.
+        let missing = PatchId::from(git2::Oid::zero());
+        assert!(matches!(
+            patches.comment(&project.urn(), &missing, 0, "Hello?"),
+            Err(Error::Retrieve(_))
+        ));
+        assert!(matches!(
+            patches.edit_comment(&project.urn(), &missing, 0, first, "Hello?"),
+            Err(Error::Retrieve(_))
+        ));
+        assert!(matches!(
+            patches.delete_comment(&project.urn(), &missing, 0, first),
+            Err(Error::Retrieve(_))
+        ));
+    }
+
+    #[test]
+    fn test_patch_create_with_author() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+        let contributor = Urn::try_from_id("hnrkbjg7r54q48sqsaho1n4qfxhi4nbmdh51y").unwrap();
+        let patch_id = patches
+            .create(
+                &project.urn(),
+                "My first patch",
+                "Blah blah blah.",
+                &target,
+                &commit,
+                None,
+                &[],
+                Some(&contributor),
+            )
+            .unwrap();
+        let patch = patches.get(&project.urn(), &patch_id).unwrap().unwrap();
+        let revision = patch.revisions.head;
+
+        assert_eq!(patch.author.urn(), &contributor);
+        assert_eq!(revision.author.urn(), &contributor);
+        assert_eq!(revision.peer, *storage.peer_id());
+    }
+
+    #[test]
+    fn test_patch_update_by_other_author() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let author = whoami.urn();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+        let contributor = Urn::try_from_id("hnrkbjg7r54q48sqsaho1n4qfxhi4nbmdh51y").unwrap();
+        let patch_id = patches
+            .create(
+                &project.urn(),
+                "My first patch",
+                "Blah blah blah.",
+                &target,
+                &commit,
+                None,
+                &[],
+                Some(&contributor),
+            )
+            .unwrap();
+        let update =
+            git::Oid::from(git2::Oid::hash_object(git2::ObjectType::Commit, b"update").unwrap());
+        let revision = patches
+            .update(
+                &project.urn(),
+                &patch_id,
+                "Rebased.",
+                &update,
+                Some(&commit),
+            )
+            .unwrap();
+        let patch = patches.get(&project.urn(), &patch_id).unwrap().unwrap();
+
+        assert_eq!(revision, 1);
+        assert_eq!(patch.revision_count(), 2);
+        assert_eq!(patch.author.urn(), &contributor);
+
+        let first = patch.revisions.first();
+        let last = patch.latest_revision();
+
+        assert_eq!(first.author.urn(), &contributor);
+        assert_eq!(last.author.urn(), &author);
+        assert_eq!(last.peer, *storage.peer_id());
+        assert_eq!(last.version, 1);
+        assert_eq!(last.commit, update);
+        assert_eq!(last.base, Some(commit));
+        assert_eq!(last.comment.body, "Rebased.");
+    }
+
+    #[test]
+    fn test_patch_remove() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+        let contributor = Urn::try_from_id("hnrkbjg7r54q48sqsaho1n4qfxhi4nbmdh51y").unwrap();
+        let create = |author: Option<&Urn>| {
+            patches
+                .create(
+                    &project.urn(),
+                    "My first patch",
+                    "Blah blah blah.",
+                    &target,
+                    &commit,
+                    None,
+                    &[],
+                    author,
+                )
+                .unwrap()
+        };
+
+        let ours = create(None);
+        patches.remove(&project.urn(), &ours).unwrap();
+        assert!(patches.get(&project.urn(), &ours).unwrap().is_none());
+
+        // Patches authored by others can't be removed.
+        let theirs = create(Some(&contributor));
+        assert!(matches!(
+            patches.remove(&project.urn(), &theirs),
+            Err(Error::NotAuthor)
+        ));
+        assert!(patches.get(&project.urn(), &theirs).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_patch_merge() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+        let patch_id = test::setup::create_patch(&patches, &project);
+
+        let merge = patches.merge(&project.urn(), &patch_id, 0, commit).unwrap();
+        let patch = patches.get(&project.urn(), &patch_id).unwrap().unwrap();
+        let merges = &patch.revisions.head.merges;
+
+        assert_eq!(merges.len(), 1);
+        assert_eq!(merges[0].peer, *storage.peer_id());
+        assert_eq!(merges[0].revision, 0);
+        assert_eq!(merges[0].commit, commit);
+        assert_eq!(merges[0].timestamp, merge.timestamp);
+
+        let missing = PatchId::from(git2::Oid::zero());
+        assert!(matches!(
+            patches.merge(&project.urn(), &missing, 0, commit),
+            Err(Error::Retrieve(_))
+        ));
+    }
+
+    #[test]
+    fn test_patch_all() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+
+        for title in ["My first patch", "My second patch"] {
+            patches
+                .create(
+                    &project.urn(),
+                    title,
+                    "Blah blah blah.",
+                    &target,
+                    &commit,
+                    None,
+                    &[],
+                    None,
+                )
+                .unwrap();
+        }
+        let loaded = patches.all(&project.urn()).unwrap();
+        let mut titles = loaded
+            .patches
+            .iter()
+            .map(|(_, p)| p.title.as_str())
+            .collect::<Vec<_>>();
+        titles.sort_unstable();
+
+        assert_eq!(titles, ["My first patch", "My second patch"]);
+        assert!(loaded.failed.is_empty());
+    }
+
+    #[test]
+    fn test_patch_history() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let author = whoami.urn();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let patch_id = test::setup::create_patch(&patches, &project);
+        patches
+            .review(&project.urn(), &patch_id, 0, Verdict::Accept, "LGTM")
+            .unwrap();
+
+        let history = patches.history(&project.urn(), &patch_id).unwrap().unwrap();
+        let messages = history
+            .iter()
+            .map(|entry| entry.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(messages, ["Create patch", "Review patch"]);
+        assert!(history
+            .iter()
+            .all(|entry| entry.author.as_ref() == Some(&author)));
+    }
+
+    #[test]
+    fn test_patch_serialize() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+        let patch_id = test::setup::create_patch(&patches, &project);
+        patches
+            .review(&project.urn(), &patch_id, 0, Verdict::Accept, "LGTM")
+            .unwrap();
+        patches.merge(&project.urn(), &patch_id, 0, commit).unwrap();
+
+        let patch = patches.get(&project.urn(), &patch_id).unwrap().unwrap();
+        let value = serde_json::to_value(&patch).unwrap();
+        let revision = &value["revisions"][0];
+
+        assert_eq!(value["title"], "My first patch");
+        assert_eq!(value["target"], "master");
+        assert_eq!(revision["commit"], commit.to_string());
+        assert_eq!(revision["comment"]["body"], "Blah blah blah.");
+        assert_eq!(revision["merges"].as_array().unwrap().len(), 1);
+
+        let reviews = revision["reviews"].as_object().unwrap();
+        let review = reviews.values().next().unwrap();
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(review["verdict"], "accept");
+        assert_eq!(review["comment"]["body"], "LGTM");
+    }
+
+    #[test]
+    fn test_patch_changed_since() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+        let create = |title: &str| {
+            patches
+                .create(
+                    &project.urn(),
+                    title,
+                    "Blah blah blah.",
+                    &target,
+                    &commit,
+                    None,
+                    &[],
+                    None,
+                )
+                .unwrap()
+        };
+        let first = create("My first patch");
+
+        let (changed, snapshot) = patches
+            .changed_since(&project.urn(), &Snapshot::default())
+            .unwrap();
+        assert_eq!(changed, vec![first]);
+
+        let (changed, snapshot) = patches.changed_since(&project.urn(), &snapshot).unwrap();
+        assert!(changed.is_empty());
+
+        patches
+            .review(&project.urn(), &first, 0, Verdict::Accept, "LGTM")
+            .unwrap();
+        let second = create("My second patch");
+
+        let (changed, snapshot) = patches.changed_since(&project.urn(), &snapshot).unwrap();
+        assert_eq!(changed.len(), 2);
+        assert!(changed.contains(&first));
+        assert!(changed.contains(&second));
+
+        let (changed, _) = patches.changed_since(&project.urn(), &snapshot).unwrap();
+        assert!(changed.is_empty());
+    }
+
+    #[test]
+    fn test_patch_iter() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
+        let commit = git::Oid::from(git2::Oid::zero());
+
+        for title in ["My first patch", "My second patch"] {
+            patches
+                .create(
+                    &project.urn(),
+                    title,
+                    "Blah blah blah.",
+                    &target,
+                    &commit,
+                    None,
+                    &[],
+                    None,
+                )
+                .unwrap();
+        }
+        let found = patches
+            .iter(&project.urn())
+            .unwrap()
+            .filter_map(Result::ok)
+            .find(|(_, patch)| patch.title == "My second patch");
+        assert!(found.is_some());
+
+        let loaded = patches.all(&project.urn()).unwrap();
+        let collected = patches.iter(&project.urn()).unwrap().collect::<Loaded>();
+        assert!(loaded.failed.is_empty());
+        assert!(collected.failed.is_empty());
+        // Patches created within the same second may be ordered differently.
+        let ids = |loaded: &Loaded| {
+            let mut ids = loaded
+                .patches
+                .iter()
+                .map(|(id, _)| id.to_string())
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(&loaded), ids(&collected));
+    }
+
+    /// Create a patch object from a single change, bypassing [`Patches::create`],
+    /// so that malformed patches can be stored.
+    fn create_raw(
+        patches: &Patches,
+        project: &Urn,
+        f: impl FnOnce(&mut automerge::transaction::Transaction) -> Result<(), AutomergeError>,
+    ) -> PatchId {
+        use automerge::transaction::CommitOptions;
+
+        let mut doc = Automerge::new();
+        doc.transact_with::<_, _, AutomergeError, _, ()>(
+            |_| CommitOptions::default().with_message("Create patch".to_owned()),
+            f,
+        )
+        .unwrap();
+
+        cobs::create(
+            EntryContents::Automerge(doc.save_incremental()),
+            project,
+            &patches.whoami,
+            &patches.store,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_patch_all_failed() {
+        use automerge::transaction::Transactable;
+
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let patch_id = test::setup::create_patch(&patches, &project);
+        // Not a patch at all.
+        let corrupt = create_raw(&patches, &project.urn(), |tx| {
+            tx.put_object(automerge::ObjId::Root, "issue", ObjType::Map)?;
+            Ok(())
+        });
+
+        let loaded = patches.all(&project.urn()).unwrap();
+        assert_eq!(
+            loaded.patches.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            [patch_id]
+        );
+        assert_eq!(loaded.failed, [corrupt]);
+        assert!(matches!(
+            patches.get(&project.urn(), &corrupt),
+            Err(Error::Load(id, _)) if id == corrupt
+        ));
+    }
+
+    #[test]
+    fn test_patch_all_malformed() {
+        use automerge::transaction::Transactable;
+
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let mut created = Vec::new();
+
+        // Enough patches for the malformed one to share a thread with others.
+        for _ in 0..MAX_LOAD_THREADS * 2 {
+            created.push(test::setup::create_patch(&patches, &project));
+        }
+        // A patch missing all of its fields, which panics when constructed.
+        let malformed = create_raw(&patches, &project.urn(), |tx| {
+            tx.put_object(automerge::ObjId::Root, "patch", ObjType::Map)?;
+            Ok(())
+        });
+
+        let loaded = patches.all(&project.urn()).unwrap();
+        let mut loaded_ids = loaded
+            .patches
+            .iter()
+            .map(|(id, _)| id.to_string())
+            .collect::<Vec<_>>();
+        let mut created = created.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        loaded_ids.sort();
+        created.sort();
+
+        assert_eq!(loaded_ids, created);
+        assert_eq!(loaded.failed, [malformed]);
+
+        let collected = patches.iter(&project.urn()).unwrap().collect::<Loaded>();
+        assert_eq!(collected.patches.len(), created.len());
+        assert_eq!(collected.failed, [malformed]);
+        assert!(matches!(
+            patches.get(&project.urn(), &malformed),
+            Err(Error::Load(id, _)) if id == malformed
+        ));
+    }
 }
@@ -0,0 +1,116 @@
+//! Local record of the changes of collaborative objects that were last viewed.
+//!
+//! Changes are identified by the tips of the object histories rather than by time, so that
+//! clock skew between peers doesn't matter. This is never shared with other peers.
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::{fs, io};
+
+use librad::collaborative_objects::ObjectId;
+use librad::paths::Paths;
+
+/// Name of the file the markers are stored in, under the COB cache directory.
+pub const SEEN_FILE: &str = "seen.json";
+
+/// "Last seen" markers of collaborative objects, by object id.
+#[derive(Debug)]
+pub struct Seen {
+    path: PathBuf,
+    markers: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Seen {
+    /// Open the markers stored in the COB cache directory. If there are none yet,
+    /// this is empty.
+    pub fn open(paths: &Paths) -> io::Result<Self> {
+        Self::open_at(paths.cob_cache_dir().join(SEEN_FILE))
+    }
+
+    fn open_at(path: PathBuf) -> io::Result<Self> {
+        let markers: BTreeMap<String, serde_json::Value> = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err),
+        };
+        // Nb. Markers of earlier versions, which recorded the time an object was seen,
+        // can't be compared with history tips, and are dropped.
+        let markers = markers
+            .into_iter()
+            .filter_map(|(id, tips)| Some((id, serde_json::from_value(tips).ok()?)))
+            .collect();
+
+        Ok(Self { path, markers })
+    }
+
+    /// The history tips of the object when it was last seen, if ever.
+    pub fn get(&self, id: &ObjectId) -> Option<&BTreeSet<String>> {
+        self.markers.get(&id.to_string())
+    }
+
+    /// Whether an object that was seen before has changed since, given its current history
+    /// tips. Objects that were never seen aren't considered unread, since all of their
+    /// changes would be.
+    pub fn is_unread(&self, id: &ObjectId, tips: Option<&BTreeSet<String>>) -> bool {
+        match (self.get(id), tips) {
+            (Some(seen), Some(tips)) => seen != tips,
+            _ => false,
+        }
+    }
+
+    /// Mark an object as seen with the given history tips.
+    pub fn mark(&mut self, id: &ObjectId, tips: BTreeSet<String>) {
+        self.markers.insert(id.to_string(), tips);
+    }
+
+    /// Write the markers back to disk.
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(&self.markers)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        fs::write(&self.path, json)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cobs::patch::{Patches, Verdict};
+    use crate::test;
+
+    #[test]
+    fn test_seen_unread() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let patch_id = test::setup::create_patch(&patches, &project);
+        let dir = test::setup::tempdir();
+        let path = dir.path().join("seen.json");
+
+        let mut seen = Seen::open_at(path.clone()).unwrap();
+        let snapshot = patches.snapshot(&project.urn()).unwrap();
+        let tips = snapshot.tips(&patch_id).cloned().unwrap();
+        assert!(!seen.is_unread(&patch_id, Some(&tips)));
+
+        seen.mark(&patch_id, tips.clone());
+        seen.save().unwrap();
+
+        patches
+            .review(&project.urn(), &patch_id, 0, Verdict::Accept, "LGTM")
+            .unwrap();
+        let snapshot = patches.snapshot(&project.urn()).unwrap();
+        let mut seen = Seen::open_at(path.clone()).unwrap();
+        assert_eq!(seen.get(&patch_id), Some(&tips));
+        assert!(seen.is_unread(&patch_id, snapshot.tips(&patch_id)));
+
+        seen.mark(&patch_id, snapshot.tips(&patch_id).cloned().unwrap());
+        assert!(!seen.is_unread(&patch_id, snapshot.tips(&patch_id)));
+
+        // Markers recording the time an object was seen are dropped.
+        fs::write(&path, format!("{{\"{}\": 1650000000}}", patch_id)).unwrap();
+        let seen = Seen::open_at(path).unwrap();
+        assert_eq!(seen.get(&patch_id), None);
+    }
+}
@@ -2,6 +2,7 @@
 use std::borrow::Borrow;
 use std::collections::HashMap;
 use std::convert::{Infallible, TryFrom};
+use std::fmt;
 
 use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -22,10 +23,19 @@ pub enum ResolveError {
     Identities(#[from] librad::git::identities::Error),
 }
 
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum TimestampError {
+    #[error("invalid date '{0}', expected eg. '2022-05-01' or '2.weeks.ago'")]
+    Invalid(String),
+}
+
 /// A discussion thread.
 pub type Discussion = Vec<Comment<Replies>>;
 
+/// An emoji reaction. Serialized as the emoji itself, so that reaction counts can be
+/// serialized as JSON objects.
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Reaction {
     pub emoji: char,
 }
@@ -51,18 +61,104 @@ impl FromStr for Reaction {
     }
 }
 
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum LabelError {
+    #[error("label cannot be empty")]
+    Empty,
+    #[error("label cannot contain control character {0:?}")]
+    InvalidChar(char),
+    #[error("invalid label color '{0}', expected a hex color, eg. '#d73a4a'")]
+    InvalidColor(String),
+}
+
+/// Colors of labels that weren't given one, see [`Label::default_color`].
+pub const LABEL_COLORS: [Color; 8] = [
+    Color::new(0xd7, 0x3a, 0x4a),
+    Color::new(0x00, 0x75, 0xca),
+    Color::new(0xa2, 0xee, 0xef),
+    Color::new(0x70, 0x57, 0xff),
+    Color::new(0x00, 0x86, 0x72),
+    Color::new(0xe4, 0xe6, 0x69),
+    Color::new(0xd8, 0x76, 0xe3),
+    Color::new(0xcf, 0xd3, 0xd7),
+];
+
+/// A label color, eg. `#d73a4a`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for Color {
+    type Err = LabelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(LabelError::InvalidColor(s.to_owned()));
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+
+        Ok(Self::new(channel(0), channel(2), channel(4)))
+    }
+}
+
+/// A label, eg. `bug` or `good first issue`.
+///
+/// Labels are normalized on construction: they are lowercased, and surrounding
+/// whitespace is trimmed, while inner whitespace is collapsed into a single space.
+/// This ensures that eg. `Bug` and ` bug ` refer to the same label.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
-#[serde(transparent)]
+#[serde(try_from = "String", into = "String")]
 pub struct Label(String);
 
 impl Label {
-    pub fn new(name: impl Into<String>) -> Result<Self, Infallible> {
-        Ok(Self(name.into()))
+    pub fn new(name: impl Into<String>) -> Result<Self, LabelError> {
+        let name = name.into();
+
+        if let Some(c) = name.chars().find(|c| c.is_control() && !c.is_whitespace()) {
+            return Err(LabelError::InvalidChar(c));
+        }
+        let name = name
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+
+        if name.is_empty() {
+            return Err(LabelError::Empty);
+        }
+        Ok(Self(name))
     }
 
     pub fn name(&self) -> &str {
         self.0.as_str()
     }
+
+    /// Color of the label if it wasn't given one. This is picked from the label name, so
+    /// that the label always has the same color.
+    pub fn default_color(&self) -> Color {
+        // Nb. FNV-1a is used since, unlike the standard library's hasher, it is guaranteed
+        // to be stable.
+        let hash = self.0.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        LABEL_COLORS[(hash % LABEL_COLORS.len() as u64) as usize]
+    }
 }
 
 impl From<Label> for String {
@@ -71,6 +167,22 @@ impl From<Label> for String {
     }
 }
 
+impl TryFrom<String> for Label {
+    type Error = LabelError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Self::new(name)
+    }
+}
+
+impl FromStr for Label {
+    type Err = LabelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
 /// Issue author.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(untagged)]
@@ -100,6 +212,16 @@ impl Author {
     }
 }
 
+impl fmt::Display for Author {
+    /// Displays the author's name if resolved, and a compact URN otherwise.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Urn { urn } => write!(f, "{}", crate::fmt::urn(urn)),
+            Self::Resolved(project::PeerIdentity { name, .. }) => write!(f, "{}", name),
+        }
+    }
+}
+
 /// Local id of a comment in an issue.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
 pub struct CommentId {
@@ -137,6 +259,9 @@ pub struct Comment<R = ()> {
     pub reactions: HashMap<Reaction, usize>,
     pub replies: R,
     pub timestamp: Timestamp,
+    /// Whether the comment was deleted by its author. Deleted comments are kept as
+    /// tombstones, without a body, so that the ids of the following comments don't change.
+    pub deleted: bool,
 }
 
 impl Comment<()> {
@@ -184,6 +309,81 @@ impl Timestamp {
     pub fn as_secs(&self) -> u64 {
         self.seconds
     }
+
+    /// Parse a date, relative to `now`. Either an absolute date like `2022-05-01`, which
+    /// is taken to be at midnight UTC, or a relative one like `3.days.ago`.
+    pub fn parse(s: &str, now: Timestamp) -> Result<Self, TimestampError> {
+        let invalid = || TimestampError::Invalid(s.to_owned());
+
+        if let Some(relative) = s.strip_suffix(".ago") {
+            let (n, unit) = relative.split_once('.').ok_or_else(invalid)?;
+            let n: u64 = n.parse().map_err(|_| invalid())?;
+            let secs = match unit.strip_suffix('s').unwrap_or(unit) {
+                "second" => 1,
+                "minute" => 60,
+                "hour" => 60 * 60,
+                "day" => 60 * 60 * 24,
+                "week" => 60 * 60 * 24 * 7,
+                "month" => 60 * 60 * 24 * 30,
+                "year" => 60 * 60 * 24 * 365,
+                _ => return Err(invalid()),
+            };
+            return Ok(Self::new(now.seconds.saturating_sub(n * secs)));
+        }
+
+        let parts = s
+            .split('-')
+            .map(|p| p.parse::<u64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (year, month, day) = match parts.as_slice() {
+            [y, m, d] if *y >= 1970 && (1..=12).contains(m) => (*y, *m, *d),
+            _ => return Err(invalid()),
+        };
+        if !(1..=days_in_month(year, month)).contains(&day) {
+            return Err(invalid());
+        }
+
+        // Days since the epoch of a date in the proleptic Gregorian calendar.
+        // See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year / 400;
+        let yoe = year - era * 400;
+        let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146097 + doe - 719468;
+
+        Ok(Self::new(days * 60 * 60 * 24))
+    }
+
+    /// Parse the end of a date range, relative to `now`. Like [`Timestamp::parse`], except
+    /// that absolute dates cover the whole day, ie. are taken to be at its last second.
+    pub fn parse_end(s: &str, now: Timestamp) -> Result<Self, TimestampError> {
+        let time = Self::parse(s, now)?;
+
+        if s.ends_with(".ago") {
+            Ok(time)
+        } else {
+            Ok(Self::new(time.seconds + 60 * 60 * 24 - 1))
+        }
+    }
+}
+
+/// Number of days in a month of the given year, taking leap years into account.
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = TimestampError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, Self::now())
+    }
 }
 
 impl From<Timestamp> for ScalarValue {
@@ -211,7 +411,31 @@ pub mod lookup {
     use std::convert::TryFrom;
     use std::str::FromStr;
 
-    use super::{Automerge, AutomergeError, Comment, HashMap, Reaction, Replies, Timestamp};
+    use super::{
+        Automerge, AutomergeError, Borrow, Comment, HashMap, Reaction, Replies, ScalarValue,
+        Timestamp, Urn, Value,
+    };
+
+    /// Get the identities that reacted to a comment, by reaction.
+    pub fn reactors(
+        doc: &Automerge,
+        obj_id: &automerge::ObjId,
+    ) -> Result<HashMap<Reaction, Vec<Urn>>, AutomergeError> {
+        let (_, reactions_id) = doc.get(&obj_id, "reactions")?.unwrap();
+
+        let mut reactors = HashMap::new();
+        for reaction in doc.keys(&reactions_id) {
+            let key = Reaction::from_str(&reaction).unwrap();
+            let (_, reaction_id) = doc.get(&reactions_id, reaction)?.unwrap();
+            let urns = doc
+                .keys(&reaction_id)
+                .filter_map(|id| Urn::try_from_id(id).ok())
+                .collect();
+
+            reactors.insert(key, urns);
+        }
+        Ok(reactors)
+    }
 
     pub fn comment(
         doc: &Automerge,
@@ -220,19 +444,21 @@ pub mod lookup {
         let (author, _) = doc.get(&obj_id, "author")?.unwrap();
         let (body, _) = doc.get(&obj_id, "body")?.unwrap();
         let (timestamp, _) = doc.get(&obj_id, "timestamp")?.unwrap();
-        let (_, reactions_id) = doc.get(&obj_id, "reactions")?.unwrap();
 
         let author = super::author(author)?;
         let body = body.into_string().unwrap();
         let timestamp = Timestamp::try_from(timestamp).unwrap();
-
-        let mut reactions: HashMap<_, usize> = HashMap::new();
-        for reaction in doc.keys(&reactions_id) {
-            let key = Reaction::from_str(&reaction).unwrap();
-            let count = reactions.entry(key).or_default();
-
-            *count += 1;
-        }
+        // Comments created before deletion was supported don't have this key.
+        let deleted = match doc.get(&obj_id, "deleted")? {
+            Some((Value::Scalar(deleted), _)) => {
+                matches!(deleted.borrow(), ScalarValue::Boolean(true))
+            }
+            _ => false,
+        };
+        let reactions = self::reactors(doc, obj_id)?
+            .into_iter()
+            .map(|(reaction, urns)| (reaction, urns.len()))
+            .collect();
 
         Ok(Comment {
             author,
@@ -240,6 +466,7 @@ pub mod lookup {
             reactions,
             replies: (),
             timestamp,
+            deleted,
         })
     }
 
@@ -265,6 +492,146 @@ pub mod lookup {
             reactions: comment.reactions,
             replies,
             timestamp: comment.timestamp,
+            deleted: comment.deleted,
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_label_normalization() {
+        assert_eq!(Label::new("bug").unwrap().name(), "bug");
+        assert_eq!(Label::new("Bug").unwrap(), Label::new("bug").unwrap());
+        assert_eq!(Label::new("  BUG\n").unwrap().name(), "bug");
+        assert_eq!(
+            Label::new("Good   first\tissue").unwrap().name(),
+            "good first issue"
+        );
+    }
+
+    #[test]
+    fn test_reaction_serialize() {
+        let reactions = [(Reaction::new('🥳').unwrap(), 2)]
+            .iter()
+            .copied()
+            .collect::<HashMap<_, _>>();
+        let json = serde_json::to_string(&reactions).unwrap();
+
+        assert_eq!(json, r#"{"🥳":2}"#);
+        assert_eq!(
+            serde_json::from_str::<HashMap<Reaction, usize>>(&json).unwrap(),
+            reactions
+        );
+    }
+
+    #[test]
+    fn test_color_from_str() {
+        let color = Color::from_str("#d73a4a").unwrap();
+
+        assert_eq!(color, Color::new(0xd7, 0x3a, 0x4a));
+        assert_eq!(color.to_string(), "#d73a4a");
+        assert_eq!(Color::from_str("D73A4A").unwrap(), color);
+        assert!(Color::from_str("#d73a4").is_err());
+        assert!(Color::from_str("#d73a4g").is_err());
+        assert!(Color::from_str("#d73aé").is_err());
+    }
+
+    #[test]
+    fn test_label_default_color() {
+        let bug = Label::new("bug").unwrap();
+
+        assert_eq!(
+            bug.default_color(),
+            Label::new("Bug").unwrap().default_color()
+        );
+        assert!(LABEL_COLORS.contains(&bug.default_color()));
+    }
+
+    #[test]
+    fn test_label_invalid() {
+        assert_eq!(Label::new(""), Err(LabelError::Empty));
+        assert_eq!(Label::new(" \t "), Err(LabelError::Empty));
+        assert_eq!(Label::new("bug\0"), Err(LabelError::InvalidChar('\0')));
+        assert!(serde_json::from_str::<Label>("\"  \"").is_err());
+    }
+
+    #[test]
+    fn test_author_display() {
+        let urn = Urn::new(git2::Oid::zero().into());
+        let author = Author::Urn { urn: urn.clone() };
+        let id = urn.encode_id();
+
+        assert_eq!(
+            author.to_string(),
+            format!("rad:git:{}…{}", &id[..7], &id[id.len() - 7..])
+        );
+
+        let author = Author::Resolved(project::PeerIdentity {
+            urn,
+            name: String::from("cloudhead"),
+            ens: None,
+        });
+        assert_eq!(author.to_string(), "cloudhead");
+    }
+
+    #[test]
+    fn test_timestamp_parse_end() {
+        let now = Timestamp::new(1_650_000_000);
+
+        // Absolute dates end at the last second of the day, relative ones are exact.
+        assert_eq!(
+            Timestamp::parse_end("2022-05-01", now),
+            Ok(Timestamp::new(1_651_363_200 + 24 * 60 * 60 - 1))
+        );
+        assert_eq!(
+            Timestamp::parse_end("1.day.ago", now),
+            Timestamp::parse("1.day.ago", now)
+        );
+        assert!(Timestamp::parse_end("2022-02-30", now).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_parse() {
+        let now = Timestamp::new(1_650_000_000);
+
+        assert_eq!(Timestamp::parse("1970-01-01", now), Ok(Timestamp::new(0)));
+        assert_eq!(
+            Timestamp::parse("2022-05-01", now),
+            Ok(Timestamp::new(1_651_363_200))
+        );
+        assert_eq!(
+            Timestamp::parse("2000-02-29", now),
+            Ok(Timestamp::new(951_782_400))
+        );
+        assert_eq!(
+            Timestamp::parse("2.weeks.ago", now),
+            Ok(Timestamp::new(1_650_000_000 - 2 * 7 * 24 * 60 * 60))
+        );
+        assert_eq!(
+            Timestamp::parse("1.day.ago", now),
+            Ok(Timestamp::new(1_650_000_000 - 24 * 60 * 60))
+        );
+
+        assert!(Timestamp::parse("2022-13-01", now).is_err());
+        assert!(Timestamp::parse("2022-02-29", now).is_err());
+        assert!(Timestamp::parse("2022-02-31", now).is_err());
+        assert!(Timestamp::parse("2022-04-31", now).is_err());
+        assert!(Timestamp::parse("1900-02-29", now).is_err());
+        assert!(Timestamp::parse("2024-02-29", now).is_ok());
+        assert!(Timestamp::parse("2022-12-31", now).is_ok());
+        assert!(Timestamp::parse("2.fortnights.ago", now).is_err());
+        assert!(Timestamp::parse("yesterday", now).is_err());
+        assert!(Timestamp::parse("", now).is_err());
+    }
+
+    #[test]
+    fn test_label_serde() {
+        let label: Label = serde_json::from_str("\"Needs Review\"").unwrap();
+
+        assert_eq!(label.name(), "needs review");
+        assert_eq!(serde_json::to_string(&label).unwrap(), "\"needs review\"");
+    }
+}
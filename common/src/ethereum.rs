@@ -39,7 +39,7 @@ pub const ENVIRONMENT_VARIABLES: &str = r#"
 "#;
 
 /// Command-line ethereum signer options.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct SignerOptions {
     /// Account derivation path when using a Ledger hardware wallet.
     pub ledger_hdpath: Option<DerivationPath>,
@@ -4,7 +4,7 @@ use std::fs::{File, OpenOptions};
 use std::io;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::str::FromStr;
 
 use anyhow::anyhow;
@@ -16,6 +16,8 @@ use librad::git::types::remote::Remote;
 use librad::profile::Profile;
 use librad::{crypto::BoxedSigner, PeerId};
 
+pub use git2::ConfigLevel;
+pub use git2::ErrorCode;
 pub use git2::Oid;
 pub use git2::Repository;
 pub use librad::git::local::transport;
@@ -29,6 +31,9 @@ pub const CONFIG_GPG_FORMAT: &str = "gpg.format";
 pub const CONFIG_GPG_SSH_PROGRAM: &str = "gpg.ssh.program";
 pub const CONFIG_GPG_SSH_ALLOWED_SIGNERS: &str = "gpg.ssh.allowedSignersFile";
 
+/// Length of abbreviated object ids, as displayed to users.
+pub const SHORT_OID_LEN: usize = 7;
+
 /// Minimum required git version.
 pub const VERSION_REQUIRED: Version = Version {
     major: 2,
@@ -36,6 +41,13 @@ pub const VERSION_REQUIRED: Version = Version {
     patch: 0,
 };
 
+/// Abbreviate an object id for display, eg. `9d0b3f2`.
+pub fn short(oid: impl ToString) -> String {
+    let mut oid = oid.to_string();
+    oid.truncate(SHORT_OID_LEN);
+    oid
+}
+
 /// A parsed git version.
 #[derive(PartialEq, Eq, Debug, PartialOrd, Ord)]
 pub struct Version {
@@ -114,7 +126,16 @@ pub fn git<S: AsRef<std::ffi::OsStr>>(
     repo: &std::path::Path,
     args: impl IntoIterator<Item = S>,
 ) -> Result<String, anyhow::Error> {
-    let output = Command::new("git").current_dir(repo).args(args).output()?;
+    let args = args.into_iter().collect::<Vec<_>>();
+    log::debug!(
+        "running `git {}` in {}",
+        args.iter()
+            .map(|arg| arg.as_ref().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" "),
+        repo.display()
+    );
+    let output = Command::new("git").current_dir(repo).args(&args).output()?;
 
     if output.status.success() {
         let out = if output.stdout.is_empty() {
@@ -131,6 +152,15 @@ pub fn git<S: AsRef<std::ffi::OsStr>>(
     )))
 }
 
+/// Count the refs updated by `git fetch --verbose`, given its output. Refs that were already
+/// up to date aren't counted.
+pub fn fetched_refs(output: &str) -> usize {
+    output
+        .lines()
+        .filter(|line| line.contains(" -> ") && !line.contains("[up to date]"))
+        .count()
+}
+
 /// Configure SSH signing in the given git repo, for the given peer.
 pub fn configure_signing(repo: &Path, peer_id: &PeerId) -> Result<(), anyhow::Error> {
     let key = keys::to_ssh_key(peer_id)?;
@@ -213,6 +243,40 @@ pub fn read_gitsigners(path: &Path) -> Result<HashSet<String>, io::Error> {
     Ok(keys)
 }
 
+/// Verify an SSH signature made by git over `payload`, eg. of a signed tag, against the given
+/// public key, in the OpenSSH format, eg. `ssh-ed25519 AAAA...`, see [`keys::to_ssh_key`].
+/// Uses `ssh-keygen`, like git itself. Returns `false` if the signature is invalid, or wasn't
+/// made with the key.
+pub fn verify_ssh_signature(payload: &[u8], signature: &str, key: &str) -> Result<bool, io::Error> {
+    let dir = tempfile::Builder::new().prefix("rad-verify-").tempdir()?;
+    let signers = dir.path().join("allowed_signers");
+    let sig = dir.path().join("signature");
+
+    std::fs::write(&signers, format!("signer {}\n", key))?;
+    std::fs::write(&sig, signature)?;
+
+    let mut child = Command::new("ssh-keygen")
+        .arg("-Y")
+        .arg("verify")
+        .arg("-f")
+        .arg(&signers)
+        .arg("-I")
+        .arg("signer")
+        .arg("-n")
+        .arg("git")
+        .arg("-s")
+        .arg(&sig)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(payload)?;
+    }
+    Ok(child.wait()?.success())
+}
+
 /// Add a path to the repository's git ignore file. Creates the
 /// ignore file if it does not exist.
 pub fn ignore(repo: &Path, item: &Path) -> Result<(), io::Error> {
@@ -318,20 +382,26 @@ pub fn parse_remote(refspec: &str) -> Option<(PeerId, &str)> {
         .and_then(|(peer, r)| PeerId::from_str(peer).ok().map(|p| (p, r)))
 }
 
+/// View the diff between two commits. If `pager` is `false`, git's pager is disabled.
 pub fn view_diff(
     repo: &git2::Repository,
     left: &git2::Oid,
     right: &git2::Oid,
+    pager: bool,
 ) -> anyhow::Result<()> {
     // TODO(erikli): Replace with repo.diff()
     let workdir = repo
         .workdir()
         .ok_or_else(|| anyhow!("Could not get workdir current repository."))?;
 
-    let left = format!("{:.7}", left.to_string());
-    let right = format!("{:.7}", right.to_string());
+    let left = short(left);
+    let right = short(right);
 
-    let mut git = Command::new("git")
+    let mut cmd = Command::new("git");
+    if !pager {
+        cmd.arg("--no-pager");
+    }
+    let mut git = cmd
         .current_dir(workdir)
         .args(["diff", &left, &right])
         .spawn()?;
@@ -340,23 +410,170 @@ pub fn view_diff(
     Ok(())
 }
 
-pub fn add_tag(repo: &git2::Repository, message: &str, patch_tag_name: &str) -> anyhow::Result<()> {
-    let head = repo.head()?;
-    let commit = head.peel(git2::ObjectType::Commit).unwrap();
+/// Word of a line in a word diff, see [`word_diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Word {
+    Unchanged(String),
+    Added(String),
+    Removed(String),
+}
 
-    repo.tag(patch_tag_name, &commit, &repo.signature()?, message, false)?;
+impl Word {
+    /// Append `word` to this word if they are of the same kind, otherwise return it.
+    fn merge(&mut self, word: Word) -> Option<Word> {
+        match (self, word) {
+            (Self::Unchanged(a), Self::Unchanged(b))
+            | (Self::Added(a), Self::Added(b))
+            | (Self::Removed(a), Self::Removed(b)) => {
+                a.push_str(&b);
+                None
+            }
+            (_, word) => Some(word),
+        }
+    }
+}
 
-    Ok(())
+/// Changes to a file, by word, see [`word_diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileWordDiff {
+    /// Path of the file. Deleted files have their old path.
+    pub path: PathBuf,
+    /// Hunks of changes to the file, as their header and their lines.
+    pub hunks: Vec<(String, Vec<Vec<Word>>)>,
+}
+
+/// Diff two commits by word rather than by line, which is easier to review for prose.
+/// Lines removed and added together are compared word by word, so that only the words
+/// that changed are marked as such.
+pub fn word_diff(
+    repo: &git2::Repository,
+    left: &git2::Oid,
+    right: &git2::Oid,
+) -> Result<Vec<FileWordDiff>, git2::Error> {
+    let left = repo.find_commit(*left)?.tree()?;
+    let right = repo.find_commit(*right)?.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&left), Some(&right), None)?;
+    let mut files = Vec::new();
+
+    for (i, delta) in diff.deltas().enumerate() {
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let patch = match git2::Patch::from_diff(&diff, i)? {
+            Some(patch) => patch,
+            None => continue,
+        };
+        let mut hunks = Vec::new();
+
+        for h in 0..patch.num_hunks() {
+            let (hunk, len) = patch.hunk(h)?;
+            let header = String::from_utf8_lossy(hunk.header()).trim_end().to_owned();
+            let mut lines = Vec::new();
+            let mut removed = String::new();
+            let mut added = String::new();
+
+            for l in 0..len {
+                let line = patch.line_in_hunk(h, l)?;
+                let content = String::from_utf8_lossy(line.content());
+
+                match line.origin() {
+                    '-' => removed.push_str(&content),
+                    '+' => added.push_str(&content),
+                    origin => {
+                        lines.extend(diff_words(&removed, &added));
+                        removed.clear();
+                        added.clear();
+
+                        if origin == ' ' {
+                            let content = content.trim_end_matches('\n').to_owned();
+                            lines.push(vec![Word::Unchanged(content)]);
+                        }
+                    }
+                }
+            }
+            lines.extend(diff_words(&removed, &added));
+            hunks.push((header, lines));
+        }
+        files.push(FileWordDiff { path, hunks });
+    }
+    Ok(files)
+}
+
+/// Compare removed and added lines word by word, and split the result back into lines.
+fn diff_words(removed: &str, added: &str) -> Vec<Vec<Word>> {
+    let mut lines = Vec::new();
+    let mut line: Vec<Word> = Vec::new();
+
+    for change in similar::TextDiff::from_words(removed, added).iter_all_changes() {
+        for (i, part) in change.value().split('\n').enumerate() {
+            if i > 0 {
+                lines.push(std::mem::take(&mut line));
+            }
+            if part.is_empty() {
+                continue;
+            }
+            let word = match change.tag() {
+                similar::ChangeTag::Equal => Word::Unchanged(part.to_owned()),
+                similar::ChangeTag::Insert => Word::Added(part.to_owned()),
+                similar::ChangeTag::Delete => Word::Removed(part.to_owned()),
+            };
+            match line.last_mut() {
+                Some(last) => line.extend(last.merge(word)),
+                None => line.push(word),
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Tag `commit` under `name` with an annotated tag, replacing an existing tag if `force`
+/// is set.
+pub fn add_tag(
+    repo: &git2::Repository,
+    message: &str,
+    name: &str,
+    commit: git2::Oid,
+    force: bool,
+) -> Result<git2::Oid, git2::Error> {
+    let object = repo.find_object(commit, Some(git2::ObjectType::Commit))?;
+
+    repo.tag(name, &object, &repo.signature()?, message, force)
 }
 
 pub fn push_tag(tag_name: &str) -> anyhow::Result<String> {
     git(Path::new("."), vec!["push", "rad", "tag", tag_name])
 }
 
+/// Push a tag, replacing it on the remote if it was recreated.
+pub fn push_tag_force(tag_name: &str) -> anyhow::Result<String> {
+    git(
+        Path::new("."),
+        vec!["push", "--force", "rad", "tag", tag_name],
+    )
+}
+
+/// Delete a tag from the remote.
+pub fn push_tag_delete(tag_name: &str) -> anyhow::Result<String> {
+    let refspec = format!(":refs/tags/{}", tag_name);
+
+    git(Path::new("."), vec!["push", "rad", refspec.as_str()])
+}
+
 pub fn push_branch(name: &str) -> anyhow::Result<String> {
     git(Path::new("."), vec!["push", "rad", name])
 }
 
+/// Push a single refspec, eg. to update a remote branch to a given commit.
+pub fn push_refspec(refspec: &str) -> anyhow::Result<String> {
+    git(Path::new("."), vec!["push", "rad", refspec])
+}
+
 fn write_gitsigner(mut w: impl io::Write, signer: &PeerId) -> io::Result<()> {
     writeln!(w, "{} {}", signer, keys::to_ssh_key(signer)?)
 }
@@ -366,6 +583,73 @@ mod test {
     use super::*;
     use std::str::FromStr;
 
+    #[test]
+    fn test_short() {
+        let oid = Oid::from_str("9d0b3f2c2a1e4a8b6c1f0e5d4c3b2a1908172635").unwrap();
+
+        assert_eq!(short(oid), "9d0b3f2");
+        assert_eq!(short(&oid), short(oid.to_string()));
+        assert_eq!(short("4c2e"), "4c2e");
+    }
+
+    #[test]
+    fn test_word_diff() {
+        let (_dir, repo) = crate::test::setup::repository();
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let commit = |text: &str, parents: &[&git2::Commit]| {
+            let blob = repo.blob(text.as_bytes()).unwrap();
+            let mut tree = repo.treebuilder(None).unwrap();
+            tree.insert("README", blob, 0o100644).unwrap();
+            let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+
+            repo.commit(None, &sig, &sig, "Update", &tree, parents)
+                .unwrap()
+        };
+        let left = commit("The quick brown fox\njumps over\nthe dog.\n", &[]);
+        let right = commit(
+            "The quick red fox\njumps over\nthe lazy dog.\nThe end.\n",
+            &[&repo.find_commit(left).unwrap()],
+        );
+
+        let diff = word_diff(&repo, &left, &right).unwrap();
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].path, Path::new("README"));
+        assert_eq!(diff[0].hunks.len(), 1);
+
+        let (header, lines) = &diff[0].hunks[0];
+        assert!(header.starts_with("@@ -1,3 +1,4 @@"));
+        assert_eq!(
+            lines,
+            &[
+                vec![
+                    Word::Unchanged(String::from("The quick ")),
+                    Word::Removed(String::from("brown")),
+                    Word::Added(String::from("red")),
+                    Word::Unchanged(String::from(" fox")),
+                ],
+                vec![Word::Unchanged(String::from("jumps over"))],
+                vec![
+                    Word::Unchanged(String::from("the ")),
+                    Word::Added(String::from("lazy ")),
+                    Word::Unchanged(String::from("dog.")),
+                ],
+                vec![Word::Added(String::from("The end."))],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fetched_refs() {
+        let output = "\
+From https://seed.example.com/hnrkbjg7r54q48sqsaho1n4qfxhi4nbmdh51y
+ = [up to date]      refs/remotes/hyn/heads/master -> refs/namespaces/hnrk/refs/remotes/hyn/heads/master
+ * [new branch]      refs/remotes/hyn/heads/fix -> refs/namespaces/hnrk/refs/remotes/hyn/heads/fix
+   4c2e1a0..9d0b3f2  refs/remotes/hyn/rad/signed_refs -> refs/namespaces/hnrk/refs/remotes/hyn/rad/signed_refs
+";
+        assert_eq!(fetched_refs(output), 2);
+        assert_eq!(fetched_refs(""), 0);
+    }
+
     #[test]
     fn test_version_ord() {
         assert!(
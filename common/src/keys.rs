@@ -14,22 +14,44 @@ use lnk_clib::keys::ssh::SshAuthSock;
 
 pub use lnk_clib::keys::LIBRAD_KEY_FILE as KEY_FILE;
 
+use crate::args;
 use crate::signer::{ToSigner, ZeroizingSecretKey};
 
 /// Get the radicle signer and storage.
 pub fn storage(profile: &Profile, signer: impl ToSigner) -> Result<Storage, Error> {
-    let signer = match signer.to_signer(profile) {
-        Ok(signer) => signer,
-        Err(keys::ssh::Error::NoSuchKey(_)) => {
-            anyhow::bail!("the radicle ssh key for this profile is not in ssh-agent")
-        }
-        Err(err) => anyhow::bail!(err),
-    };
+    let signer = signer
+        .to_signer(profile)
+        .map_err(|err| signer_error(profile, err))?;
     let storage = Storage::open(profile.paths(), signer)?;
 
     Ok(storage)
 }
 
+/// Convert an error obtaining a profile's signer. If ssh-agent doesn't hold the signing key,
+/// the fingerprint of the expected key is included, so that it can be told apart from other
+/// keys, eg. in the output of `ssh-add -l`, along with a hint on how to add it.
+pub fn signer_error(profile: &Profile, err: keys::ssh::Error) -> Error {
+    if !matches!(err, keys::ssh::Error::NoSuchKey(_)) {
+        return anyhow::anyhow!(err);
+    }
+    let fingerprint = crate::profile::read_only(profile)
+        .ok()
+        .and_then(|storage| to_ssh_fingerprint(storage.peer_id()).ok());
+    let err = match fingerprint {
+        Some(fingerprint) => anyhow::anyhow!(
+            "the radicle ssh key for this profile ({}) is not in ssh-agent",
+            fingerprint
+        ),
+        None => anyhow::anyhow!("the radicle ssh key for this profile is not in ssh-agent"),
+    };
+
+    args::Error::WithHint {
+        err,
+        hint: "Add it to ssh-agent with `rad auth`, or switch to the profile it belongs to.",
+    }
+    .into()
+}
+
 /// Add a profile's radicle signing key to ssh-agent.
 pub fn add<P: Pinentry>(
     profile: &Profile,
@@ -119,7 +141,7 @@ pub fn load_secret_key(
 ) -> Result<ZeroizingSecretKey, anyhow::Error> {
     let pwhash = pwhash(passphrase);
     let file_storage: FileStorage<_, PublicKey, _, _> = FileStorage::new(
-        &profile.paths().keys_dir().join(keys::LIBRAD_KEY_FILE),
+        &crate::profile::keys_dir(profile).join(keys::LIBRAD_KEY_FILE),
         pwhash,
     );
     let keypair = file_storage.get_key()?;
@@ -21,13 +21,36 @@ pub use url::Url;
 
 /// String formatting of various types.
 pub mod fmt {
+    use librad::git::Urn;
     use librad::PeerId;
 
+    /// Number of characters kept at each end of an id when formatting it to be compact.
+    pub const COMPACT_CHARS: usize = 7;
+
     /// Format a peer id to be more compact.
     pub fn peer(peer: &PeerId) -> String {
-        let peer = peer.to_string();
-        let start = peer.chars().take(7).collect::<String>();
-        let end = peer.chars().skip(peer.len() - 7).collect::<String>();
+        peer_with(peer, COMPACT_CHARS)
+    }
+
+    /// Format a peer id to be more compact, keeping `chars` characters at each end.
+    pub fn peer_with(peer: &PeerId, chars: usize) -> String {
+        compact(&peer.to_string(), chars)
+    }
+
+    /// Format a URN to be more compact, eg. `rad:git:hnrkbtw…ba4drqy`.
+    pub fn urn(urn: &Urn) -> String {
+        format!("rad:git:{}", compact(&urn.encode_id(), COMPACT_CHARS))
+    }
+
+    /// Keep the first and last `chars` characters of an id. Ids that are too short to be
+    /// shortened are returned as is.
+    fn compact(id: &str, chars: usize) -> String {
+        let count = id.chars().count();
+        if count <= chars * 2 + 1 {
+            return id.to_owned();
+        }
+        let start = id.chars().take(chars).collect::<String>();
+        let end = id.chars().skip(count - chars).collect::<String>();
 
         format!("{}…{}", start, end)
     }
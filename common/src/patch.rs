@@ -1,5 +1,8 @@
 //! Patch-related functions and types.
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
+use std::path::Path;
+use std::str::FromStr;
+use std::{fmt, fs, io};
 
 use librad::git::refs::Refs;
 use librad::git::storage::{ReadOnly, ReadOnlyStorage};
@@ -11,9 +14,40 @@ use radicle_git_ext as git;
 use serde::Serialize;
 
 use crate::cobs::patch as cob;
+use crate::cobs::{Color, Label, Timestamp};
 use crate::project;
 
+/// Type name and schema of patch COBs, so that integrators needn't hardcode them.
+pub use crate::cobs::patch::{SCHEMA as COB_SCHEMA, TYPENAME as COB_TYPENAME};
+
+/// Default prefix of patch tags.
 pub const TAG_PREFIX: &str = "patches/";
+/// Git configuration key used to override the patch tag prefix, eg. `rfc/`.
+///
+/// Set it with `git config rad.patch.prefix <prefix>`. If unset, [`TAG_PREFIX`] is used.
+pub const CONFIG_PREFIX_KEY: &str = "rad.patch.prefix";
+/// Default length of shortened patch ids, see [`Metadata::short_id`].
+pub const SHORT_ID_LENGTH: usize = 24;
+/// Git configuration key used to override the length of shortened patch ids.
+///
+/// Set it with `git config rad.patch.idLength <n>`. If unset, [`SHORT_ID_LENGTH`] is used.
+pub const CONFIG_ID_LENGTH_KEY: &str = "rad.patch.idLength";
+/// Git configuration key of the labels given to new patches, separated by commas.
+///
+/// Set it with eg. `git config rad.patch.defaultLabels "needs-review, good first issue"`.
+pub const CONFIG_DEFAULT_LABELS_KEY: &str = "rad.patch.defaultLabels";
+/// Trailer holding the id of the patch COB a tag belongs to.
+pub const COB_TRAILER: &str = "Rad-Cob";
+/// Trailer used to attribute a patch to an identity other than the publishing peer.
+pub const AUTHOR_TRAILER: &str = "Rad-Author";
+/// Trailer holding the [`DiffStats`] of a patch, so they needn't be recomputed.
+pub const STATS_TRAILER: &str = "Rad-Stats";
+/// Trailer holding the commit a patch is based on, eg. its merge-base with the target branch.
+pub const BASE_TRAILER: &str = "Rad-Base";
+/// Path of the patch description template, relative to the root of the working copy.
+pub const TEMPLATE_PATH: &str = ".rad/patch_template.md";
+/// Start of an SSH signature, as appended by git to the message of a signed tag.
+const SSH_SIGNATURE_BEGIN: &str = "-----BEGIN SSH SIGNATURE-----";
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -21,54 +55,554 @@ pub enum Error {
     Git(#[from] git2::Error),
     #[error("storage: {0}")]
     Storage(#[from] librad::git::storage::Error),
+    #[error("cob: {0}")]
+    Cob(#[from] cob::Error),
+    #[error("invalid target branch '{0}'")]
+    InvalidTarget(String),
+    #[error("target branch '{0}' has more than one level, only branches such as 'master' can be targeted")]
+    MultiLevelTarget(String),
+    #[error("HEAD is not on a branch")]
+    DetachedHead,
+    #[error("commit {0} is already merged")]
+    AlreadyMerged(git2::Oid),
+    #[error("merge conflicts in {}", .0.join(", "))]
+    Conflicts(Vec<String>),
+    #[error("a patch for branch '{0}' already exists")]
+    AlreadyExists(String),
+    #[error("no patch exists for branch '{0}'")]
+    NoPatch(String),
+    #[error("commit '{0}' was not found")]
+    CommitNotFound(String),
+    #[error("commit {0} is not reachable from HEAD")]
+    Unreachable(git2::Oid),
+    #[error("branch name '{0}' is not valid UTF-8")]
+    NonUtf8Branch(String),
+    #[error("'{0}' is not a valid patch tag name")]
+    InvalidTagName(String),
+    #[error("invalid default label '{0}': {1}")]
+    InvalidDefaultLabel(String, crate::cobs::LabelError),
 }
 
-#[derive(PartialEq, Eq)]
+/// State of a patch. Besides being merged, a patch can be marked as a draft or closed in
+/// its COB, see [`cob::State`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum State {
     Open,
+    Draft,
     Merged,
+    Closed,
+}
+
+impl State {
+    /// All states, in the order patches are listed in.
+    pub const ALL: [State; 4] = [Self::Open, Self::Draft, Self::Merged, Self::Closed];
+
+    /// Name of the state, eg. as given to `--state`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Merged => "merged",
+            Self::Open => cob::State::Open.as_str(),
+            Self::Draft => cob::State::Draft.as_str(),
+            Self::Closed => cob::State::Closed.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for State {
+    type Err = cob::StateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|state| state.as_str() == s)
+            .ok_or_else(|| cob::StateError::Invalid(s.to_owned()))
+    }
+}
+
+impl From<cob::State> for State {
+    fn from(state: cob::State) -> Self {
+        match state {
+            cob::State::Open => Self::Open,
+            cob::State::Draft => Self::Draft,
+            cob::State::Closed => Self::Closed,
+        }
+    }
 }
 
 /// A patch is a change set that a user wants the maintainer to merge into a project's default
 /// branch.
 ///
-/// A patch is represented by an annotated tag, prefixed with `patches/`.
+/// A patch is represented by an annotated tag, prefixed with `patches/`, or the
+/// prefix configured under [`CONFIG_PREFIX_KEY`].
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Metadata {
-    /// ID of a patch. This is the portion of the tag name following the `patches/` prefix.
+    /// ID of a patch. This is the portion of the tag name following the patch tag prefix.
     pub id: String,
     /// Peer that the patch originated from
     pub peer: project::PeerInfo,
-    /// Message attached to the patch. This is the message of the annotated tag.
+    /// Message attached to the patch. This is the message of the annotated tag, in the
+    /// format described in [`tag_message`].
     pub message: Option<String>,
     /// Head commit that the author wants to merge with this patch.
     pub commit: git::Oid,
+    /// Commit that the patch is based on, if it was recorded when the patch was created.
+    pub base: Option<git::Oid>,
+    /// Size of the patch, if it was recorded when the patch was created.
+    pub stats: Option<DiffStats>,
+    /// Creation time of the patch, ie. the time of the tag, if known.
+    pub timestamp: Option<Timestamp>,
+    /// Annotated tag of the patch, eg. to check its signature with [`verify`].
+    pub tag: git::Oid,
+}
+
+impl Metadata {
+    /// Title of the patch, ie. the subject line of its message.
+    pub fn title(&self) -> String {
+        self.message
+            .as_deref()
+            .map(|m| parse_message(m).0)
+            .unwrap_or_default()
+    }
+
+    /// Description of the patch, ie. the body of its message, without trailers.
+    pub fn description(&self) -> String {
+        self.message
+            .as_deref()
+            .map(|m| parse_message(m).1)
+            .unwrap_or_default()
+    }
+
+    /// The id of the patch, shortened to at most `len` characters, eg. for listings. Ids
+    /// that are too long are truncated and end with `…`.
+    pub fn short_id(&self, len: usize) -> String {
+        if self.id.chars().count() <= len {
+            return self.id.clone();
+        }
+        let mut short = self
+            .id
+            .chars()
+            .take(len.saturating_sub(1))
+            .collect::<String>();
+        short.push('…');
+        short
+    }
+}
+
+/// Get the length of shortened patch ids configured for the given repository, falling back
+/// to [`SHORT_ID_LENGTH`].
+pub fn short_id_length(repo: &git2::Repository) -> usize {
+    repo.config()
+        .and_then(|c| c.get_i64(CONFIG_ID_LENGTH_KEY))
+        .ok()
+        .filter(|len| *len > 0)
+        .map_or(SHORT_ID_LENGTH, |len| len as usize)
+}
+
+/// Get the default labels of new patches configured for the given repository, see
+/// [`CONFIG_DEFAULT_LABELS_KEY`]. Fails if any of them isn't a valid label.
+pub fn default_labels(repo: &git2::Repository) -> Result<Vec<Label>, Error> {
+    let labels = match repo
+        .config()
+        .and_then(|c| c.get_string(CONFIG_DEFAULT_LABELS_KEY))
+    {
+        Ok(labels) => labels,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    labels
+        .split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(|name| {
+            Label::new(name).map_err(|err| Error::InvalidDefaultLabel(name.trim().to_owned(), err))
+        })
+        .collect()
+}
+
+/// Summary of the changes introduced by a patch.
+#[derive(Debug, Default, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct DiffStats {
+    /// Number of files changed.
+    pub files: usize,
+    /// Number of lines added.
+    pub insertions: usize,
+    /// Number of lines removed.
+    pub deletions: usize,
+}
+
+impl DiffStats {
+    /// Compute the stats of the changes between two commits. If there is no `base`,
+    /// eg. when the histories are unrelated, `head` is compared to the empty tree.
+    pub fn between(
+        repo: &git2::Repository,
+        base: Option<git2::Oid>,
+        head: git2::Oid,
+    ) -> Result<Self, Error> {
+        let base = base.map(|oid| repo.find_commit(oid)?.tree()).transpose()?;
+        let head = repo.find_commit(head)?.tree()?;
+        let diff = repo.diff_tree_to_tree(base.as_ref(), Some(&head), None)?;
+        let stats = diff.stats()?;
+
+        Ok(Self {
+            files: stats.files_changed(),
+            insertions: stats.insertions(),
+            deletions: stats.deletions(),
+        })
+    }
+
+    /// Find the stats trailer in a patch message.
+    pub fn from_message(message: &str) -> Option<Self> {
+        trailer(message, STATS_TRAILER)?.parse().ok()
+    }
+}
+
+impl fmt::Display for DiffStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "files={} insertions={} deletions={}",
+            self.files, self.insertions, self.deletions
+        )
+    }
+}
+
+impl FromStr for DiffStats {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut stats = Self::default();
+
+        for field in s.split_whitespace() {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| format!("invalid stats field '{}'", field))?;
+            let value = value
+                .parse()
+                .map_err(|_| format!("invalid stats value '{}'", value))?;
+
+            match key {
+                "files" => stats.files = value,
+                "insertions" => stats.insertions = value,
+                "deletions" => stats.deletions = value,
+                _ => return Err(format!("unknown stats field '{}'", key)),
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Reference to a patch COB, possibly of a project that isn't available locally yet, eg.
+/// when it was shared out-of-band. One of:
+///
+/// * `<patch-id>`, a patch of the current project;
+/// * `<project-urn>:<patch-id>`, a patch of the given project;
+/// * `rad://<seed>/<project-id>/<patch-id>`, a patch of the given project, which can be
+///   fetched from the given seed.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PatchRef {
+    /// Where to find the patch, if not in the current project.
+    pub origin: Option<project::Origin>,
+    /// Patch id.
+    pub id: cob::PatchId,
+}
+
+impl FromStr for PatchRef {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || anyhow::anyhow!("invalid patch reference '{}'", s);
+
+        if let Ok(id) = cob::PatchId::from_str(s) {
+            return Ok(Self { origin: None, id });
+        }
+        let (origin, id) = if s.starts_with(&format!("{}://", project::URL_SCHEME)) {
+            let (origin, id) = s.rsplit_once('/').ok_or_else(invalid)?;
+            (project::Origin::from_str(origin)?, id)
+        } else {
+            let (urn, id) = s.rsplit_once(':').ok_or_else(invalid)?;
+            let urn = Urn::from_str(urn).map_err(|_| invalid())?;
+            (project::Origin::from_urn(urn), id)
+        };
+        let id = cob::PatchId::from_str(id).map_err(|_| invalid())?;
+
+        Ok(Self {
+            origin: Some(origin),
+            id,
+        })
+    }
+}
+
+/// Get the patch tag prefix configured for the given repository, falling back to
+/// [`TAG_PREFIX`]. The returned prefix always ends with a `/`.
+pub fn tag_prefix(repo: &git2::Repository) -> String {
+    let prefix = repo
+        .config()
+        .and_then(|c| c.get_string(CONFIG_PREFIX_KEY))
+        .ok()
+        .map(|p| p.trim().trim_matches('/').to_owned())
+        .filter(|p| !p.is_empty());
+
+    match prefix {
+        Some(prefix) => format!("{}/", prefix),
+        None => TAG_PREFIX.to_owned(),
+    }
+}
+
+/// Get the name of the patch tag of a branch, eg. `patches/feature/login`. Fails if the
+/// branch and prefix don't form a valid tag name. Names aren't sanitized, since the branch
+/// of a patch is read back from its tag name.
+pub fn tag_name(repo: &git2::Repository, branch: &str) -> Result<String, Error> {
+    let name = format!("{}{}", tag_prefix(repo), branch);
+
+    if !git2::Reference::is_valid_name(&format!("refs/tags/{}", name)) {
+        return Err(Error::InvalidTagName(name));
+    }
+    Ok(name)
+}
+
+/// Parse the name of a patch's target branch. Since patch COBs store their target as a
+/// [`git::OneLevel`], only single-level branch names are accepted: `master`, but not
+/// `feature/x` nor `refs/heads/master`.
+pub fn target_branch(name: &str) -> Result<git::OneLevel, Error> {
+    let reference =
+        git::RefLike::try_from(name).map_err(|_| Error::InvalidTarget(name.to_owned()))?;
+    if name.contains('/') {
+        return Err(Error::MultiLevelTarget(name.to_owned()));
+    }
+    Ok(git::OneLevel::from(reference))
+}
+
+/// Get the name of the branch checked out in `repo`. Patches can only be created for
+/// branches with UTF-8 names, since their tags are named after them.
+pub fn current_branch(repo: &git2::Repository) -> Result<String, Error> {
+    let head = repo.head()?;
+    if !head.is_branch() {
+        return Err(Error::DetachedHead);
+    }
+    match head.shorthand() {
+        Some(branch) => Ok(branch.to_owned()),
+        None => Err(Error::NonUtf8Branch(
+            String::from_utf8_lossy(head.shorthand_bytes()).into_owned(),
+        )),
+    }
+}
+
+/// Get the head of a project branch, as found under `rad/<branch>`.
+/// Returns `None` if the branch doesn't exist, eg. in a freshly initialized project.
+pub fn branch_head(repo: &git2::Repository, branch: &str) -> Result<Option<git2::Oid>, Error> {
+    match repo.resolve_reference_from_short_name(&format!("rad/{}", branch)) {
+        Ok(reference) => Ok(reference.target()),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Read the patch description template at `path` or, if not given, the template of the
+/// working copy at [`TEMPLATE_PATH`], if there is one.
+pub fn description_template(
+    repo: &git2::Repository,
+    path: Option<&Path>,
+) -> io::Result<Option<String>> {
+    if let Some(path) = path {
+        return fs::read_to_string(path).map(Some);
+    }
+    let path = match repo.workdir() {
+        Some(workdir) => workdir.join(TEMPLATE_PATH),
+        None => return Ok(None),
+    };
+
+    match fs::read_to_string(path) {
+        Ok(template) => Ok(Some(template)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Remove the comment lines, ie. lines starting with `#`, of a description written from a
+/// template, as well as surrounding whitespace.
+pub fn strip_comments(description: &str) -> String {
+    description
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_owned()
+}
+
+/// Find the value of the last trailer with the given token in a patch message.
+fn trailer<'a>(message: &'a str, token: &str) -> Option<&'a str> {
+    message.lines().rev().find_map(|line| {
+        let (t, value) = line.split_once(": ")?;
+        if t == token {
+            Some(value)
+        } else {
+            None
+        }
+    })
+}
+
+/// Find the base commit trailer in a patch message.
+pub fn base_from_message(message: &str) -> Option<git::Oid> {
+    let base = trailer(message, BASE_TRAILER)?;
+    git2::Oid::from_str(base).ok().map(git::Oid::from)
+}
+
+/// Format the message of a patch tag. Like a git commit message, it consists of:
+///
+/// 1. A subject line, which is the title of the patch normalized to a single line;
+/// 2. A blank line, followed by the description, if any, which may span several
+///    paragraphs;
+/// 3. A blank line, followed by the trailers, one `<token>: <value>` per line.
+///
+/// The title and description can be read back with [`parse_message`].
+pub fn tag_message(title: &str, description: &str, trailers: &[(&str, String)]) -> String {
+    let mut message = cob::normalize_title(title);
+    message.push('\n');
+
+    let description = description.trim();
+    if !description.is_empty() {
+        message.push('\n');
+        message.push_str(description);
+        message.push('\n');
+    }
+    if !trailers.is_empty() {
+        message.push('\n');
+        for (token, value) in trailers {
+            message.push_str(&format!("{}: {}\n", token, value));
+        }
+    }
+    message
+}
+
+/// Whether a paragraph of a patch message is a block of trailers.
+fn is_trailers(paragraph: &str) -> bool {
+    paragraph.lines().all(|line| {
+        line.split_once(": ").map_or(false, |(token, _)| {
+            !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+    })
+}
+
+/// Parse the title and description of a patch tag message, see [`tag_message`].
+/// The trailers and the tag signature, if any, are left out.
+///
+/// Messages written before the format was settled have the description start right
+/// after the title line; these are parsed as well.
+pub fn parse_message(message: &str) -> (String, String) {
+    let message = match message.find(SSH_SIGNATURE_BEGIN) {
+        Some(ix) => &message[..ix],
+        None => message,
+    };
+    let mut paragraphs = message
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>();
+    if paragraphs.len() > 1 && paragraphs.last().map_or(false, |p| is_trailers(p)) {
+        paragraphs.pop();
+    }
+    if paragraphs.is_empty() {
+        return (String::new(), String::new());
+    }
+    let (title, rest) = paragraphs[0]
+        .split_once('\n')
+        .map_or((paragraphs[0], ""), |(title, rest)| (title, rest.trim()));
+    let description = Some(rest)
+        .filter(|rest| !rest.is_empty())
+        .into_iter()
+        .chain(paragraphs[1..].iter().copied())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    (title.trim().to_owned(), description)
+}
+
+/// Split a tag object into the payload signed by git, ie. the raw tag without its signature,
+/// and its SSH signature. Returns `None` if the tag isn't signed.
+fn tag_signature(repo: &git2::Repository, tag: git2::Oid) -> Option<(Vec<u8>, String)> {
+    let object = repo.odb().ok()?.read(tag).ok()?;
+    let data = object.data();
+    // The signature is appended to the tag message, so it's the last one found.
+    let begin = SSH_SIGNATURE_BEGIN.as_bytes();
+    let start = data.windows(begin.len()).rposition(|w| w == begin)?;
+    let (payload, signature) = data.split_at(start);
+
+    Some((
+        payload.to_vec(),
+        String::from_utf8(signature.to_vec()).ok()?,
+    ))
+}
+
+/// Verify the SSH signature of a patch tag against the given public key.
+/// Returns `false` if the tag isn't signed, or not with that key.
+fn verify_tag(repo: &git2::Repository, tag: git2::Oid, key: &str) -> bool {
+    match tag_signature(repo, tag) {
+        Some((payload, signature)) => {
+            crate::git::verify_ssh_signature(&payload, &signature, key).unwrap_or(false)
+        }
+        None => false,
+    }
+}
+
+/// Verify that the tag of a patch is signed by the peer that the patch originated from.
+/// Unsigned tags are never verified.
+///
+/// Nb. This runs `ssh-keygen` for every patch, so listings only verify patches when asked,
+/// eg. with `rad patch --verify`.
+pub fn verify<S>(storage: &S, patch: &Metadata) -> bool
+where
+    S: AsRef<ReadOnly>,
+{
+    let key = match crate::keys::to_ssh_key(&patch.peer.id) {
+        Ok(key) => key,
+        Err(_) => return false,
+    };
+    git2::Repository::open_bare(storage.as_ref().path())
+        .map_or(false, |repo| verify_tag(&repo, *patch.tag, &key))
 }
 
 /// Tries to construct a patch from ['git2::Tag'] and ['project::PeerInfo'].
-/// If the tag name matches the radicle patch prefix, a new patch metadata is
+/// If the tag name matches the given patch prefix, a new patch metadata is
 /// created.
-pub fn from_tag(tag: git2::Tag, info: project::PeerInfo) -> Result<Option<Metadata>, Error> {
+pub fn from_tag(
+    tag: git2::Tag,
+    info: project::PeerInfo,
+    prefix: &str,
+) -> Result<Option<Metadata>, Error> {
     let patch = tag
         .name()
-        .and_then(|name| name.strip_prefix(TAG_PREFIX))
+        .and_then(|name| name.strip_prefix(prefix))
         .map(|id| Metadata {
             id: id.to_owned(),
+            tag: tag.id().into(),
             peer: info,
             message: tag.message().map(|m| m.to_string()),
             commit: tag.target_id().into(),
+            base: tag.message().and_then(base_from_message),
+            stats: tag.message().and_then(DiffStats::from_message),
+            timestamp: tag
+                .tagger()
+                .map(|tagger| Timestamp::new(tagger.when().seconds() as u64)),
         });
 
     Ok(patch)
 }
 
 /// List patches on the local device. Returns a given peer's patches or this peer's
-/// patches if `peer` is `None`.
+/// patches if `peer` is `None`. Only tags under the given `prefix` are considered.
 pub fn all<S>(
     project: &project::Metadata,
     peer: Option<project::PeerInfo>,
     storage: &S,
+    prefix: &str,
 ) -> Result<Vec<Metadata>, Error>
 where
     S: AsRef<ReadOnly>,
@@ -92,7 +626,7 @@ where
                 Ok(Some(object)) => {
                     let tag = object.peel_to_tag()?;
 
-                    if let Some(patch) = from_tag(tag, info.clone())? {
+                    if let Some(patch) = from_tag(tag, info.clone(), prefix)? {
                         patches.push(patch);
                     }
                 }
@@ -109,8 +643,61 @@ where
     Ok(patches)
 }
 
-pub fn state(repo: &git2::Repository, patch: &Metadata) -> State {
-    match merge_base(repo, patch) {
+/// Where to look when determining whether a patch was merged.
+#[derive(Clone, Copy)]
+pub enum StateSource<'a> {
+    /// Check whether the patch commit is reachable from the given target branch.
+    Git(&'a git2::Repository, &'a str),
+    /// Consult the state and merges recorded in patch COBs. This is authoritative across
+    /// peers, since it includes merges that aren't part of the local history yet.
+    Cobs(&'a [(cob::PatchId, cob::Patch)]),
+}
+
+/// Get the state of a patch, using the given source.
+pub fn state_from(source: StateSource, patch: &Metadata) -> State {
+    match source {
+        StateSource::Git(repo, target) => state(repo, patch, target),
+        StateSource::Cobs(cobs) => {
+            let cob = cobs.iter().find(|(_, cob)| {
+                cob.revisions
+                    .iter()
+                    .any(|revision| revision.commit == patch.commit)
+            });
+
+            match cob {
+                Some((_, cob)) => {
+                    let merged = cob.revisions.iter().any(|revision| {
+                        revision.commit == patch.commit && !revision.merges.is_empty()
+                    });
+
+                    if merged {
+                        State::Merged
+                    } else {
+                        cob.state.into()
+                    }
+                }
+                None => State::Open,
+            }
+        }
+    }
+}
+
+/// Get the state of a patch, based on whether its commit is reachable from the head of
+/// the `target` branch. The checked out branch is irrelevant.
+pub fn state(repo: &git2::Repository, patch: &Metadata, target: &str) -> State {
+    state_at(repo, patch, branch_head(repo, target).ok().flatten())
+}
+
+/// Get the state of a patch, based on whether its commit is reachable from the given head
+/// of its target branch, eg. as resolved with [`cob::Patch::target_oid`]. Patches are open
+/// if the target branch wasn't found.
+pub fn state_at(repo: &git2::Repository, patch: &Metadata, head: Option<git2::Oid>) -> State {
+    let head = match head {
+        Some(head) => head,
+        None => return State::Open,
+    };
+
+    match merge_base_between(repo, head, *patch.commit) {
         Ok(Some(merge_base)) => match merge_base == patch.commit {
             true => State::Merged,
             false => State::Open,
@@ -119,20 +706,371 @@ pub fn state(repo: &git2::Repository, patch: &Metadata) -> State {
     }
 }
 
-pub fn merge_base(repo: &git2::Repository, patch: &Metadata) -> Result<Option<git::Oid>, Error> {
-    let head = repo.head()?;
-    let merge_base = match repo.merge_base(head.target().unwrap(), *patch.commit) {
-        Ok(commit) => Some(commit),
-        Err(_) => None,
+/// Get the creation time of a patch. This is the time of its tag if known, and otherwise
+/// the time recorded in its COB.
+pub fn timestamp(cobs: &[(cob::PatchId, cob::Patch)], patch: &Metadata) -> Option<Timestamp> {
+    patch.timestamp.or_else(|| {
+        cobs.iter()
+            .find(|(_, cob)| {
+                cob.revisions
+                    .iter()
+                    .any(|revision| revision.commit == patch.commit)
+            })
+            .map(|(_, cob)| cob.timestamp)
+    })
+}
+
+/// Get the COB of a patch, ie. the one with a revision at the patch commit, if any.
+pub fn find_cob<'a>(
+    cobs: &'a [(cob::PatchId, cob::Patch)],
+    patch: &Metadata,
+) -> Option<&'a (cob::PatchId, cob::Patch)> {
+    cobs.iter().find(|(_, cob)| {
+        cob.revisions
+            .iter()
+            .any(|revision| revision.commit == patch.commit)
+    })
+}
+
+/// Get the best common ancestor of two commits, or `None` if their histories are unrelated.
+pub fn merge_base_between(
+    repo: &git2::Repository,
+    a: git2::Oid,
+    b: git2::Oid,
+) -> Result<Option<git::Oid>, Error> {
+    match repo.merge_base(a, b) {
+        Ok(commit) => Ok(Some(commit.into())),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Find the commit given by `rev`, eg. a commit id or a prefix of one, which must be `head`
+/// or one of its ancestors.
+pub fn find_commit(
+    repo: &git2::Repository,
+    rev: &str,
+    head: git2::Oid,
+) -> Result<git2::Oid, Error> {
+    let commit = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| Error::CommitNotFound(rev.to_owned()))?
+        .id();
+
+    if !is_reachable(repo, commit, head)? {
+        return Err(Error::Unreachable(commit));
+    }
+    Ok(commit)
+}
+
+/// Whether `commit` is `head` or one of its ancestors.
+fn is_reachable(
+    repo: &git2::Repository,
+    commit: git2::Oid,
+    head: git2::Oid,
+) -> Result<bool, Error> {
+    Ok(commit == head || repo.graph_descendant_of(head, commit)?)
+}
+
+/// Submit the branch checked out in `repo` as a patch against the `target` branch.
+///
+/// The patch COB is created first, then the head of the branch is tagged under the patch
+/// tag prefix, with the author, [`DiffStats`] and base of the patch recorded as trailers.
+/// Nothing is pushed: the caller is expected to push the tag and branch to publish the patch.
+/// This doesn't interact with the terminal, so it can be used outside of the CLI.
+///
+/// If `commit` is given, it is proposed rather than the head of the branch. It must be
+/// reachable from the head, otherwise [`Error::Unreachable`] is returned.
+///
+/// If the branch already has a patch tag, [`Error::AlreadyExists`] is returned, unless
+/// `force` is set, in which case the existing patch is updated, see [`update_patch`]. Tags
+/// without a COB are recreated along with a new COB.
+#[allow(clippy::too_many_arguments)]
+pub fn submit_patch(
+    repo: &git2::Repository,
+    patches: &cob::Patches,
+    project: &Urn,
+    title: &str,
+    description: &str,
+    target: &str,
+    labels: &[cob::Label],
+    author: Option<&Urn>,
+    commit: Option<git2::Oid>,
+    force: bool,
+) -> Result<cob::PatchId, Error> {
+    let branch = current_branch(repo)?;
+    // Nb. This is checked before creating the COB, so that no patch is left without a tag,
+    // including that the tag name is valid.
+    let name = tag_name(repo, &branch)?;
+    if !force {
+        ensure_no_patch(repo, &branch)?;
+    } else if let Some(message) = patch_tag_message(repo, &branch)? {
+        if trailer(&message, COB_TRAILER).is_some() {
+            return update_patch(repo, patches, project, title, description, target, commit);
+        }
+    }
+    let (commit, base, stats) = proposed_commit(repo, target, commit)?;
+    let target_ref = target_branch(target)?;
+
+    let patch_id = patches.create(
+        project,
+        title,
+        description,
+        &target_ref,
+        &commit.into(),
+        base.map(git::Oid::from).as_ref(),
+        labels,
+        author,
+    )?;
+    tag_patch(
+        repo,
+        &name,
+        patch_id,
+        title,
+        description,
+        author,
+        &stats,
+        base,
+        commit,
+        force,
+    )?;
+
+    Ok(patch_id)
+}
+
+/// Update the patch of the branch checked out in `repo` with a new revision, and move its
+/// tag to the revision's commit, with the given `title` and `description`. The description
+/// is also the comment of the revision. The author of the patch and its labels are kept.
+///
+/// As with [`submit_patch`], the head of the branch is proposed, unless a `commit` is given.
+/// If the branch has no patch tag, or its tag has no COB, [`Error::NoPatch`] is returned.
+pub fn update_patch(
+    repo: &git2::Repository,
+    patches: &cob::Patches,
+    project: &Urn,
+    title: &str,
+    description: &str,
+    target: &str,
+    commit: Option<git2::Oid>,
+) -> Result<cob::PatchId, Error> {
+    let branch = current_branch(repo)?;
+    let name = tag_name(repo, &branch)?;
+    let message = patch_tag_message(repo, &branch)?.unwrap_or_default();
+    let patch_id = trailer(&message, COB_TRAILER)
+        .and_then(|id| cob::PatchId::from_str(id).ok())
+        .ok_or_else(|| Error::NoPatch(branch.clone()))?;
+    let author = trailer(&message, AUTHOR_TRAILER).and_then(|urn| Urn::from_str(urn).ok());
+    let (commit, base, stats) = proposed_commit(repo, target, commit)?;
+
+    patches.update(
+        project,
+        &patch_id,
+        description,
+        &commit.into(),
+        base.map(git::Oid::from).as_ref(),
+    )?;
+    log::debug!("updated patch {} to {}", patch_id, commit);
+
+    tag_patch(
+        repo,
+        &name,
+        patch_id,
+        title,
+        description,
+        author.as_ref(),
+        &stats,
+        base,
+        commit,
+        true,
+    )?;
+
+    Ok(patch_id)
+}
+
+/// Check that the branch has no patch yet, otherwise [`Error::AlreadyExists`] is returned.
+pub fn ensure_no_patch(repo: &git2::Repository, branch: &str) -> Result<(), Error> {
+    if patch_tag(repo, branch)?.is_some() {
+        return Err(Error::AlreadyExists(branch.to_owned()));
+    }
+    Ok(())
+}
+
+/// Get the message of the patch tag of a branch, if it has one.
+fn patch_tag_message(repo: &git2::Repository, branch: &str) -> Result<Option<String>, Error> {
+    match patch_tag(repo, branch)? {
+        Some(oid) => {
+            let tag = repo.find_tag(oid)?;
+            Ok(Some(tag.message().unwrap_or_default().to_owned()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Get the commit proposed against the `target` branch, either the given `commit` or the
+/// head of the branch checked out in `repo`, along with its base and [`DiffStats`].
+fn proposed_commit(
+    repo: &git2::Repository,
+    target: &str,
+    commit: Option<git2::Oid>,
+) -> Result<(git2::Oid, Option<git2::Oid>, DiffStats), Error> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    let commit = match commit {
+        Some(commit) if !is_reachable(repo, commit, head)? => {
+            return Err(Error::Unreachable(commit))
+        }
+        Some(commit) => commit,
+        None => head,
+    };
+    let base = match branch_head(repo, target)? {
+        Some(target) => merge_base_between(repo, target, commit)?.map(|base| *base),
+        None => None,
     };
+    let stats = DiffStats::between(repo, base, commit)?;
+
+    Ok((commit, base, stats))
+}
+
+/// Tag the commit of a patch under `name`, with the patch title and description as message,
+/// and its COB, author, stats and base as trailers. An existing tag is replaced if `force`
+/// is set.
+#[allow(clippy::too_many_arguments)]
+fn tag_patch(
+    repo: &git2::Repository,
+    name: &str,
+    patch_id: cob::PatchId,
+    title: &str,
+    description: &str,
+    author: Option<&Urn>,
+    stats: &DiffStats,
+    base: Option<git2::Oid>,
+    commit: git2::Oid,
+    force: bool,
+) -> Result<(), Error> {
+    let mut trailers = vec![(COB_TRAILER, patch_id.to_string())];
+    if let Some(author) = author {
+        // Attribute the patch to its author, while we remain the publishing peer.
+        trailers.push((AUTHOR_TRAILER, author.to_string()));
+    }
+    trailers.push((STATS_TRAILER, stats.to_string()));
+    if let Some(base) = base {
+        trailers.push((BASE_TRAILER, base.to_string()));
+    }
+    let message = tag_message(title, description, &trailers);
+    log::debug!("tagging {} as {} for patch {}", commit, name, patch_id);
 
-    Ok(merge_base.map(|o| o.into()))
+    crate::git::add_tag(repo, &message, name, commit, force)?;
+
+    Ok(())
+}
+
+/// Git configuration key of the color of a label, in the working copy.
+pub fn label_color_key(label: &Label) -> String {
+    format!("rad.label.{}.color", label.name())
+}
+
+/// Get the color of a label: the one set in the working copy, if any, otherwise its
+/// default color.
+pub fn label_color(repo: &git2::Repository, label: &Label) -> Color {
+    repo.config()
+        .and_then(|config| config.get_string(&label_color_key(label)))
+        .ok()
+        .and_then(|color| color.parse().ok())
+        .unwrap_or_else(|| label.default_color())
+}
+
+/// Set the color of a label in the working copy.
+pub fn set_label_color(repo: &git2::Repository, label: &Label, color: Color) -> Result<(), Error> {
+    repo.config()?
+        .set_str(&label_color_key(label), &color.to_string())?;
+
+    Ok(())
+}
+
+/// Whether the commit of a revision is available in the given repository, eg. so that it
+/// can be diffed or merged without fetching it first.
+pub fn revision_is_local(repo: &git2::Repository, revision: &cob::Revision) -> bool {
+    repo.find_commit(*revision.commit).is_ok()
+}
+
+/// Get the patch tag of a branch in the working copy, if a patch was created for it.
+pub fn patch_tag(repo: &git2::Repository, branch: &str) -> Result<Option<git2::Oid>, Error> {
+    let name = format!("refs/tags/{}", tag_name(repo, branch)?);
+
+    match repo.find_reference(&name) {
+        Ok(reference) => Ok(reference.target()),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Merge a patch commit into the branch checked out in `repo`, fast-forwarding if possible,
+/// and update the working copy. Otherwise a merge commit is created with the given `message`.
+/// Nothing is changed if the merge has conflicts, which are returned as
+/// [`Error::Conflicts`]. Returns the new head of the branch.
+pub fn merge(
+    repo: &git2::Repository,
+    commit: git2::Oid,
+    message: &str,
+) -> Result<git2::Oid, Error> {
+    let mut head = repo.head()?;
+    if !head.is_branch() {
+        return Err(Error::DetachedHead);
+    }
+    let ours = head.peel_to_commit()?;
+    let theirs = repo.find_commit(commit)?;
+
+    if ours.id() == commit || repo.graph_descendant_of(ours.id(), commit)? {
+        return Err(Error::AlreadyMerged(commit));
+    }
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.safe();
+
+    if repo.graph_descendant_of(commit, ours.id())? {
+        log::debug!("fast-forwarding {} to {}", ours.id(), commit);
+        repo.checkout_tree(theirs.as_object(), Some(&mut checkout))?;
+        head.set_target(commit, message)?;
+
+        return Ok(commit);
+    }
+
+    let mut index = repo.merge_commits(&ours, &theirs, None)?;
+    if index.has_conflicts() {
+        let paths = index
+            .conflicts()?
+            .filter_map(|conflict| conflict.ok())
+            .filter_map(|conflict| conflict.our.or(conflict.their).or(conflict.ancestor))
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .collect();
+
+        return Err(Error::Conflicts(paths));
+    }
+    let tree = repo.find_tree(index.write_tree_to(repo)?)?;
+    let signature = repo.signature()?;
+    log::debug!("merging {} into {}", commit, ours.id());
+
+    // Nb. The working copy is updated before moving the branch, so that it is compared
+    // against the previous head.
+    repo.checkout_tree(tree.as_object(), Some(&mut checkout))?;
+    let oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &[&ours, &theirs],
+    )?;
+
+    Ok(oid)
 }
 
 /// Create a "patch" tag under:
 ///
-/// > /refs/namespaces/<project>/refs/tags/patches/<patch>/<remote>/<revision>
+/// > /refs/namespaces/<project>/refs/tags/<prefix><patch>/<remote>/<revision>
 ///
+/// Where `<prefix>` is the configured patch tag prefix, see [`tag_prefix`]. If a `base` is
+/// given, it is recorded under [`BASE_TRAILER`].
+#[allow(clippy::too_many_arguments)]
 pub fn create_tag(
     repo: &git2::Repository,
     author: &Urn,
@@ -140,28 +1078,37 @@ pub fn create_tag(
     patch_id: cob::PatchId,
     peer_id: &PeerId,
     commit: git2::Oid,
+    base: Option<git2::Oid>,
     revision: usize,
 ) -> Result<git2::Oid, Error> {
     let commit = repo.find_commit(commit)?;
-    let name = format!("{patch_id}/{peer_id}/{revision}");
-    let trailers = [
+    let prefix = tag_prefix(repo);
+    let name = format!("{prefix}{patch_id}/{peer_id}/{revision}");
+    let mut trailers = vec![
         trailers::Trailer {
-            token: "Rad-Cob".try_into().unwrap(),
+            token: COB_TRAILER.try_into().unwrap(),
             values: vec![patch_id.to_string().into()],
         },
         trailers::Trailer {
-            token: "Rad-Author".try_into().unwrap(),
+            token: AUTHOR_TRAILER.try_into().unwrap(),
             values: vec![author.to_string().into()],
         },
         trailers::Trailer {
             token: "Rad-Peer".try_into().unwrap(),
             values: vec![peer_id.to_string().into()],
         },
-    ]
-    .iter()
-    .map(|t| t.display(": ").to_string())
-    .collect::<Vec<_>>()
-    .join("\n");
+    ];
+    if let Some(base) = base {
+        trailers.push(trailers::Trailer {
+            token: BASE_TRAILER.try_into().unwrap(),
+            values: vec![base.to_string().into()],
+        });
+    }
+    let trailers = trailers
+        .iter()
+        .map(|t| t.display(": ").to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
 
     repo.set_namespace(&project.to_string())?;
 
@@ -175,3 +1122,770 @@ pub fn create_tag(
 
     Ok(oid)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_patch_ref_from_str() {
+        let id =
+            cob::PatchId::from(git2::Oid::hash_object(git2::ObjectType::Blob, b"patch").unwrap());
+        let urn = Urn::new(git2::Oid::zero().into());
+
+        assert_eq!(
+            PatchRef::from_str(&id.to_string()).unwrap(),
+            PatchRef { origin: None, id }
+        );
+        assert_eq!(
+            PatchRef::from_str(&format!("{}:{}", urn, id)).unwrap(),
+            PatchRef {
+                origin: Some(project::Origin::from_urn(urn.clone())),
+                id
+            }
+        );
+
+        let patch = PatchRef::from_str(&format!(
+            "rad://willow.radicle.garden/{}/{}",
+            urn.encode_id(),
+            id
+        ))
+        .unwrap();
+        let origin = patch.origin.unwrap();
+        assert_eq!(patch.id, id);
+        assert_eq!(origin.urn, urn);
+        assert_eq!(
+            origin.seed_url().unwrap().as_str(),
+            "https://willow.radicle.garden/"
+        );
+
+        assert!(PatchRef::from_str("rad://willow.radicle.garden").is_err());
+        assert!(PatchRef::from_str(&format!("{}:", urn)).is_err());
+        assert!(PatchRef::from_str("patch").is_err());
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        for state in State::ALL {
+            assert_eq!(state.to_string().parse::<State>().unwrap(), state);
+        }
+        assert_eq!("merged".parse::<State>().unwrap(), State::Merged);
+        assert!("Open".parse::<State>().is_err());
+        assert!("rejected".parse::<State>().is_err());
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        let description = "# Summary\n\nFix the thing.\n\n# Testing\nRan the tests.\n#\n";
+
+        assert_eq!(
+            strip_comments(description),
+            "Fix the thing.\n\nRan the tests."
+        );
+        assert_eq!(strip_comments("# Summary\n"), "");
+        assert_eq!(strip_comments("Fix #12"), "Fix #12");
+    }
+
+    #[test]
+    fn test_description_template() {
+        let (dir, repo) = crate::test::setup::repository();
+        let path = dir.path();
+
+        assert_eq!(description_template(&repo, None).unwrap(), None);
+
+        std::fs::create_dir_all(path.join(".rad")).unwrap();
+        std::fs::write(path.join(TEMPLATE_PATH), "# Summary\n").unwrap();
+        assert_eq!(
+            description_template(&repo, None).unwrap().as_deref(),
+            Some("# Summary\n")
+        );
+
+        let other = path.join("other.md");
+        std::fs::write(&other, "# Testing\n").unwrap();
+        assert_eq!(
+            description_template(&repo, Some(other.as_path()))
+                .unwrap()
+                .as_deref(),
+            Some("# Testing\n")
+        );
+        assert!(description_template(&repo, Some(path.join("missing.md").as_path())).is_err());
+    }
+
+    #[test]
+    fn test_diff_stats_roundtrip() {
+        let stats = DiffStats {
+            files: 3,
+            insertions: 42,
+            deletions: 7,
+        };
+        assert_eq!(stats.to_string().parse::<DiffStats>(), Ok(stats));
+        assert!("files=3 lines=9".parse::<DiffStats>().is_err());
+    }
+
+    #[test]
+    fn test_diff_stats_from_message() {
+        let message = format!(
+            "Fix the thing\nIt was broken.\n\n{}: urn\n{}: files=1 insertions=2 deletions=3",
+            AUTHOR_TRAILER, STATS_TRAILER
+        );
+        assert_eq!(
+            DiffStats::from_message(&message),
+            Some(DiffStats {
+                files: 1,
+                insertions: 2,
+                deletions: 3
+            })
+        );
+        assert_eq!(DiffStats::from_message("Fix the thing"), None);
+    }
+
+    #[test]
+    fn test_base_from_message() {
+        let base = git2::Oid::hash_object(git2::ObjectType::Commit, b"base").unwrap();
+        let message = format!(
+            "Fix the thing\n\n{}: files=1 insertions=2 deletions=3\n{}: {}",
+            STATS_TRAILER, BASE_TRAILER, base
+        );
+        assert_eq!(base_from_message(&message), Some(base.into()));
+        assert_eq!(base_from_message("Fix the thing"), None);
+        assert_eq!(
+            base_from_message(&format!("Fix the thing\n\n{}: HEAD~1", BASE_TRAILER)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tag_message_roundtrip() {
+        let description = "It was broken.\n\nNow it works:\n\n- Foo\n- Bar";
+        let trailers = [
+            (COB_TRAILER, String::from("cob")),
+            (
+                STATS_TRAILER,
+                String::from("files=1 insertions=2 deletions=3"),
+            ),
+        ];
+        let message = tag_message("Fix the thing\n", description, &trailers);
+
+        assert_eq!(
+            message,
+            format!(
+                "Fix the thing\n\n{}\n\n{}: cob\n{}: files=1 insertions=2 deletions=3\n",
+                description, COB_TRAILER, STATS_TRAILER
+            )
+        );
+        assert_eq!(
+            parse_message(&message),
+            (String::from("Fix the thing"), String::from(description))
+        );
+        assert_eq!(DiffStats::from_message(&message).map(|s| s.files), Some(1));
+
+        // Titles are normalized to a single line.
+        let message = tag_message("  Fix\n  the thing ", "", &trailers);
+        assert!(message.starts_with(&format!("Fix the thing\n\n{}: cob\n", COB_TRAILER)));
+        assert_eq!(
+            parse_message(&message),
+            (String::from("Fix the thing"), String::new())
+        );
+
+        // Without trailers.
+        let message = tag_message("Fix: the thing", description, &[]);
+        assert_eq!(
+            parse_message(&message),
+            (String::from("Fix: the thing"), String::from(description))
+        );
+        assert_eq!(
+            parse_message("Fix: the thing\n"),
+            (String::from("Fix: the thing"), String::new())
+        );
+    }
+
+    #[test]
+    fn test_parse_message() {
+        // Messages where the description directly follows the title.
+        assert_eq!(
+            parse_message(&format!(
+                "Fix the thing\nIt was broken.\n\nIt works now.\n\n{}: cob\n",
+                COB_TRAILER
+            )),
+            (
+                String::from("Fix the thing"),
+                String::from("It was broken.\n\nIt works now.")
+            )
+        );
+        // Signed messages.
+        assert_eq!(
+            parse_message(&format!(
+                "Fix the thing\n\nIt was broken.\n\n{}: cob\n{}\nsignature\n",
+                COB_TRAILER, SSH_SIGNATURE_BEGIN
+            )),
+            (
+                String::from("Fix the thing"),
+                String::from("It was broken.")
+            )
+        );
+        assert_eq!(parse_message(""), (String::new(), String::new()));
+    }
+
+    #[test]
+    fn test_all_read_only() {
+        let (storage, profile, _, project) = crate::test::setup::profile();
+        let project = project::get(&storage, &project.urn()).unwrap().unwrap();
+        let repo = git2::Repository::open_bare(profile.paths().git_dir()).unwrap();
+
+        // Publish a patch tag in storage, as pushing a patch does.
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let commit = repo
+            .commit(None, &sig, &sig, "Fix the thing", &tree, &[])
+            .unwrap();
+        let object = repo
+            .find_object(commit, Some(git2::ObjectType::Commit))
+            .unwrap();
+        let message = tag_message("Fix the thing", "It was broken.", &[]);
+        repo.set_namespace(&project.urn.encode_id()).unwrap();
+        repo.tag("patches/feature", &object, &sig, &message, false)
+            .unwrap();
+        repo.remove_namespace().unwrap();
+        Refs::update(&storage, &project.urn).unwrap();
+
+        // Listing patches needs nothing beyond read-only storage.
+        let read_only = crate::profile::read_only(&profile).unwrap();
+        let patches = all(&project, None, &read_only, TAG_PREFIX).unwrap();
+
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].id, "feature");
+        assert_eq!(patches[0].title(), "Fix the thing");
+        assert_eq!(*patches[0].commit, commit);
+        assert!(all(&project, None, &read_only, "rfc/").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_branch_head() {
+        let (_dir, repo) = crate::test::setup::repository();
+
+        assert_eq!(branch_head(&repo, "master").unwrap(), None);
+
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let oid = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+        repo.reference("refs/remotes/rad/master", oid, false, "test")
+            .unwrap();
+
+        assert_eq!(branch_head(&repo, "master").unwrap(), Some(oid));
+
+        let patch = Metadata {
+            id: String::from("feature"),
+            peer: project::PeerInfo {
+                id: PeerId::from(librad::SecretKey::new()),
+                person: None,
+                delegate: false,
+            },
+            message: None,
+            commit: oid.into(),
+            base: None,
+            stats: None,
+            timestamp: None,
+            tag: git2::Oid::zero().into(),
+        };
+        assert_eq!(state_at(&repo, &patch, Some(oid)), State::Merged);
+        assert_eq!(state_at(&repo, &patch, None), State::Open);
+        assert_eq!(state(&repo, &patch, "master"), State::Merged);
+    }
+
+    #[test]
+    fn test_state_against_target_branch() {
+        let (_dir, repo) = crate::test::setup::repository();
+
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let base = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+        let feature = repo
+            .commit(
+                None,
+                &sig,
+                &sig,
+                "Add feature",
+                &tree,
+                &[&repo.find_commit(base).unwrap()],
+            )
+            .unwrap();
+
+        repo.reference("refs/remotes/rad/master", base, false, "test")
+            .unwrap();
+        repo.reference("refs/heads/feature", feature, false, "test")
+            .unwrap();
+        // We're on the feature branch, which contains both commits.
+        repo.set_head("refs/heads/feature").unwrap();
+
+        let patch = |commit: git2::Oid| Metadata {
+            id: String::from("feature"),
+            peer: project::PeerInfo {
+                id: PeerId::from(librad::SecretKey::new()),
+                person: None,
+                delegate: false,
+            },
+            message: None,
+            commit: commit.into(),
+            base: None,
+            stats: None,
+            timestamp: None,
+            tag: git2::Oid::zero().into(),
+        };
+
+        assert_eq!(state(&repo, &patch(base), "master"), State::Merged);
+        assert_eq!(state(&repo, &patch(feature), "master"), State::Open);
+        assert_eq!(state(&repo, &patch(feature), "unknown"), State::Open);
+    }
+
+    #[test]
+    fn test_short_id() {
+        let (_dir, repo) = crate::test::setup::repository();
+        let patch = |id: &str| Metadata {
+            id: id.to_owned(),
+            peer: project::PeerInfo {
+                id: PeerId::from(librad::SecretKey::new()),
+                person: None,
+                delegate: false,
+            },
+            message: None,
+            commit: git2::Oid::zero().into(),
+            base: None,
+            stats: None,
+            timestamp: None,
+            tag: git2::Oid::zero().into(),
+        };
+
+        assert_eq!(patch("feature").short_id(7), "feature");
+        assert_eq!(patch("feature/login").short_id(8), "feature…");
+        assert_eq!(patch("fïx-ünïcode").short_id(4), "fïx…");
+
+        assert_eq!(short_id_length(&repo), SHORT_ID_LENGTH);
+        repo.config()
+            .unwrap()
+            .set_i64(CONFIG_ID_LENGTH_KEY, 12)
+            .unwrap();
+        assert_eq!(short_id_length(&repo), 12);
+    }
+
+    #[test]
+    fn test_default_labels() {
+        let (_dir, repo) = crate::test::setup::repository();
+        let mut config = repo.config().unwrap();
+
+        assert!(default_labels(&repo).unwrap().is_empty());
+
+        config
+            .set_str(
+                CONFIG_DEFAULT_LABELS_KEY,
+                "Needs-Review, good  first issue,",
+            )
+            .unwrap();
+        assert_eq!(
+            default_labels(&repo).unwrap(),
+            vec![
+                Label::new("needs-review").unwrap(),
+                Label::new("good first issue").unwrap()
+            ]
+        );
+
+        config
+            .set_str(CONFIG_DEFAULT_LABELS_KEY, "bug, bad\u{7}label")
+            .unwrap();
+        assert!(matches!(
+            default_labels(&repo),
+            Err(Error::InvalidDefaultLabel(..))
+        ));
+    }
+
+    #[test]
+    fn test_verify_tag() {
+        use std::process::Command;
+
+        let (dir, repo) = crate::test::setup::repository();
+        let keys = crate::test::setup::tempdir();
+        let key = keys.path().join("key");
+
+        let sig = git2::Signature::new(
+            "cloudhead",
+            "cloudhead@radicle.xyz",
+            &git2::Time::new(1651000000, -150),
+        )
+        .unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let commit = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+
+        let unsigned = repo
+            .tag(
+                "patches/unsigned",
+                &repo.find_object(commit, None).unwrap(),
+                &sig,
+                "Fix the thing\n",
+                false,
+            )
+            .unwrap();
+        assert!(tag_signature(&repo, unsigned).is_none());
+
+        // Sign a tag with git, the way a user with SSH signing configured would.
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&key)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let status = Command::new("git")
+            .current_dir(dir.path())
+            .args([
+                "-c",
+                "user.name=cloudhead",
+                "-c",
+                "user.email=cloudhead@radicle.xyz",
+            ])
+            .args(["-c", "gpg.format=ssh", "-c"])
+            .arg(format!("user.signingKey={}", key.display()))
+            .args(["tag", "-s", "-m", "Fix the thing", "patches/signed"])
+            .arg(commit.to_string())
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let signed = repo.refname_to_id("refs/tags/patches/signed").unwrap();
+        let raw = repo.odb().unwrap().read(signed).unwrap().data().to_vec();
+        let (payload, signature) = tag_signature(&repo, signed).unwrap();
+
+        // The payload is exactly what git signs, ie. the tag object without its signature.
+        assert_eq!([payload, signature.clone().into_bytes()].concat(), raw);
+        assert!(signature.starts_with(SSH_SIGNATURE_BEGIN));
+
+        let public = fs::read_to_string(keys.path().join("key.pub")).unwrap();
+        let other = crate::keys::to_ssh_key(&PeerId::from(librad::SecretKey::new())).unwrap();
+
+        assert!(verify_tag(&repo, signed, public.trim()));
+        assert!(!verify_tag(&repo, signed, &other));
+        assert!(!verify_tag(&repo, unsigned, public.trim()));
+    }
+
+    #[test]
+    fn test_tag_name() {
+        let (_dir, repo) = crate::test::setup::repository();
+
+        assert_eq!(
+            tag_name(&repo, "feature/login").unwrap(),
+            "patches/feature/login"
+        );
+        assert_eq!(
+            tag_name(&repo, "fix-ünïcode").unwrap(),
+            "patches/fix-ünïcode"
+        );
+        assert!(matches!(
+            tag_name(&repo, "feature..login"),
+            Err(Error::InvalidTagName(name)) if name == "patches/feature..login"
+        ));
+        assert!(matches!(
+            tag_name(&repo, "feature.lock"),
+            Err(Error::InvalidTagName(_))
+        ));
+        assert!(matches!(tag_name(&repo, ""), Err(Error::InvalidTagName(_))));
+    }
+
+    #[test]
+    fn test_target_branch() {
+        assert_eq!(target_branch("master").unwrap().to_string(), "master");
+        assert_eq!(
+            target_branch("release-1.0").unwrap().to_string(),
+            "release-1.0"
+        );
+        assert!(matches!(
+            target_branch("feature/x"),
+            Err(Error::MultiLevelTarget(name)) if name == "feature/x"
+        ));
+        assert!(matches!(
+            target_branch("refs/heads/master"),
+            Err(Error::MultiLevelTarget(_))
+        ));
+        assert!(matches!(
+            target_branch("feature..x"),
+            Err(Error::InvalidTarget(name)) if name == "feature..x"
+        ));
+        assert!(matches!(target_branch(""), Err(Error::InvalidTarget(_))));
+    }
+
+    #[test]
+    fn test_current_branch() {
+        let (_dir, repo) = crate::test::setup::repository();
+
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let commit = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+
+        for branch in ["feature/login", "fix-ünïcode"] {
+            let name = format!("refs/heads/{}", branch);
+            repo.reference(&name, commit, true, "test").unwrap();
+            repo.set_head(&name).unwrap();
+
+            assert_eq!(current_branch(&repo).unwrap(), branch);
+        }
+        repo.set_head_detached(commit).unwrap();
+        assert!(matches!(current_branch(&repo), Err(Error::DetachedHead)));
+    }
+
+    #[test]
+    fn test_revision_is_local() {
+        let (storage, profile, whoami, project) = crate::test::setup::profile();
+        let patches = cob::Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let (_dir, repo) = crate::test::setup::repository();
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let local = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+
+        for (commit, is_local) in [(local, true), (git2::Oid::zero(), false)] {
+            let id = crate::test::setup::create_patch_at(&patches, &project, commit);
+            let patch = patches.get(&project.urn(), &id).unwrap().unwrap();
+
+            assert_eq!(revision_is_local(&repo, patch.latest_revision()), is_local);
+        }
+    }
+
+    #[test]
+    fn test_patch_tag() {
+        let (_dir, repo) = crate::test::setup::repository();
+
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let commit = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+        let commit = repo.find_object(commit, None).unwrap();
+
+        assert_eq!(patch_tag(&repo, "feature").unwrap(), None);
+
+        let tag = repo
+            .tag("patches/feature", &commit, &sig, "Fix the thing", false)
+            .unwrap();
+        assert_eq!(patch_tag(&repo, "feature").unwrap(), Some(tag));
+        assert_eq!(patch_tag(&repo, "other").unwrap(), None);
+    }
+
+    #[test]
+    fn test_submit_patch_duplicate() {
+        let (storage, profile, whoami, project) = crate::test::setup::profile();
+        let patches = cob::Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let project = project.urn();
+
+        let (_dir, repo) = crate::test::setup::repository();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "cloudhead").unwrap();
+        config
+            .set_str("user.email", "cloudhead@radicle.xyz")
+            .unwrap();
+
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let first = repo
+            .commit(
+                Some("refs/heads/feature"),
+                &sig,
+                &sig,
+                "Initial commit",
+                &tree,
+                &[],
+            )
+            .unwrap();
+        repo.set_head("refs/heads/feature").unwrap();
+
+        assert!(matches!(
+            update_patch(&repo, &patches, &project, "Fix", "", "master", None),
+            Err(Error::NoPatch(branch)) if branch == "feature"
+        ));
+
+        let submit = |force| {
+            submit_patch(
+                &repo,
+                &patches,
+                &project,
+                "Fix",
+                "Fixed.",
+                "master",
+                &[],
+                None,
+                None,
+                force,
+            )
+        };
+        let id = submit(false).unwrap();
+        assert!(matches!(
+            submit(false),
+            Err(Error::AlreadyExists(branch)) if branch == "feature"
+        ));
+        assert!(matches!(
+            ensure_no_patch(&repo, "feature"),
+            Err(Error::AlreadyExists(_))
+        ));
+
+        // Forcing updates the existing patch, rather than creating another one.
+        let parent = repo.find_commit(first).unwrap();
+        let second = repo
+            .commit(Some("HEAD"), &sig, &sig, "Fix", &tree, &[&parent])
+            .unwrap();
+        assert_eq!(submit(true).unwrap(), id);
+
+        let patch = patches.get(&project, &id).unwrap().unwrap();
+        assert_eq!(patch.revisions.len(), 2);
+        assert_eq!(*patch.latest_revision().commit, second);
+        assert_eq!(patches.all(&project).unwrap().patches.len(), 1);
+
+        let tag = repo
+            .find_tag(patch_tag(&repo, "feature").unwrap().unwrap())
+            .unwrap();
+        assert_eq!(tag.target_id(), second);
+        assert_eq!(
+            trailer(tag.message().unwrap(), COB_TRAILER),
+            Some(id.to_string().as_str())
+        );
+    }
+
+    #[test]
+    fn test_find_commit() {
+        let (_dir, repo) = crate::test::setup::repository();
+
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let base = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+        let parent = repo.find_commit(base).unwrap();
+        let head = repo
+            .commit(None, &sig, &sig, "Fix the thing", &tree, &[&parent])
+            .unwrap();
+        let other = repo
+            .commit(None, &sig, &sig, "Other thing", &tree, &[&parent])
+            .unwrap();
+
+        assert_eq!(find_commit(&repo, &head.to_string(), head).unwrap(), head);
+        assert_eq!(
+            find_commit(&repo, &base.to_string()[..7], head).unwrap(),
+            base
+        );
+        assert!(matches!(
+            find_commit(&repo, &other.to_string(), head),
+            Err(Error::Unreachable(oid)) if oid == other
+        ));
+        assert!(matches!(
+            find_commit(&repo, "deadbeef", head),
+            Err(Error::CommitNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_label_color() {
+        let (_dir, repo) = crate::test::setup::repository();
+        let label = Label::new("good first issue").unwrap();
+        let color = Color::new(0x70, 0x57, 0xff);
+
+        assert_eq!(label_color(&repo, &label), label.default_color());
+
+        set_label_color(&repo, &label, color).unwrap();
+        assert_eq!(label_color(&repo, &label), color);
+        assert_eq!(
+            label_color(&repo, &Label::new("bug").unwrap()),
+            Label::new("bug").unwrap().default_color()
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let (dir, repo) = crate::test::setup::repository();
+        let path = dir.path();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("user.name", "cloudhead").unwrap();
+            config
+                .set_str("user.email", "cloudhead@radicle.xyz")
+                .unwrap();
+        }
+        let sig = repo.signature().unwrap();
+        let commit = |parent: Option<git2::Oid>, file: &str, content: &str| {
+            let blob = repo.blob(content.as_bytes()).unwrap();
+            let mut tree = match parent {
+                Some(parent) => {
+                    let parent = repo.find_commit(parent).unwrap().tree().unwrap();
+                    repo.treebuilder(Some(&parent)).unwrap()
+                }
+                None => repo.treebuilder(None).unwrap(),
+            };
+            tree.insert(file, blob, 0o100644).unwrap();
+
+            let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+            let parents = parent
+                .map(|p| repo.find_commit(p).unwrap())
+                .into_iter()
+                .collect::<Vec<_>>();
+            let parents = parents.iter().collect::<Vec<_>>();
+
+            repo.commit(None, &sig, &sig, content, &tree, &parents)
+                .unwrap()
+        };
+
+        let base = commit(None, "README", "base");
+        let feature = commit(Some(base), "README", "feature");
+        let other = commit(Some(base), "LICENSE", "other");
+        let conflicting = commit(Some(base), "README", "conflicting");
+
+        repo.reference("refs/heads/master", base, false, "test")
+            .unwrap();
+        repo.set_head("refs/heads/master").unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+
+        // Fast-forward.
+        assert_eq!(merge(&repo, feature, "Merge feature").unwrap(), feature);
+        assert_eq!(repo.head().unwrap().target(), Some(feature));
+        assert!(matches!(
+            merge(&repo, feature, "Merge feature"),
+            Err(Error::AlreadyMerged(oid)) if oid == feature
+        ));
+
+        // Merge commit.
+        let merged = merge(&repo, other, "Merge other").unwrap();
+        let merged = repo.find_commit(merged).unwrap();
+        assert_eq!(
+            merged.parent_ids().collect::<Vec<_>>(),
+            vec![feature, other]
+        );
+        assert!(path.join("LICENSE").exists());
+
+        // Conflicts leave the branch untouched.
+        assert!(matches!(
+            merge(&repo, conflicting, "Merge conflicting"),
+            Err(Error::Conflicts(paths)) if paths == vec![String::from("README")]
+        ));
+        assert_eq!(repo.head().unwrap().target(), Some(merged.id()));
+    }
+}
@@ -1,5 +1,7 @@
 //! User profile related functions.
+use std::env;
 use std::fmt;
+use std::path::PathBuf;
 
 use anyhow::{anyhow, Error, Result};
 use serde::{de::DeserializeOwned, Serialize};
@@ -26,7 +28,23 @@ where
 }
 
 /// Get the default profile. Fails if there is no profile.
+///
+/// This is the active profile, unless another one was selected with the global
+/// `--profile <id>` option, see [`args::PROFILE_ENV`].
 pub fn default() -> Result<Profile, Error> {
+    if let Ok(id) = env::var(args::PROFILE_ENV) {
+        return list()?
+            .into_iter()
+            .find(|p| p.id().to_string() == id)
+            .ok_or_else(|| {
+                args::Error::WithHint {
+                    err: anyhow!("Could not find radicle profile '{}'", id),
+                    hint: "To list your profiles, run `rad auth`.",
+                }
+                .into()
+            });
+    }
+
     let error = args::Error::WithHint {
         err: anyhow!("Could not load radicle profile"),
         hint: "To setup your radicle profile, run `rad auth`.",
@@ -39,11 +57,44 @@ pub fn default() -> Result<Profile, Error> {
 
     match lnk_profile::get(None, None) {
         Ok(Some(profile)) => Ok(profile),
-        Ok(None) => Err(not_active_error.into()),
+        // If there are profiles, but none is active, the user should pick one of them
+        // rather than create another.
+        Ok(None) => match list() {
+            Ok(profiles) if !profiles.is_empty() => {
+                let ids = profiles
+                    .iter()
+                    .map(|p| p.id().to_string())
+                    .collect::<Vec<_>>();
+
+                Err(none_active_error(&ids).into())
+            }
+            _ => Err(not_active_error.into()),
+        },
         Err(_) => Err(error.into()),
     }
 }
 
+/// Error for when there are profiles, with the given ids, but none of them is active.
+fn none_active_error(ids: &[String]) -> args::Error {
+    args::Error::WithHint {
+        err: anyhow!(
+            "No radicle profile is active, available profiles are: {}",
+            ids.join(", ")
+        ),
+        hint: "To activate one, run `rad auth --switch <id>`, or pass `--profile <id>`.",
+    }
+}
+
+/// Get the id of the profile selected with `--profile`, if it isn't the active profile.
+pub fn overridden() -> Option<String> {
+    let id = env::var(args::PROFILE_ENV).ok()?;
+
+    match lnk_profile::get(None, None) {
+        Ok(Some(active)) if active.id().to_string() == id => None,
+        _ => Some(id),
+    }
+}
+
 /// Get a profile's name. If none is given, get the default profile's name.
 pub fn name(profile: Option<&Profile>) -> Result<String, Error> {
     let default = default()?;
@@ -78,3 +129,39 @@ pub fn read_only(profile: &Profile) -> Result<ReadOnly, Error> {
 
     Ok(storage)
 }
+
+/// Get the path to a profile's git storage, ie. the monorepo.
+pub fn git_dir(profile: &Profile) -> PathBuf {
+    profile.paths().git_dir().to_path_buf()
+}
+
+/// Get the path to a profile's collaborative object cache.
+pub fn cob_cache_dir(profile: &Profile) -> PathBuf {
+    profile.paths().cob_cache_dir().to_path_buf()
+}
+
+/// Get the path to a profile's keys.
+pub fn keys_dir(profile: &Profile) -> PathBuf {
+    profile.paths().keys_dir().to_path_buf()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_none_active_error() {
+        let ids = [String::from("alice"), String::from("bob")];
+
+        match none_active_error(&ids) {
+            args::Error::WithHint { err, hint } => {
+                assert_eq!(
+                    err.to_string(),
+                    "No radicle profile is active, available profiles are: alice, bob"
+                );
+                assert!(hint.contains("rad auth --switch <id>"));
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+}
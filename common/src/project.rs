@@ -2,7 +2,7 @@
 use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::iter;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context as _, Result};
@@ -155,33 +155,43 @@ pub struct PeerInfo {
 }
 
 impl PeerInfo {
+    /// Name of the peer's person, if known, or else its compact peer id, see
+    /// [`crate::fmt::peer`].
     pub fn name(&self) -> String {
+        self.name_with(crate::fmt::COMPACT_CHARS)
+    }
+
+    /// Like [`PeerInfo::name`], keeping `chars` characters at each end of the peer id if
+    /// there is no known name.
+    pub fn name_with(&self, chars: usize) -> String {
         match &self.person {
             Some(person) => person.name.clone(),
-            None => self.id.default_encoding(),
+            None => crate::fmt::peer_with(&self.id, chars),
         }
     }
 
+    /// Whether this peer belongs to one of the project's delegates.
+    pub fn is_delegate(&self, project: &Metadata) -> bool {
+        project.delegates.iter().any(|d| d.contains(&self.id))
+    }
+
     pub fn get<S: AsRef<ReadOnly>>(peer_id: &PeerId, project: &Metadata, storage: &S) -> PeerInfo {
-        let delegate = project.delegates.iter().any(|d| d.contains(peer_id));
+        let mut info = PeerInfo {
+            id: *peer_id,
+            person: None,
+            delegate: false,
+        };
+        info.delegate = info.is_delegate(project);
 
         if let Ok(delegate_urn) = Urn::try_from(Reference::rad_self(
             Namespace::from(project.urn.clone()),
             Some(*peer_id),
         )) {
             if let Ok(Some(identity)) = PeerIdentity::get(&delegate_urn, &storage) {
-                return PeerInfo {
-                    id: *peer_id,
-                    person: Some(identity),
-                    delegate,
-                };
+                info.person = Some(identity);
             }
         }
-        PeerInfo {
-            id: *peer_id,
-            person: None,
-            delegate,
-        }
+        info
     }
 }
 
@@ -240,6 +250,32 @@ pub enum Error {
     MissingDefaultBranch,
 }
 
+/// Error returned when the working directory isn't a project, see [`cwd_checked`].
+#[derive(thiserror::Error, Debug)]
+pub enum CwdError {
+    #[error("{} is not a git repository", .0.display())]
+    NotRepository(PathBuf),
+    #[error("the git repository at {} has no `rad` remote", .0.display())]
+    NoRadRemote(PathBuf),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl CwdError {
+    /// How to get to a working copy of a project, if known.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::NotRepository(_) => Some(
+                "Run this command from a project's working copy, eg. one created with `rad checkout`.",
+            ),
+            Self::NoRadRemote(_) => {
+                Some("To publish this repository as a project, run `rad init` first.")
+            }
+            Self::Other(_) => None,
+        }
+    }
+}
+
 impl TryFrom<librad::identities::Project> for Metadata {
     type Error = Error;
 
@@ -340,6 +376,24 @@ pub fn init(
     Ok(())
 }
 
+/// Phase of a project checkout, reported via the callback passed to [`checkout`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CheckoutProgress {
+    /// Project objects are being fetched from storage and the working copy is being created.
+    Cloning,
+    /// The working copy's git configuration is being finalized.
+    Configuring,
+}
+
+impl fmt::Display for CheckoutProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cloning => write!(f, "Fetching objects and checking out files..."),
+            Self::Configuring => write!(f, "Configuring working copy..."),
+        }
+    }
+}
+
 /// Create a checkout of a radicle project.
 pub fn checkout<S>(
     storage: &S,
@@ -348,11 +402,15 @@ pub fn checkout<S>(
     urn: &Urn,
     peer: Option<PeerId>,
     path: PathBuf,
+    mut progress: impl FnMut(CheckoutProgress),
 ) -> anyhow::Result<git2::Repository>
 where
     S: AsRef<ReadOnly>,
 {
+    progress(CheckoutProgress::Cloning);
     let repo = crate::identities::project::checkout(storage, paths, signer, urn, peer, path)?;
+
+    progress(CheckoutProgress::Configuring);
     // The checkout leaves a leftover config section sometimes, we clean it up here.
     git::git(
         repo.path(),
@@ -409,19 +467,45 @@ pub fn list_remote_heads(
     Ok(remotes)
 }
 
-/// Get a local head of a project.
-pub fn get_local_head<S>(storage: &S, urn: &Urn, branch: &str) -> anyhow::Result<Option<git2::Oid>>
+/// Get the head of a project branch, for the given peer, or the local peer if `None`.
+/// Returns `None` if the peer has no such branch, so it can be used to check that a
+/// branch exists before acting on it.
+pub fn get_head<S>(
+    storage: &S,
+    urn: &Urn,
+    peer: Option<&PeerId>,
+    branch: &str,
+) -> anyhow::Result<Option<git2::Oid>>
 where
     S: AsRef<ReadOnly>,
 {
+    // Open the monorepo.
     let repo = git2::Repository::open_bare(storage.as_ref().path())?;
-    let reference = repo.find_reference(&format!(
-        "refs/namespaces/{}/refs/heads/{}",
-        urn.encode_id(),
-        branch
-    ))?;
 
-    Ok(reference.target())
+    // Nb. `git2` doesn't handle namespaces properly, so we specify it manually.
+    let name = match peer {
+        Some(peer) => format!(
+            "refs/namespaces/{}/refs/remotes/{}/heads/{}",
+            urn.encode_id(),
+            peer,
+            branch
+        ),
+        None => format!("refs/namespaces/{}/refs/heads/{}", urn.encode_id(), branch),
+    };
+
+    match repo.find_reference(&name) {
+        Ok(reference) => Ok(reference.target()),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Get a local head of a project.
+pub fn get_local_head<S>(storage: &S, urn: &Urn, branch: &str) -> anyhow::Result<Option<git2::Oid>>
+where
+    S: AsRef<ReadOnly>,
+{
+    get_head(storage, urn, None, branch)
 }
 
 /// Get the head of a project remote.
@@ -434,18 +518,7 @@ pub fn get_remote_head<S>(
 where
     S: AsRef<ReadOnly>,
 {
-    // Open the monorepo.
-    let repo = git2::Repository::open_bare(storage.as_ref().path())?;
-
-    // Nb. `git2` doesn't handle namespaces properly, so we specify it manually.
-    let reference = repo.find_reference(&format!(
-        "refs/namespaces/{}/refs/remotes/{}/heads/{}",
-        urn.encode_id(),
-        peer,
-        branch
-    ))?;
-
-    Ok(reference.target())
+    get_head(storage, urn, Some(peer), branch)
 }
 
 /// Get project metadata.
@@ -502,8 +575,42 @@ pub fn remote(urn: &Urn, peer: &PeerId, name: &str) -> Result<Remote<LocalUrl>,
 
 /// Get the project URN and repository of the current working directory.
 pub fn cwd() -> anyhow::Result<(Urn, Repository)> {
-    let repo = git::repository()?;
-    let urn = self::rad_remote(&repo)?.url.urn;
+    cwd_checked().map_err(anyhow::Error::from)
+}
+
+/// Like [`cwd`], but distinguishes a working directory that isn't a git repository from
+/// a repository that isn't a project, and reports the directory.
+pub fn cwd_checked() -> Result<(Urn, Repository), CwdError> {
+    let path = std::env::current_dir().context("could not get the current directory")?;
+
+    at(&path)
+}
+
+/// Get the project URN and repository at the given path.
+fn at(path: &Path) -> Result<(Urn, Repository), CwdError> {
+    let repo = match Repository::open(path) {
+        Ok(repo) => repo,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {
+            return Err(CwdError::NotRepository(path.to_owned()))
+        }
+        Err(err) => {
+            return Err(anyhow::Error::new(err)
+                .context(format!(
+                    "could not open git repository at {}",
+                    path.display()
+                ))
+                .into())
+        }
+    };
+    let urn = match Remote::<LocalUrl>::find(&repo, reflike!("rad")) {
+        Ok(Some(remote)) => remote.url.urn,
+        Ok(None) => return Err(CwdError::NoRadRemote(path.to_owned())),
+        Err(err) => {
+            return Err(anyhow::Error::new(err)
+                .context("could not read git remote configuration")
+                .into())
+        }
+    };
 
     Ok((urn, repo))
 }
@@ -599,6 +706,75 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_peer_info_is_delegate() {
+        let direct = PeerId::from(librad::SecretKey::new());
+        let indirect = PeerId::from(librad::SecretKey::new());
+        let other = PeerId::from(librad::SecretKey::new());
+        let project = Metadata {
+            urn: Urn::new(git2::Oid::zero().into()),
+            name: String::from("acme"),
+            description: String::new(),
+            default_branch: String::from("master"),
+            delegates: vec![
+                Delegate::Direct { id: direct },
+                Delegate::Indirect {
+                    urn: Urn::new(git2::Oid::zero().into()),
+                    ids: iter::once(indirect).collect(),
+                },
+            ],
+            remotes: HashSet::new(),
+        };
+        let info = |id| PeerInfo {
+            id,
+            person: None,
+            delegate: false,
+        };
+
+        assert!(info(direct).is_delegate(&project));
+        assert!(info(indirect).is_delegate(&project));
+        assert!(!info(other).is_delegate(&project));
+    }
+
+    #[test]
+    fn test_peer_info_name() {
+        let id = PeerId::from(librad::SecretKey::new());
+        let encoded = id.default_encoding();
+        let mut info = PeerInfo {
+            id,
+            person: None,
+            delegate: false,
+        };
+
+        assert_eq!(
+            info.name(),
+            format!("{}…{}", &encoded[..7], &encoded[encoded.len() - 7..])
+        );
+        assert_eq!(
+            info.name_with(4),
+            format!("{}…{}", &encoded[..4], &encoded[encoded.len() - 4..])
+        );
+        assert_eq!(info.name_with(encoded.len()), encoded);
+
+        info.person = Some(PeerIdentity {
+            urn: Urn::new(git2::Oid::zero().into()),
+            name: String::from("cloudhead"),
+            ens: None,
+        });
+        assert_eq!(info.name(), "cloudhead");
+    }
+
+    #[test]
+    fn test_cwd_errors() {
+        let dir = crate::test::setup::tempdir();
+        let path = dir.path().to_path_buf();
+
+        assert!(matches!(at(&path), Err(CwdError::NotRepository(p)) if p == path));
+
+        Repository::init(&path).unwrap();
+        assert!(matches!(at(&path), Err(CwdError::NoRadRemote(p)) if p == path));
+    }
+
     #[test]
     fn test_origin_from_str() {
         let origin = Origin::from_str("rad:git:hnrkbjg7r54q48sqsaho1n4qfxhi4nbmdh51y").unwrap();
@@ -13,6 +13,9 @@ use crate::{git, project};
 
 pub const CONFIG_SEED_KEY: &str = "rad.seed";
 pub const CONFIG_PEER_KEY: &str = "rad.peer";
+/// Environment variable holding the seed to use, eg. `willow.radicle.garden`. It takes
+/// precedence over the configured seed, but not over a seed given on the command line.
+pub const SEED_ENV: &str = "RAD_SEED";
 pub const DEFAULT_SEEDS: &[&str] = &[
     "pine.radicle.garden",
     "willow.radicle.garden",
@@ -68,6 +71,15 @@ impl Address {
             _ => Url::parse(&format!("http://{}", self)).unwrap(),
         }
     }
+
+    /// Get the address of a seed URL, eg. as configured under [`CONFIG_SEED_KEY`]. Returns
+    /// `None` if the URL has no host.
+    pub fn from_url(url: &Url) -> Option<Self> {
+        Some(Self {
+            host: url.host()?.to_owned(),
+            port: url.port(),
+        })
+    }
 }
 
 impl std::fmt::Display for Address {
@@ -144,6 +156,17 @@ pub fn get_seed(scope: Scope) -> Result<Url, anyhow::Error> {
     Ok(url)
 }
 
+/// Get the seed set in the environment, under [`SEED_ENV`], if any.
+pub fn env_seed() -> Result<Option<Address>, anyhow::Error> {
+    match std::env::var(SEED_ENV) {
+        Ok(value) if !value.is_empty() => value
+            .parse()
+            .map(Some)
+            .context(format!("`{}` is not set to a valid seed address", SEED_ENV)),
+        _ => Ok(None),
+    }
+}
+
 /// Set the configured seed within a scope.
 pub fn set_seed(seed: &Url, scope: Scope) -> Result<(), anyhow::Error> {
     let seed = seed.as_str();
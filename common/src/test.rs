@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use std::fmt;
 use std::path::Path;
 use std::{env, error};
@@ -18,8 +19,13 @@ use librad::keystore::Keystore;
 use librad::profile::{Profile, LNK_HOME};
 use librad::PublicKey;
 
+use radicle_git_ext as git;
+
+use super::cobs::patch::{PatchId, Patches};
 use super::{keys, person, profile, project, signer, test};
 
+pub use tempfile::TempDir;
+
 pub type BoxedError = Box<dyn error::Error>;
 
 pub const USER_PASS: &str = "password";
@@ -59,6 +65,44 @@ pub mod setup {
 
         (storage, profile, whoami, project)
     }
+
+    /// Create a patch of the given project, proposing the zero commit against `master`.
+    pub fn create_patch(patches: &Patches, project: &Project) -> PatchId {
+        create_patch_at(patches, project, git2::Oid::zero())
+    }
+
+    /// Create a patch of the given project, proposing `commit` against `master`.
+    pub fn create_patch_at(patches: &Patches, project: &Project, commit: git2::Oid) -> PatchId {
+        let target = git::OneLevel::try_from(git::RefLike::try_from("master").unwrap()).unwrap();
+
+        patches
+            .create(
+                &project.urn(),
+                "My first patch",
+                "Blah blah blah.",
+                &target,
+                &commit.into(),
+                None,
+                &[],
+                None,
+            )
+            .unwrap()
+    }
+
+    /// Create an empty temporary directory, unique to the caller. It is removed when the
+    /// returned [`TempDir`] is dropped.
+    pub fn tempdir() -> TempDir {
+        tempfile::Builder::new().prefix("rad-").tempdir().unwrap()
+    }
+
+    /// Initialize a git repository in a temporary directory, see [`tempdir`]. The handle
+    /// must be kept alive for as long as the repository is used.
+    pub fn repository() -> (TempDir, git2::Repository) {
+        let dir = tempdir();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+
+        (dir, repo)
+    }
 }
 
 pub mod teardown {
@@ -1,14 +1,17 @@
 #![allow(clippy::or_fun_call)]
 use std::convert::TryFrom;
 use std::ffi::OsString;
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::str::FromStr;
 
 use radicle_common::args::{Args, Error, Help};
-use radicle_common::{git, profile, project};
+use radicle_common::cobs::patch::{PatchId, Patches};
+use radicle_common::{git, keys, person, profile, project};
 use radicle_terminal as term;
 
+use librad::collaborative_objects::EntryContents;
 use librad::git::identities::any;
 use librad::git::storage::ReadOnlyStorage;
 use librad::git::types::Reference;
@@ -35,10 +38,11 @@ Usage
 
 Options
 
-    --payload   Inspect the object's payload
-    --refs      Inspect the object's refs on the local device (requires `tree`)
-    --history   Show object's history
-    --help      Print help
+    --payload       Inspect the object's payload
+    --refs          Inspect the object's refs on the local device (requires `tree`)
+    --history       Show object's history
+    --patch <id>    Show the history of the given patch of the project
+    --help          Print help
 "#,
 };
 
@@ -49,6 +53,7 @@ pub struct Options {
     pub refs: bool,
     pub payload: bool,
     pub history: bool,
+    pub patch: Option<PatchId>,
 }
 
 impl Args for Options {
@@ -61,6 +66,7 @@ impl Args for Options {
         let mut refs = false;
         let mut payload = false;
         let mut history = false;
+        let mut patch: Option<PatchId> = None;
 
         while let Some(arg) = parser.next()? {
             match arg {
@@ -76,6 +82,14 @@ impl Args for Options {
                 Long("history") => {
                     history = true;
                 }
+                Long("patch") => {
+                    let val = parser.value()?;
+                    let val = val.to_string_lossy();
+                    let id = PatchId::from_str(&val)
+                        .map_err(|_| anyhow!("invalid patch id '{}'", val))?;
+
+                    patch = Some(id);
+                }
                 Value(val) if path.is_none() && urn.is_none() => {
                     let val = val.to_string_lossy();
 
@@ -98,6 +112,7 @@ impl Args for Options {
                 path,
                 payload,
                 history,
+                patch,
                 refs,
                 urn,
             },
@@ -120,7 +135,7 @@ pub fn run(options: Options) -> anyhow::Result<()> {
     };
 
     if options.refs {
-        let path = profile.paths().git_dir().join("refs").join("namespaces");
+        let path = profile::git_dir(&profile).join("refs").join("namespaces");
 
         Command::new("tree")
             .current_dir(path)
@@ -139,6 +154,55 @@ pub fn run(options: Options) -> anyhow::Result<()> {
             "{}",
             serde_json::to_string_pretty(&payload)?.to_colored_json_auto()?
         );
+    } else if let Some(patch_id) = options.patch {
+        let signer = term::signer(&profile)?;
+        let storage = keys::storage(&profile, signer)?;
+        let whoami = person::local(&storage)?;
+        let patches = Patches::new(whoami, profile.paths(), &storage)?;
+        let cob = patches.get_object(&urn, &patch_id)?.ok_or(anyhow!(
+            "Couldn't find patch {} in {}",
+            patch_id,
+            urn
+        ))?;
+
+        let entries = cob.history().traverse(Vec::new(), |mut entries, entry| {
+            let change = match entry.contents() {
+                EntryContents::Automerge(bytes) => automerge::Change::from_bytes(bytes.clone()),
+            };
+            let (hash, message) = match change {
+                Ok(change) => (
+                    change.hash.to_string(),
+                    change.message().unwrap_or_default(),
+                ),
+                Err(err) => (format!("<invalid change: {}>", err), String::new()),
+            };
+            let author = entry
+                .author()
+                .as_ref()
+                .map(|urn| urn.to_string())
+                .unwrap_or_else(|| String::from("<unknown>"));
+
+            entries.push(format!(
+                "{}
+change {}
+author {}
+actor  {}
+
+{}",
+                term::format::yellow(format!("entry  {}", entry.id())),
+                term::format::dim(hash),
+                term::format::dim(author),
+                term::format::dim(entry.actor()),
+                message,
+            ));
+            ControlFlow::Continue(entries)
+        });
+
+        for (i, entry) in entries.into_iter().enumerate() {
+            print!("{}", term::TextBox::new(entry).first(i == 0).last(false));
+        }
+        println!(" └─ {}", term::format::highlight(patch_id.to_string()));
+        println!();
     } else if options.history {
         let branch = Reference::try_from(&urn)?;
         match storage.reference(&branch) {
@@ -126,7 +126,7 @@ pub fn run(options: Options) -> anyhow::Result<()> {
     let profile = profile::default()?;
     let signer = term::signer(&profile)?;
     let storage = keys::storage(&profile, signer)?;
-    let (project, _) = project::cwd()?;
+    let (project, repo) = project::cwd()?;
     let whoami = person::local(&storage)?;
     let issues = Issues::new(whoami, profile.paths(), &storage)?;
 
@@ -149,7 +149,11 @@ pub fn run(options: Options) -> anyhow::Result<()> {
                 description.unwrap_or("Enter a description...".to_owned())
             );
 
-            if let Some(text) = term::Editor::new().edit(&doc)? {
+            let editor = term::editor::command(Some(&repo));
+            let text = term::editor::edit(&editor, &doc)
+                .with_context(|| format!("couldn't run editor '{}'", editor))?;
+
+            if let Some(text) = text {
                 let mut meta = String::new();
                 let mut frontmatter = false;
                 let mut lines = text.lines();
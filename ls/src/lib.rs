@@ -1,7 +1,7 @@
 use std::ffi::OsString;
 
 use radicle_common::args::{Args, Error, Help};
-use radicle_common::{profile, project};
+use radicle_common::{git, profile, project};
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -47,9 +47,7 @@ pub fn run(_options: Options) -> anyhow::Result<()> {
     let mut table = term::Table::default();
 
     for (urn, meta, head) in projs {
-        let head = head
-            .map(|h| format!("{:.7}", h.to_string()))
-            .unwrap_or_else(String::new);
+        let head = head.map(git::short).unwrap_or_else(String::new);
 
         table.push([
             term::format::bold(meta.name),
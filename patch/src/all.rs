@@ -0,0 +1,92 @@
+//! Listing of patches across all local projects, eg. as an inbox for maintainers.
+use librad::profile::Profile;
+
+use radicle_common::cobs::patch::{Patch, PatchesReadOnly};
+use radicle_common::{patch, profile, project};
+use radicle_terminal as term;
+
+use super::{Options, TITLE_WIDTH};
+
+/// List the patches of every project in storage, grouped by project. Projects without
+/// patches are skipped. Since there is no working copy, patches are shown as found in
+/// their COBs.
+pub fn run(profile: &Profile, options: &Options) -> anyhow::Result<()> {
+    let storage = profile::read_only(profile)?;
+    let patches = PatchesReadOnly::new(&storage)?;
+    let mut projects = project::list(&storage)?;
+    projects.sort_by(|(_, a, _), (_, b, _)| a.name.cmp(&b.name));
+
+    term::headline("🌱 Listing patches across all projects.");
+
+    let mut found = false;
+    for (urn, project, _) in projects {
+        let loaded = patches.all(&urn)?;
+        for id in &loaded.failed {
+            term::warning(&format!(
+                "Patch {} of {} could not be loaded, skipping it.",
+                id, project.name
+            ));
+        }
+        let mut cobs = loaded
+            .patches
+            .into_iter()
+            .filter(|(_, cob)| options.state.map_or(true, |s| s == state(cob)))
+            .filter(|(_, cob)| {
+                options
+                    .reviewed_by
+                    .as_ref()
+                    .map_or(true, |reviewer| cob.is_reviewed_by(reviewer))
+            })
+            .collect::<Vec<_>>();
+        if cobs.is_empty() {
+            continue;
+        }
+        found = true;
+
+        cobs.sort_by_key(|(_, cob)| std::cmp::Reverse(cob.timestamp));
+        if options.reverse {
+            cobs.reverse();
+        }
+        // As when listing the patches of a single project, the limit applies per state.
+        let limit = options.limit.unwrap_or(usize::MAX);
+        let sections = patch::State::ALL.iter().map(|s| {
+            cobs.iter()
+                .filter(move |(_, cob)| state(cob) == *s)
+                .take(limit)
+        });
+
+        term::info!(
+            "{} {}",
+            term::format::bold(&project.name),
+            term::format::dim(&urn)
+        );
+        let mut table = term::Table::default();
+        table
+            .max_width(1, TITLE_WIDTH)
+            .align(2, term::Alignment::Right);
+
+        for (id, cob) in sections.flatten() {
+            table.push([
+                format!("[{}]", state(cob)),
+                term::format::bold(&cob.title),
+                term::format::tertiary(id),
+            ]);
+        }
+        table.render();
+        term::blank();
+    }
+
+    if !found {
+        term::info!("No patches found.");
+    }
+    Ok(())
+}
+
+/// State of a patch as far as its COB knows. A patch is merged once any of its revisions
+/// was merged.
+fn state(cob: &Patch) -> patch::State {
+    if cob.revisions.iter().any(|r| !r.merges.is_empty()) {
+        return patch::State::Merged;
+    }
+    cob.state.into()
+}
@@ -0,0 +1,83 @@
+//! Fetching of peer patches from the seed, prior to listing them.
+use std::thread;
+
+use anyhow::anyhow;
+
+use librad::git::Storage;
+use librad::PeerId;
+
+use radicle_common as common;
+use radicle_common::{project, seed};
+use radicle_terminal as term;
+
+/// Maximum number of peers fetched from at once.
+pub const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// Fetch the refs of the given peers from their seed, so that their patches are up to date.
+/// Failures are reported per peer on stderr, without aborting the others. With `quiet`,
+/// nothing is printed to stdout, eg. so that it only has the patches listed as JSON.
+pub fn run<'a>(
+    storage: &Storage,
+    project: &project::Metadata,
+    peers: impl IntoIterator<Item = &'a PeerId>,
+    quiet: bool,
+) -> anyhow::Result<()> {
+    let seed = match seed::env_seed()? {
+        Some(addr) => addr.url(),
+        None => seed::get_seed(seed::Scope::Any)?,
+    };
+    let peers = peers.into_iter().copied().collect::<Vec<_>>();
+    let mut spinner = term::spinner(&format!("Fetching patches from {} peer(s)...", peers.len()));
+    let mut failures = Vec::new();
+
+    for chunk in peers.chunks(MAX_CONCURRENT_FETCHES) {
+        let fetches = chunk
+            .iter()
+            .map(|peer| {
+                let peer = *peer;
+                let path = storage.path().to_path_buf();
+                let urn = project.urn.clone();
+                let seed = seed::get_peer_seed(&peer).unwrap_or_else(|_| seed.clone());
+
+                let handle =
+                    thread::spawn(move || seed::fetch_remotes(&path, &seed, &urn, [&peer]));
+
+                (peer, handle)
+            })
+            .collect::<Vec<_>>();
+
+        for (peer, handle) in fetches {
+            let result = handle
+                .join()
+                .unwrap_or_else(|_| Err(anyhow!("fetch thread panicked")))
+                .and_then(|_| {
+                    term::sync::verify_signed_refs(storage, &project.urn, [&peer], &mut spinner)
+                });
+
+            if let Err(err) = result {
+                failures.push((peer, err));
+            }
+        }
+    }
+
+    // Nb. Failures are reported on stderr, so they are reported even if `quiet`.
+    if !failures.is_empty() {
+        spinner.failed();
+    } else if quiet {
+        spinner.clear();
+    } else {
+        spinner.finish();
+    }
+    for (peer, err) in failures {
+        term::warning(&format!(
+            "Failed to fetch patches from {}: {}",
+            common::fmt::peer(&peer),
+            err
+        ));
+    }
+    if !quiet {
+        term::blank();
+    }
+
+    Ok(())
+}
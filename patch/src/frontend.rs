@@ -0,0 +1,84 @@
+//! User interaction, so that patch flows can be driven without a terminal, eg. in tests.
+use anyhow::Context as _;
+
+use radicle_common::git;
+use radicle_terminal as term;
+
+/// Interactions with the user that creating a patch requires. The terminal is the usual
+/// frontend, but flows can be driven by other means, eg. with scripted answers.
+pub trait Frontend {
+    /// Ask the user a yes/no question, defaulting to yes.
+    fn confirm(&mut self, prompt: &str) -> bool;
+    /// Ask the user for a line of text, pre-filled with `initial`, if given.
+    fn text_input(&mut self, prompt: &str, initial: Option<String>) -> anyhow::Result<String>;
+    /// Let the user edit `text`, and return the edited text. Returns `None` if the edit
+    /// was aborted.
+    fn edit(&mut self, text: &str) -> anyhow::Result<Option<String>>;
+}
+
+/// The terminal, with text edited in the user's editor.
+pub struct Terminal {
+    editor: String,
+}
+
+impl Terminal {
+    /// Use the editor configured for the given working copy, see [`term::editor::command`].
+    pub fn new(repo: Option<&git::Repository>) -> Self {
+        Self {
+            editor: term::editor::command(repo),
+        }
+    }
+}
+
+impl Frontend for Terminal {
+    fn confirm(&mut self, prompt: &str) -> bool {
+        term::confirm(prompt)
+    }
+
+    fn text_input(&mut self, prompt: &str, initial: Option<String>) -> anyhow::Result<String> {
+        term::text_input(prompt, initial)
+    }
+
+    fn edit(&mut self, text: &str) -> anyhow::Result<Option<String>> {
+        let edited = term::editor::edit(&self.editor, text)
+            .with_context(|| format!("couldn't run editor '{}'", self.editor))?;
+
+        if edited.is_none() {
+            term::warning(&format!(
+                "The editor '{}' exited with an error.",
+                self.editor
+            ));
+        }
+        Ok(edited)
+    }
+}
+
+/// A frontend answering with a script, in order. Runs out of answers with a panic, so
+/// that unexpected prompts fail the test.
+#[cfg(test)]
+#[derive(Default)]
+pub struct Scripted {
+    pub confirms: std::collections::VecDeque<bool>,
+    pub inputs: std::collections::VecDeque<String>,
+    pub edits: std::collections::VecDeque<Option<String>>,
+}
+
+#[cfg(test)]
+impl Frontend for Scripted {
+    fn confirm(&mut self, prompt: &str) -> bool {
+        self.confirms
+            .pop_front()
+            .unwrap_or_else(|| panic!("unexpected confirmation: {}", prompt))
+    }
+
+    fn text_input(&mut self, prompt: &str, _initial: Option<String>) -> anyhow::Result<String> {
+        Ok(self
+            .inputs
+            .pop_front()
+            .unwrap_or_else(|| panic!("unexpected input: {}", prompt)))
+    }
+
+    fn edit(&mut self, _text: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.edits.pop_front().expect("unexpected edit"))
+    }
+}
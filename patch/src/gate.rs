@@ -0,0 +1,115 @@
+//! Gating of merges on the merger's ethereum account, for projects with on-chain
+//! governance. Merges are only gated if the working copy is configured to.
+//!
+//! The account is the one of the merger's wallet, eg. their Ledger device, so the merger
+//! can't claim the balance of an account they don't hold.
+use anyhow::anyhow;
+
+use radicle_common::args::Error;
+use radicle_common::ethereum::ethers::prelude::{Signer, U256};
+use radicle_common::ethereum::ethers::utils::parse_ether;
+use radicle_common::ethereum::{self, ProviderOptions, SignerOptions};
+use radicle_common::git;
+use radicle_terminal as term;
+
+/// Git configuration key of the minimum balance, in ether, of the merger's account.
+/// Merges are gated only if this is set in the working copy's own configuration.
+pub const CONFIG_MIN_BALANCE_KEY: &str = "rad.patch.mergeMinBalance";
+
+/// Requirement on the merger's account.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Gate {
+    /// Minimum balance of the account, in wei.
+    pub min_balance: U256,
+    /// Minimum balance as configured, in ether.
+    pub min_ether: String,
+}
+
+impl Gate {
+    /// Get the gate configured in the working copy, if any. Global and system settings
+    /// are ignored, since gates are specific to a project.
+    pub fn get(repo: &git::Repository) -> anyhow::Result<Option<Self>> {
+        let config = repo.config()?.open_level(git::ConfigLevel::Local)?;
+        let min_ether = match config.get_string(CONFIG_MIN_BALANCE_KEY) {
+            Ok(min) => min,
+            Err(_) => return Ok(None),
+        };
+        let min_balance = parse_ether(&min_ether).map_err(|_| {
+            anyhow!(
+                "invalid value '{}' of '{}', expected an amount of ether",
+                min_ether,
+                CONFIG_MIN_BALANCE_KEY
+            )
+        })?;
+
+        Ok(Some(Self {
+            min_balance,
+            min_ether,
+        }))
+    }
+}
+
+/// Check that the account of the merger's wallet holds the configured minimum balance.
+/// Does nothing unless the working copy is configured to gate merges.
+pub fn check(repo: &git::Repository, signer: SignerOptions) -> anyhow::Result<()> {
+    let gate = match Gate::get(repo)? {
+        Some(gate) => gate,
+        None => return Ok(()),
+    };
+    let provider = ethereum::provider(ProviderOptions::default())?;
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let (wallet, provider) = rt.block_on(term::ethereum::get_wallet(signer, provider))?;
+    let account = wallet.address();
+
+    let spinner = term::spinner("Checking account balance...");
+    let balance = match rt.block_on(rad_account::balance(&provider, account)) {
+        Ok(balance) => balance,
+        Err(err) => {
+            spinner.failed();
+            return Err(err);
+        }
+    };
+    if balance < gate.min_balance {
+        spinner.failed();
+
+        return Err(Error::WithHint {
+            err: anyhow!(
+                "account {:?} holds less than the {} ETH required to merge",
+                account,
+                gate.min_ether
+            ),
+            hint: "Fund the account, or pass `--no-account-check` to merge anyway.",
+        }
+        .into());
+    }
+    spinner.finish();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gate() {
+        let (_dir, repo) = radicle_common::test::setup::repository();
+        let mut config = repo
+            .config()
+            .unwrap()
+            .open_level(git::ConfigLevel::Local)
+            .unwrap();
+
+        assert_eq!(Gate::get(&repo).unwrap(), None);
+
+        config.set_str(CONFIG_MIN_BALANCE_KEY, "1.5").unwrap();
+        let gate = Gate::get(&repo).unwrap().unwrap();
+        assert_eq!(gate.min_balance, parse_ether("1.5").unwrap());
+        assert_eq!(gate.min_ether, "1.5");
+
+        config.set_str(CONFIG_MIN_BALANCE_KEY, "lots").unwrap();
+        assert!(Gate::get(&repo).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Patch history, ie. the audit trail of changes made to a patch.
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use librad::git::Storage;
+
+use radicle_common::cobs::patch::{PatchId, Patches};
+use radicle_common::cobs::{Author, Timestamp};
+use radicle_common::{git, project};
+use radicle_terminal as term;
+
+/// Show the changes made to a patch, oldest first, with their author and time.
+pub fn run(
+    storage: &Storage,
+    patches: &Patches,
+    project: &project::Metadata,
+    patch_id: &PatchId,
+) -> anyhow::Result<()> {
+    let history = patches
+        .history(&project.urn, patch_id)?
+        .ok_or_else(|| anyhow!("patch {} was not found in {}", patch_id, project.name))?;
+    // Changes are stored as commits, which record when they were made.
+    let repo = git::Repository::open_bare(storage.path())?;
+
+    term::headline(&format!(
+        "🌱 History of patch {}",
+        term::format::highlight(patch_id)
+    ));
+
+    let mut table = term::Table::default();
+    for entry in history {
+        let time = git::Oid::from_str(&entry.id)
+            .ok()
+            .and_then(|oid| repo.find_commit(oid).ok())
+            .map(|commit| term::format::timeago(Timestamp::new(commit.time().seconds() as u64)))
+            .unwrap_or_default();
+        let author = match entry.author {
+            Some(urn) => {
+                let mut author = Author::Urn { urn };
+                author.resolve(storage).ok();
+                author.to_string()
+            }
+            None => String::from("unknown"),
+        };
+
+        table.push([term::format::bold(&entry.message), term::format::dim(time)]);
+        table.push([
+            term::format::italic(format!("└── by {}", author)),
+            term::format::tertiary(git::short(&entry.id)),
+        ]);
+    }
+    table.render();
+    term::blank();
+
+    Ok(())
+}
@@ -1,14 +1,47 @@
+use std::collections::HashMap;
 use std::ffi::OsString;
+use std::fmt;
+use std::io::{self, Read as _};
+use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
 
 use anyhow::anyhow;
+use anyhow::Context as _;
 
 use librad::git::storage::ReadOnly;
 use librad::git::Storage;
+use librad::git::Urn;
+use librad::profile::Profile;
 
-use radicle_common::args::{Args, Error, Help};
-use radicle_common::{git, keys, patch, profile, project};
+use radicle_common as common;
+use radicle_common::args::{self, Args, Error, Help};
+use radicle_common::cobs::patch::{Patch, PatchId, Patches, PatchesReadOnly, RevisionId, Verdict};
+use radicle_common::cobs::seen::Seen;
+use radicle_common::cobs::{Color, Label, Timestamp};
+#[cfg(feature = "ethereum")]
+use radicle_common::ethereum;
+use radicle_common::{git, keys, patch, person, profile, project, seed};
 use radicle_terminal as term;
 
+use frontend::Frontend;
+
+mod all;
+mod fetch;
+mod frontend;
+#[cfg(feature = "ethereum")]
+mod gate;
+mod history;
+mod merge;
+mod process;
+mod reactions;
+mod review;
+mod show;
+mod status;
+mod sync;
+
 pub const HELP: Help = Help {
     name: "patch",
     description: env!("CARGO_PKG_DESCRIPTION"),
@@ -17,32 +50,596 @@ pub const HELP: Help = Help {
 Usage
 
     rad patch [<option>...]
+    rad patch --list [<option>...]
+    rad patch update [<option>...]
+    rad patch show <id> [--revision <n>] [--diff] [--format json] [<option>...]
+    rad patch merge <id> [<option>...]
+    rad patch status [<option>...]
+    rad patch log <id> [<option>...]
+    rad patch reactions <id> [<option>...]
+    rad patch sync <id> [--seed <host>...] [<option>...]
+    rad patch review <patch> [--accept | --reject] [--message <text>] [<option>...]
+    rad patch delete <id> [--cob] [<option>...]
+    rad patch label <name> [--color <hex>] [<option>...]
+    rad patch labels [<option>...]
 
 Options
 
-    --list    List all patches (default: false)
-    --help    Print help
+    --list              List all patches (default: false)
+    --limit <n>         Show at most <n> patches per state when listing, newest first
+    --reverse           List the oldest patches first, applied before --limit
+    --format <format>   Output format: table (default), json or oneline. Patches are shown
+                        as table or json
+    --revision <n>      Show the given revision of the patch (default: the latest)
+    --json              Same as `--format json`
+    --resolve-ens       Show authors by their ENS name when showing a patch, if they have
+                        one (ethereum builds only)
+    --fetch             Fetch the patches of tracked peers from the seed before listing
+    --since <date>      Only list patches created after the given date
+    --until <date>      Only list patches created before the end of the given date
+    --state <state>     Only list patches in the given state: open, draft, merged or closed
+    --verify            Verify the signatures of the listed patch tags, and show whether they
+                        are signed by the peer they originate from
+    --reviewed-by <urn> Only list patches whose latest revision was reviewed by the given
+                        identity
+    --by-peer           Group the listed patches by the peer they originate from
+    --all-projects      List the patches of all local projects, grouped by project
+    --author <urn>      Attribute the patch to the given identity
+    --allow-empty       Allow creating a patch without any commits
+    --force             Update the patch of the current branch, if it already exists
+    --commit <oid>      Propose the given commit rather than the head of the branch
+    --from-commit       Use the head commit message as the patch title and description
+                        (default when the patch has a single commit)
+    --target <branch>   Propose the patch against the given branch (default: the project's
+                        default branch). Only single-level branch names are supported
+    --title <text>      Use the given patch title, rather than prompting for it
+    --no-editor         Read the patch description from stdin, rather than the editor
+                        (default when stdin isn't a terminal and --title is given)
+    --label <name>      Label the created patch. May be given more than once, and replaces
+                        the default labels
+    --no-default-labels Create the patch without the default labels of the working copy
+    --template <path>   Pre-fill the patch description from the given template
+                        (default: .rad/patch_template.md, if it exists)
+    --no-pager          Do not page long output, eg. patch lists and diffs
+    --diff              Show the changes of the revision when showing a patch
+    --word-diff         Highlight changed words rather than lines when viewing changes
+    --sync              Sync the patch to the seed after creating it, without asking
+    --no-sync           Do not sync the patch to the seed after creating it
+    --sync-timeout <s>  Give up syncing after <s> seconds (default: 60)
+    --seed <host>       Sync with the given seed (default: $RAD_SEED, or the seed last synced
+                        with, as configured under `rad.seed`). May be given more than once,
+                        to sync with several seeds
+    --no-confirm        Do not ask for confirmation before merging or deleting a patch
+    --no-account-check  Merge without checking the balance of your account, if merges are
+                        gated on it (ethereum builds only)
+    --ledger-hdpath <hdpath>
+                        Derivation path of your account on your Ledger device, if merges
+                        are gated on its balance (default: $ETH_HDPATH)
+    --keystore <file>   Keystore file of your account, if merges are gated on its balance
+    --cob               Delete the patch COB with the given id, rather than the patch tag
+    --accept            Accept the patch when reviewing it
+    --reject            Reject the patch when reviewing it
+    --message, -m       Comment to review the patch with
+    --color <hex>       Color to show a label in, eg. '#d73a4a'
+    --quiet             Only print essential output, without headlines, spinners or banners.
+                        Must be given before the operation, eg. `rad patch --quiet show <id>`
+    --help              Print help
+
+    With `--format json` or `--json`, only JSON is printed to stdout. Warnings,
+    eg. about patches that couldn't be loaded, are printed to stderr. Each patch
+    includes the URN and name of its project, under `project`.
+
+    Label colors are stored in the git configuration of the working copy, as
+    `rad.label.<name>.color`. Labels without a color are given one based on their name.
+
+    Listed patch ids are shortened to 24 characters, or to the length set with
+    `git config rad.patch.idLength <n>`. Full ids are shown with `--format json`.
+
+    Patch descriptions are written in the editor set with `git config rad.editor`,
+    or otherwise $VISUAL or $EDITOR. They can also be piped from other tools,
+    eg. `echo "..." | rad patch --title <text>`, in which case the patch is
+    created without asking for confirmation, and is only synced with `--sync`.
+
+    Lines starting with '#' in a patch description written from a template are
+    comments, and are removed.
+
+    `rad patch update` adds a revision to the patch of the current branch, at its
+    head, and fails if the branch has no patch yet.
+
+    The patch is created locally before it is synced, so it is kept if syncing
+    times out or is interrupted. It can then be synced with `rad sync`.
+
+    Dates given to --since and --until are either absolute, eg. 2022-05-01,
+    or relative, eg. 2.weeks.ago. Absolute dates are in UTC, and include the
+    whole day, eg. `--until 2022-05-01` includes patches created on May 1.
+
+    Patches that changed, eg. with comments or reviews, since you last
+    viewed them with `rad patch show` are marked as having new activity
+    in the list.
+
+    The patch to review is either a patch id, a patch of another project,
+    eg. <project-urn>:<id>, or a patch on a seed, eg. rad://<seed>/<project-id>/<id>.
+    Patches that aren't available locally are fetched along with their commits.
+
+    Deleting a patch tag also removes it from the seed. Deleting a patch COB
+    only removes it from your storage; peers who already fetched it may keep
+    their copy.
+
+Configuration
+
+    rad.patch.prefix             Tag prefix used for patches (default: patches/)
+    rad.patch.defaultLabels      Labels given to created patches, separated by commas,
+                                 eg. "needs-review, good first issue" (default: none)
+    rad.patch.mergeMinBalance    Minimum balance, in ether, of your account to merge
+                                 patches (ethereum builds only, default: none). Only
+                                 read from the working copy's configuration. Your
+                                 account is the one of your wallet, given with
+                                 `--ledger-hdpath` or `--keystore`, and its balance
+                                 is checked with $ETH_RPC_URL
 "#,
 };
 
+/// Sub-commands, as offered by shell completion.
+pub const OPERATIONS: &[&str] = &[
+    "update",
+    "show",
+    "status",
+    "log",
+    "reactions",
+    "sync",
+    "merge",
+    "review",
+    "delete",
+    "label",
+    "labels",
+];
+/// Maximum width of patch titles when listing patches.
+pub const TITLE_WIDTH: usize = 64;
+/// Number of attempts made at pushing a patch to the remote.
+pub const PUSH_ATTEMPTS: u32 = 4;
+/// Delay before retrying a failed push. Doubles with every attempt.
+pub const PUSH_BACKOFF: Duration = Duration::from_secs(1);
+/// Time after which syncing a patch with the seed is given up.
+pub const SYNC_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OperationName {
+    Create,
+    List,
+    Show,
+    Status,
+    Log,
+    Reactions,
+    Sync,
+    Merge,
+    Review,
+    Delete,
+    Label,
+    Labels,
+    Complete,
+}
+
+#[derive(Debug)]
+pub enum Operation {
+    /// Review a patch, which is fetched first if it isn't available locally. Since the
+    /// patch may be of any project, this doesn't need a working copy.
+    Review { patch: patch::PatchRef },
+    /// Operate on the patches of the project of the working copy.
+    Project(ProjectOperation),
+}
+
+impl Default for Operation {
+    fn default() -> Self {
+        Self::Project(ProjectOperation::default())
+    }
+}
+
+/// Operation on the patches of the project of the working copy.
+#[derive(Debug)]
+pub enum ProjectOperation {
+    Create,
+    List,
+    Show {
+        patch_id: PatchId,
+    },
+    /// Show the patch of the current branch, if any.
+    Status,
+    /// Show the history of changes made to a patch.
+    Log {
+        patch_id: PatchId,
+    },
+    /// Show the reactions to a patch's comments.
+    Reactions {
+        patch_id: PatchId,
+    },
+    /// Sync an existing patch with seeds, eg. after updating it.
+    Sync {
+        patch_id: PatchId,
+    },
+    Merge {
+        patch_id: PatchId,
+    },
+    /// Delete a patch. The id is that of the patch COB if `cob` is set, or else of the
+    /// patch tag.
+    Delete {
+        id: String,
+        cob: bool,
+    },
+    /// Set the color of a label, or show it if no color is given.
+    Label {
+        label: Label,
+        color: Option<Color>,
+    },
+    Labels,
+    /// Print completion candidates for the given sub-command, or the sub-commands
+    /// themselves if none is given. Used by shell completion scripts.
+    Complete {
+        operation: Option<String>,
+    },
+}
+
+impl ProjectOperation {
+    /// The patch that the operation acts on, if it acts on a patch COB of the current project.
+    fn patch_id(&self) -> Option<&PatchId> {
+        match self {
+            Self::Show { patch_id }
+            | Self::Log { patch_id }
+            | Self::Reactions { patch_id }
+            | Self::Sync { patch_id }
+            | Self::Merge { patch_id } => Some(patch_id),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ProjectOperation {
+    fn default() -> Self {
+        Self::Create
+    }
+}
+
+/// Output format of patch lists, and of shown patches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListFormat {
+    /// Patches grouped by state, for humans.
+    Table,
+    /// A JSON array of patches, each with its state and project.
+    Json,
+    /// One patch per line, eg. for use with `fzf`.
+    Oneline,
+}
+
+impl Default for ListFormat {
+    fn default() -> Self {
+        Self::Table
+    }
+}
+
+impl FromStr for ListFormat {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "oneline" => Ok(Self::Oneline),
+            _ => Err(FormatError(s.to_owned())),
+        }
+    }
+}
+
+/// Error returned when parsing an unknown [`ListFormat`].
+#[derive(Debug)]
+pub struct FormatError(String);
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown format '{}', expected 'table', 'json' or 'oneline'",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for FormatError {}
+
 #[derive(Default, Debug)]
 pub struct Options {
-    pub list: bool,
+    pub op: Operation,
+    pub limit: Option<usize>,
+    /// List the oldest patches first, rather than the newest. The limit applies to the
+    /// reversed list.
+    pub reverse: bool,
+    pub format: ListFormat,
+    /// Revision of the shown patch. The latest revision is shown if `None`.
+    pub revision: Option<RevisionId>,
+    /// Show authors by their ENS name when showing a patch.
+    #[cfg(feature = "ethereum")]
+    pub resolve_ens: bool,
+    pub fetch: bool,
+    pub since: Option<Timestamp>,
+    pub until: Option<Timestamp>,
+    /// Only list patches in this state, if set.
+    pub state: Option<patch::State>,
+    /// Only list patches whose latest revision was reviewed by this identity, if set.
+    pub reviewed_by: Option<Urn>,
+    /// Verify the tag signatures of listed patches, see [`patch::verify`].
+    pub verify: bool,
+    /// Group listed patches by peer.
+    pub by_peer: bool,
+    /// List the patches of all local projects, rather than of the current project.
+    pub all_projects: bool,
+    pub author: Option<Urn>,
+    pub allow_empty: bool,
+    /// Update the patch of the current branch if it exists, rather than refusing to create it.
+    pub force: bool,
+    /// Update the patch of the current branch, which must exist.
+    pub update: bool,
+    /// Commit to propose, instead of the head of the branch. Must be reachable from the head.
+    pub commit: Option<String>,
+    /// Pre-fill the patch title and description from the head commit message.
+    pub from_commit: bool,
+    /// Branch the patch is proposed against. Falls back to the project's default branch if
+    /// `None`. Validated to be a single-level branch name, see [`patch::target_branch`].
+    pub target: Option<String>,
+    /// Title of the patch. Prompts the user if `None`.
+    pub title: Option<String>,
+    /// Read the patch description from stdin, rather than from the editor.
+    pub no_editor: bool,
+    /// Labels of the created patch. Replace the default labels if not empty.
+    pub labels: Vec<Label>,
+    /// Create the patch without the default labels, see [`patch::default_labels`].
+    pub no_default_labels: bool,
+    /// Patch description template, instead of the working copy's template.
+    pub template: Option<PathBuf>,
+    pub pager: bool,
+    /// Highlight changed words rather than lines when viewing a diff.
+    pub word_diff: bool,
+    /// Show the changes of the revision when showing a patch.
+    pub diff: bool,
+    /// Whether to sync after creating a patch. Prompts the user if `None`.
+    pub sync: Option<bool>,
+    /// Time after which syncing is given up. Falls back to [`SYNC_TIMEOUT`] if `None`.
+    pub sync_timeout: Option<Duration>,
+    /// Seeds to sync with. Falls back to the environment and configuration if empty.
+    pub seeds: Vec<seed::Address>,
+    /// Whether to ask for confirmation before merging a patch.
+    pub confirm: bool,
+    /// Skip checking the merger's account before merging, see [`gate::check`].
+    #[cfg(feature = "ethereum")]
+    pub skip_account_check: bool,
+    /// Wallet of the merger, whose account is checked before merging.
+    #[cfg(feature = "ethereum")]
+    pub signer: ethereum::SignerOptions,
+    /// Verdict of a review. Prompts the user if `None`.
+    pub verdict: Option<Verdict>,
+    /// Comment of a review. Prompts the user if `None`.
+    pub message: Option<String>,
     pub verbose: bool,
 }
 
+impl Options {
+    /// Whether the patch description is read from stdin, eg. by a script. Scripts can't
+    /// answer prompts either, so none are shown when creating the patch.
+    fn scripted(&self) -> bool {
+        self.no_editor || (self.title.is_some() && !atty::is(atty::Stream::Stdin))
+    }
+}
+
 impl Args for Options {
     fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
         use lexopt::prelude::*;
 
-        let mut parser = lexopt::Parser::from_args(args);
-        let mut list = false;
+        let parser = lexopt::Parser::from_args(args);
+        #[cfg(feature = "ethereum")]
+        let (signer, parser) = ethereum::SignerOptions::from(parser)?;
+        let mut parser = parser;
+        let mut op: Option<OperationName> = None;
+        let mut patch_id: Option<PatchId> = None;
+        let mut completing: Option<String> = None;
+        let mut limit = None;
+        let mut reverse = false;
+        let mut format = ListFormat::default();
+        let mut revision = None;
+        #[cfg(feature = "ethereum")]
+        let mut resolve_ens = false;
+        let mut fetch = false;
+        let mut since = None;
+        let mut until = None;
+        let mut state = None;
+        let mut reviewed_by: Option<Urn> = None;
+        let mut verify = false;
+        let mut by_peer = false;
+        let mut all_projects = false;
+        let mut author: Option<Urn> = None;
+        let mut allow_empty = false;
+        let mut force = false;
+        let mut update = false;
+        let mut commit = None;
+        let mut from_commit = false;
+        let mut target = None;
+        let mut title = None;
+        let mut no_editor = false;
+        let mut labels = Vec::new();
+        let mut no_default_labels = false;
+        let mut template = None;
+        let mut pager = true;
+        let mut word_diff = false;
+        let mut diff = false;
+        let mut sync = None;
+        let mut sync_timeout = None;
+        let mut seeds = Vec::new();
+        let mut confirm = true;
+        #[cfg(feature = "ethereum")]
+        let mut skip_account_check = false;
+        let mut cob = false;
+        let mut delete_id: Option<String> = None;
+        let mut label: Option<Label> = None;
+        let mut color = None;
+        let mut patch_ref: Option<patch::PatchRef> = None;
+        let mut verdict = None;
+        let mut message = None;
         let mut verbose = false;
 
-        if let Some(arg) = parser.next()? {
+        while let Some(arg) = parser.next()? {
             match arg {
-                Long("list") | Short('l') => {
-                    list = true;
+                Long("list") | Short('l') if op.is_none() => {
+                    op = Some(OperationName::List);
+                }
+                Long("limit") => {
+                    limit = Some(args::parse_value("limit", parser.value()?)?);
+                }
+                Long("reverse") => {
+                    reverse = true;
+                }
+                Long("format") => {
+                    format = args::parse_value("format", parser.value()?)?;
+                }
+                Long("revision") => {
+                    revision = Some(args::parse_value("revision", parser.value()?)?);
+                }
+                Long("json") => {
+                    format = ListFormat::Json;
+                }
+                #[cfg(feature = "ethereum")]
+                Long("resolve-ens") => {
+                    resolve_ens = true;
+                }
+                Long("fetch") => {
+                    fetch = true;
+                }
+                Long("since") => {
+                    since = Some(args::parse_value("since", parser.value()?)?);
+                }
+                Long("until") => {
+                    let value: String = args::parse_value("until", parser.value()?)?;
+                    let end = Timestamp::parse_end(&value, Timestamp::now())
+                        .map_err(|e| anyhow!("invalid value specified for '--until' ({})", e))?;
+                    until = Some(end);
+                }
+                Long("state") => {
+                    state = Some(args::parse_value("state", parser.value()?)?);
+                }
+                Long("verify") => {
+                    verify = true;
+                }
+                Long("by-peer") => {
+                    by_peer = true;
+                }
+                Long("all-projects") => {
+                    all_projects = true;
+                }
+                Long("reviewed-by") => {
+                    let value = parser.value()?;
+                    let value = value.to_string_lossy();
+                    let urn = Urn::from_str(&value).with_context(|| {
+                        format!("invalid URN '{}' specified for '--reviewed-by'", value)
+                    })?;
+
+                    reviewed_by = Some(urn);
+                }
+                Long("author") => {
+                    let value = parser.value()?;
+                    let value = value.to_string_lossy();
+                    let urn = Urn::from_str(&value).with_context(|| {
+                        format!("invalid URN '{}' specified for '--author'", value)
+                    })?;
+
+                    author = Some(urn);
+                }
+                Long("allow-empty") => {
+                    allow_empty = true;
+                }
+                Long("force") | Short('f') => {
+                    force = true;
+                }
+                Long("commit") => {
+                    commit = Some(parser.value()?.to_string_lossy().into_owned());
+                }
+                Long("from-commit") => {
+                    from_commit = true;
+                }
+                Long("target") => {
+                    let value = parser.value()?;
+                    let value = value.to_string_lossy();
+                    match patch::target_branch(&value) {
+                        Ok(_) => target = Some(value.into_owned()),
+                        Err(err @ patch::Error::MultiLevelTarget(_)) => {
+                            return Err(Error::WithHint {
+                                err: err.into(),
+                                hint: "Patch targets are stored as single-level branch names, eg. `master` rather than `feature/x`.",
+                            }
+                            .into());
+                        }
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                Long("title") => {
+                    title = Some(parser.value()?.to_string_lossy().into_owned());
+                }
+                Long("no-editor") => {
+                    no_editor = true;
+                }
+                Long("label") => {
+                    let value = parser.value()?;
+
+                    labels.push(Label::from_str(&value.to_string_lossy())?);
+                }
+                Long("no-default-labels") => {
+                    no_default_labels = true;
+                }
+                Long("template") => {
+                    template = Some(PathBuf::from(parser.value()?));
+                }
+                Long("no-pager") => {
+                    pager = false;
+                }
+                Long("word-diff") => {
+                    word_diff = true;
+                }
+                Long("diff") => {
+                    diff = true;
+                }
+                Long("sync") => {
+                    sync = Some(true);
+                }
+                Long("no-sync") => {
+                    sync = Some(false);
+                }
+                Long("sync-timeout") => {
+                    let secs = args::parse_value("sync-timeout", parser.value()?)?;
+
+                    sync_timeout = Some(Duration::from_secs(secs));
+                }
+                Long("seed") => {
+                    let value = parser.value()?;
+                    let addr = seed::Address::from_str(&value.to_string_lossy())
+                        .context("invalid host specified for `--seed`")?;
+
+                    seeds.push(addr);
+                }
+                Long("no-confirm") => {
+                    confirm = false;
+                }
+                #[cfg(feature = "ethereum")]
+                Long("no-account-check") => {
+                    skip_account_check = true;
+                }
+                Long("cob") => {
+                    cob = true;
+                }
+                Long("accept") => {
+                    verdict = Some(Verdict::Accept);
+                }
+                Long("reject") => {
+                    verdict = Some(Verdict::Reject);
+                }
+                Long("message") | Short('m') => {
+                    message = Some(parser.value()?.to_string_lossy().into_owned());
+                }
+                Long("color") => {
+                    color = Some(args::parse_value("color", parser.value()?)?);
                 }
                 Long("verbose") | Short('v') => {
                     verbose = true;
@@ -50,132 +647,914 @@ impl Args for Options {
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
+                // Nb. This option is hidden from the help, as it is meant for completion scripts.
+                Long("complete") if op.is_none() => {
+                    op = Some(OperationName::Complete);
+                }
+                Value(val) if op == Some(OperationName::Complete) && completing.is_none() => {
+                    completing = Some(val.to_string_lossy().into_owned());
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "update" => {
+                        op = Some(OperationName::Create);
+                        update = true;
+                    }
+                    "show" => op = Some(OperationName::Show),
+                    "status" => op = Some(OperationName::Status),
+                    "log" => op = Some(OperationName::Log),
+                    "reactions" => op = Some(OperationName::Reactions),
+                    "sync" => op = Some(OperationName::Sync),
+                    "merge" => op = Some(OperationName::Merge),
+                    "review" => op = Some(OperationName::Review),
+                    "delete" => op = Some(OperationName::Delete),
+                    "label" => op = Some(OperationName::Label),
+                    "labels" => op = Some(OperationName::Labels),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val)
+                    if matches!(
+                        op,
+                        Some(
+                            OperationName::Show
+                                | OperationName::Log
+                                | OperationName::Reactions
+                                | OperationName::Sync
+                                | OperationName::Merge
+                        )
+                    ) && patch_id.is_none() =>
+                {
+                    let val = val
+                        .to_str()
+                        .ok_or_else(|| anyhow!("patch id specified is not UTF-8"))?;
+
+                    patch_id = Some(
+                        PatchId::from_str(val)
+                            .map_err(|_| anyhow!("invalid patch id '{}'", val))?,
+                    );
+                }
+                Value(val) if op == Some(OperationName::Review) && patch_ref.is_none() => {
+                    patch_ref = Some(patch::PatchRef::from_str(&val.to_string_lossy())?);
+                }
+                Value(val) if op == Some(OperationName::Delete) && delete_id.is_none() => {
+                    delete_id = Some(val.to_string_lossy().into_owned());
+                }
+                Value(val) if op == Some(OperationName::Label) && label.is_none() => {
+                    label = Some(Label::from_str(&val.to_string_lossy())?);
+                }
                 _ => return Err(anyhow::anyhow!(arg.unexpected())),
             }
         }
 
-        Ok((Options { list, verbose }, vec![]))
+        // Nb. Patches can only be listed across projects, so that is implied.
+        let default = if all_projects {
+            OperationName::List
+        } else {
+            OperationName::Create
+        };
+        let op = match op.unwrap_or(default) {
+            OperationName::Review => Operation::Review {
+                patch: patch_ref.ok_or_else(|| anyhow!("a patch must be provided"))?,
+            },
+            OperationName::Create => Operation::Project(ProjectOperation::Create),
+            OperationName::List => Operation::Project(ProjectOperation::List),
+            OperationName::Show => Operation::Project(ProjectOperation::Show {
+                patch_id: patch_id.ok_or_else(|| anyhow!("a patch id must be provided"))?,
+            }),
+            OperationName::Status => Operation::Project(ProjectOperation::Status),
+            OperationName::Log => Operation::Project(ProjectOperation::Log {
+                patch_id: patch_id.ok_or_else(|| anyhow!("a patch id must be provided"))?,
+            }),
+            OperationName::Reactions => Operation::Project(ProjectOperation::Reactions {
+                patch_id: patch_id.ok_or_else(|| anyhow!("a patch id must be provided"))?,
+            }),
+            OperationName::Sync => Operation::Project(ProjectOperation::Sync {
+                patch_id: patch_id.ok_or_else(|| anyhow!("a patch id must be provided"))?,
+            }),
+            OperationName::Merge => Operation::Project(ProjectOperation::Merge {
+                patch_id: patch_id.ok_or_else(|| anyhow!("a patch id must be provided"))?,
+            }),
+            OperationName::Delete => Operation::Project(ProjectOperation::Delete {
+                id: delete_id.ok_or_else(|| anyhow!("a patch id must be provided"))?,
+                cob,
+            }),
+            OperationName::Label => Operation::Project(ProjectOperation::Label {
+                label: label.ok_or_else(|| anyhow!("a label must be provided"))?,
+                color,
+            }),
+            OperationName::Labels => Operation::Project(ProjectOperation::Labels),
+            OperationName::Complete => Operation::Project(ProjectOperation::Complete {
+                operation: completing,
+            }),
+        };
+        if author.is_some() && !matches!(op, Operation::Project(ProjectOperation::Create)) {
+            anyhow::bail!("`--author` can only be used when creating a patch");
+        }
+        if author.is_some() && update {
+            anyhow::bail!("`--author` can't be used when updating a patch, its author is kept");
+        }
+
+        Ok((
+            Options {
+                op,
+                limit,
+                reverse,
+                format,
+                revision,
+                #[cfg(feature = "ethereum")]
+                resolve_ens,
+                fetch,
+                since,
+                until,
+                state,
+                reviewed_by,
+                verify,
+                by_peer,
+                all_projects,
+                author,
+                allow_empty,
+                force,
+                update,
+                commit,
+                from_commit,
+                target,
+                title,
+                no_editor,
+                labels,
+                no_default_labels,
+                template,
+                pager,
+                word_diff,
+                diff,
+                sync,
+                sync_timeout,
+                seeds,
+                confirm,
+                #[cfg(feature = "ethereum")]
+                skip_account_check,
+                #[cfg(feature = "ethereum")]
+                signer,
+                verdict,
+                message,
+                verbose,
+            },
+            vec![],
+        ))
     }
 }
 
 pub fn run(options: Options) -> anyhow::Result<()> {
-    let (urn, repo) = project::cwd()
-        .map_err(|_| anyhow!("this command must be run in the context of a project"))?;
-
     let profile = profile::default()?;
-    let signer = term::signer(&profile)?;
-    let storage = keys::storage(&profile, signer)?;
-    let project = project::get(&storage, &urn)?
+
+    match &options.op {
+        // Patches of other projects can be reviewed without a working copy.
+        Operation::Review { patch } => {
+            review::run(&profile, patch, options.verdict, options.message.clone())
+        }
+        Operation::Project(op) => run_project(&profile, op, &options),
+    }
+}
+
+/// Run an operation on the patches of the project of the working copy.
+fn run_project(profile: &Profile, op: &ProjectOperation, options: &Options) -> anyhow::Result<()> {
+    // Listing patches of all projects doesn't need a working copy.
+    if options.all_projects {
+        if !matches!(op, ProjectOperation::List) {
+            anyhow::bail!("`--all-projects` can only be used when listing patches");
+        }
+        if options.format != ListFormat::Table {
+            anyhow::bail!("`--all-projects` only supports the table format");
+        }
+        let unsupported = [
+            ("--since", options.since.is_some()),
+            ("--until", options.until.is_some()),
+            ("--by-peer", options.by_peer),
+            ("--verify", options.verify),
+            ("--no-pager", !options.pager),
+        ];
+        if let Some((flag, _)) = unsupported.iter().find(|(_, given)| *given) {
+            anyhow::bail!("`{}` can't be used with `--all-projects`", flag);
+        }
+        return all::run(profile, options);
+    }
+    let (urn, repo) = project::cwd_checked().map_err(|err| {
+        let hint = err.hint();
+        let err = anyhow!(
+            "this command must be run in the context of a project: {}",
+            err
+        );
+
+        match hint {
+            Some(hint) => Error::WithHint { err, hint }.into(),
+            None => err,
+        }
+    })?;
+
+    let read_only = profile::read_only(profile)?;
+    let project = project::get(&read_only, &urn)?
         .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?;
+    // Fail fast on unknown patches, eg. before prompting for the signing key.
+    if let Some(patch_id) = op.patch_id() {
+        if !common::cobs::patch::exists(&read_only, &urn, patch_id)? {
+            return Err(Error::WithHint {
+                err: anyhow!("no such patch {} in {}", patch_id, project.name),
+                hint: "List the patches of the project with `rad patch --list`.",
+            }
+            .into());
+        }
+    }
+    // Only operations that write to storage, or need verified patch histories, require
+    // the signing key. With the key in ssh-agent, getting it doesn't prompt for it.
+    let signing = || -> anyhow::Result<Storage> {
+        let signer = term::signer(profile)?;
+        Ok(keys::storage(profile, signer)?)
+    };
 
-    if options.list {
-        list(&storage, &project, &repo)?;
-    } else {
-        create(&project, &repo, options.verbose)?;
+    match op {
+        ProjectOperation::List => {
+            if options.fetch {
+                let storage = signing()?;
+                let peers = project::tracked(&project, &storage)?;
+                // Machine-readable listings are kept free of progress output.
+                let quiet = options.format != ListFormat::Table;
+
+                fetch::run(&storage, &project, peers.keys(), quiet)?;
+            }
+            let patches = PatchesReadOnly::new(&read_only)?;
+            let seen = Seen::open(profile.paths())?;
+
+            list(&read_only, &patches, &seen, &project, &repo, options)?;
+        }
+        ProjectOperation::Show { patch_id } => {
+            let json = match options.format {
+                ListFormat::Table => false,
+                ListFormat::Json => true,
+                ListFormat::Oneline => {
+                    anyhow::bail!("patches can only be shown with the table or json format")
+                }
+            };
+            let patches = PatchesReadOnly::new(&read_only)?;
+            #[cfg(feature = "ethereum")]
+            let resolve_ens = options.resolve_ens;
+            #[cfg(not(feature = "ethereum"))]
+            let resolve_ens = false;
+
+            show::run(
+                &read_only,
+                &patches,
+                &project,
+                Some(&repo),
+                patch_id,
+                options.revision,
+                json,
+                resolve_ens,
+                options.diff.then(|| show::Diff {
+                    words: options.word_diff,
+                    pager: options.pager,
+                }),
+            )?;
+            // Patches printed as JSON are meant for other programs, not read by the user.
+            if !json {
+                if let Some(tips) = patches.snapshot(&project.urn)?.tips(patch_id) {
+                    let mut seen = Seen::open(profile.paths())?;
+                    seen.mark(patch_id, tips.clone());
+                    seen.save()?;
+                }
+            }
+        }
+        ProjectOperation::Status => {
+            let patches = PatchesReadOnly::new(&read_only)?;
+
+            status::run(&read_only, &patches, &project, &repo)?;
+        }
+        ProjectOperation::Log { patch_id } => {
+            let storage = signing()?;
+            // The authors of changes are only known once they are verified, which requires
+            // signing storage, see `PatchesReadOnly`.
+            let whoami = person::local(&storage)?;
+            let patches = Patches::new(whoami, profile.paths(), &storage)?;
+
+            history::run(&storage, &patches, &project, patch_id)?;
+        }
+        ProjectOperation::Reactions { patch_id } => {
+            let patches = PatchesReadOnly::new(&read_only)?;
+
+            reactions::run(&read_only, &patches, &project, patch_id)?;
+        }
+        ProjectOperation::Sync { patch_id } => {
+            let patches = PatchesReadOnly::new(&read_only)?;
+            let timeout = options.sync_timeout.unwrap_or(SYNC_TIMEOUT);
+
+            sync::run(
+                &read_only,
+                &patches,
+                &project,
+                &repo,
+                patch_id,
+                &options.seeds,
+                timeout,
+            )?;
+        }
+        ProjectOperation::Merge { patch_id } => {
+            let storage = signing()?;
+            let whoami = person::local(&storage)?;
+            let patches = Patches::new(whoami, profile.paths(), &storage)?;
+
+            // Projects with on-chain governance may require the merger's account to hold a
+            // minimum balance.
+            #[cfg(feature = "ethereum")]
+            if !options.skip_account_check {
+                gate::check(&repo, options.signer.clone())?;
+            }
+            merge::run(
+                &storage,
+                &patches,
+                &project,
+                &repo,
+                patch_id,
+                options.confirm,
+                options.verbose,
+            )?;
+        }
+        ProjectOperation::Delete { id, cob } => {
+            let storage = signing()?;
+            let whoami = person::local(&storage)?;
+            let patches = Patches::new(whoami, profile.paths(), &storage)?;
+
+            delete(&patches, &project, &repo, id, *cob, options.confirm)?;
+        }
+        ProjectOperation::Label { label, color } => {
+            if let Some(color) = color {
+                patch::set_label_color(&repo, label, *color)?;
+            }
+            let color = patch::label_color(&repo, label);
+
+            term::info!(
+                "{} {}",
+                term::format::label(label, color),
+                term::format::dim(color)
+            );
+        }
+        ProjectOperation::Labels => {
+            let patches = PatchesReadOnly::new(&read_only)?;
+
+            labels(&patches, &project, &repo)?;
+        }
+        ProjectOperation::Complete { operation } => {
+            let patches = PatchesReadOnly::new(&read_only)?;
+
+            complete(&patches, &project, operation.as_deref())?;
+        }
+        ProjectOperation::Create => {
+            let storage = signing()?;
+            let whoami = person::local(&storage)?;
+            let patches = Patches::new(whoami, profile.paths(), &storage)?;
+
+            create(
+                &mut frontend::Terminal::new(Some(&repo)),
+                &patches,
+                &project,
+                &repo,
+                options,
+            )?;
+        }
     }
 
     Ok(())
 }
 
 fn list(
-    storage: &Storage,
+    storage: &ReadOnly,
+    patches: &PatchesReadOnly,
+    seen: &Seen,
     project: &project::Metadata,
     repo: &git::Repository,
+    options: &Options,
 ) -> anyhow::Result<()> {
-    term::headline(&format!(
-        "🌱 Listing patches for {}.",
-        term::format::highlight(&project.name)
-    ));
+    if options.format == ListFormat::Table {
+        term::headline(&format!(
+            "🌱 Listing patches for {}.",
+            term::format::highlight(&project.name)
+        ));
+    }
+
+    let loaded = patches.all(&project.urn)?;
+    for id in &loaded.failed {
+        term::warning(&format!("Patch {} could not be loaded, skipping it.", id));
+    }
+    let cobs = loaded.patches;
+    let mut sections = Vec::with_capacity(patch::State::ALL.len());
+    for state in patch::State::ALL {
+        if options.state.map_or(false, |s| s != state) {
+            continue;
+        }
+        let patches = list_by_state(storage, repo, &cobs, project, state, options)?;
+        sections.push((state, patches));
+    }
+
+    match options.format {
+        ListFormat::Table => {
+            let snapshot = patches.snapshot(&project.urn)?;
+            let unread = cobs
+                .iter()
+                .map(|(id, _)| id)
+                .filter(|id| seen.is_unread(id, snapshot.tips(id)))
+                .collect::<Vec<_>>();
+
+            list_table(storage, repo, project, &sections, &cobs, &unread, options)?
+        }
+        ListFormat::Json => list_json(storage, project, &sections, options)?,
+        ListFormat::Oneline => list_oneline(&sections, patch::short_id_length(repo), options),
+    }
+
+    Ok(())
+}
 
+/// Render patches grouped by state, or by peer and then state if `--by-peer` was given.
+/// Patches that changed since they were last shown are marked as unread.
+fn list_table(
+    storage: &ReadOnly,
+    repo: &git::Repository,
+    project: &project::Metadata,
+    sections: &[(patch::State, Vec<patch::Metadata>)],
+    cobs: &[(PatchId, Patch)],
+    unread: &[&PatchId],
+    options: &Options,
+) -> anyhow::Result<()> {
     let mut table = term::Table::default();
+    table.align(1, term::Alignment::Right);
     let blank = ["".to_owned(), "".to_owned()];
 
-    table.push([
-        format!("[{}]", term::format::secondary("Open")),
-        String::new(),
-    ]);
-    table.push(blank.clone());
-    list_by_state(storage, repo, project, &mut table, patch::State::Open)?;
-    table.push(blank.clone());
-    table.push(blank.clone());
-
-    table.push([
-        format!("[{}]", term::format::positive("Merged")),
-        String::new(),
-    ]);
-    table.push(blank);
-    list_by_state(storage, repo, project, &mut table, patch::State::Merged)?;
-    table.render();
+    if options.by_peer {
+        // Peers are listed in the order their first patch appears in.
+        let mut peers: Vec<&project::PeerInfo> = Vec::new();
+        for patch in sections.iter().flat_map(|(_, patches)| patches) {
+            if !peers.iter().any(|peer| peer.id == patch.peer.id) {
+                peers.push(&patch.peer);
+            }
+        }
+        if peers.is_empty() {
+            table.push(["No patches found.".to_owned(), String::new()]);
+        }
+
+        for (i, peer) in peers.into_iter().enumerate() {
+            let mut heading = vec![term::format::bold(peer.name())];
+            // Without a known name, the peer id is already shown as its name.
+            if peer.person.is_some() {
+                heading.push(term::format::dim(common::fmt::peer(&peer.id)));
+            }
+            if peer.id == *storage.peer_id() {
+                heading.push(term::format::badge_secondary("you"));
+            }
+            if peer.delegate {
+                heading.push(term::format::badge_secondary("delegate"));
+            }
+            if i > 0 {
+                table.push(blank.clone());
+                table.push(blank.clone());
+            }
+            table.push([heading.join(" "), String::new()]);
+            table.push(blank.clone());
+
+            let theirs = sections
+                .iter()
+                .map(|(state, patches)| {
+                    let patches = patches
+                        .iter()
+                        .filter(|patch| patch.peer.id == peer.id)
+                        .collect::<Vec<_>>();
+                    (*state, patches)
+                })
+                .filter(|(_, patches)| !patches.is_empty())
+                .collect::<Vec<_>>();
 
+            push_sections(
+                storage, repo, project, &mut table, &theirs, cobs, unread, options,
+            )?;
+        }
+    } else {
+        let sections = sections
+            .iter()
+            .map(|(state, patches)| (*state, patches.iter().collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        push_sections(
+            storage, repo, project, &mut table, &sections, cobs, unread, options,
+        )?;
+    }
+
+    if options.pager {
+        table.render_paged()?;
+    } else {
+        table.render();
+    }
     term::blank();
 
     Ok(())
 }
 
+/// Add a block of rows per state to `table`, with the patches in that state. Merged
+/// patches are shown with who merged them.
+#[allow(clippy::too_many_arguments)]
+fn push_sections(
+    storage: &ReadOnly,
+    repo: &git::Repository,
+    project: &project::Metadata,
+    table: &mut term::Table<2>,
+    sections: &[(patch::State, Vec<&patch::Metadata>)],
+    cobs: &[(PatchId, Patch)],
+    unread: &[&PatchId],
+    options: &Options,
+) -> anyhow::Result<()> {
+    let blank = ["".to_owned(), "".to_owned()];
+    let id_length = patch::short_id_length(repo);
+
+    for (i, (state, patches)) in sections.iter().enumerate() {
+        let name = match state {
+            patch::State::Open => term::format::secondary("Open"),
+            patch::State::Draft => term::format::dim("Draft"),
+            patch::State::Merged => term::format::positive("Merged"),
+            patch::State::Closed => term::format::negative("Closed"),
+        };
+        if i > 0 {
+            table.push(blank.clone());
+            table.push(blank.clone());
+        }
+        table.push([format!("[{}]", name), String::new()]);
+        table.push(blank.clone());
+
+        if patches.is_empty() {
+            table.push(["No patches found.".to_owned(), String::new()]);
+            continue;
+        }
+        let total = patches.len();
+        let shown = options.limit.unwrap_or(total).min(total);
+
+        for patch in patches.iter().take(shown) {
+            let cob = patch::find_cob(cobs, patch);
+            let unread = cob.map_or(false, |(id, _)| unread.contains(&id));
+            let labels = cob
+                .map(|(_, cob)| show::labels(Some(repo), &cob.labels))
+                .unwrap_or_default();
+
+            // Verifying runs `ssh-keygen` for every patch, so it's only done when asked.
+            let verified = options.verify.then(|| patch::verify(storage, patch));
+
+            print(storage, patch, unread, verified, &labels, id_length, table)?;
+            if *state == patch::State::Merged {
+                let merged = merged_by(storage, project, cob.map(|(_, cob)| cob));
+                table.push([term::format::italic(merged), String::new()]);
+            }
+        }
+        if total > shown {
+            table.push([
+                term::format::dim(format!("... and {} more", total - shown)),
+                String::new(),
+            ]);
+        }
+    }
+    Ok(())
+}
+
+/// Describe who merged a patch, and into which commit, as recorded in its COB. Patches
+/// without recorded merges, eg. those without a COB, were merged in the local history.
+fn merged_by(storage: &ReadOnly, project: &project::Metadata, cob: Option<&Patch>) -> String {
+    let merges = cob
+        .map(|cob| {
+            cob.revisions
+                .iter()
+                .flat_map(|revision| revision.merges.iter())
+                .map(|merge| {
+                    let name = if merge.peer == *storage.peer_id() {
+                        String::from("you")
+                    } else {
+                        project::PeerInfo::get(&merge.peer, project, storage).name()
+                    };
+                    format!("{} ({})", name, git::short(merge.commit))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    if merges.is_empty() {
+        String::from("    Merged locally")
+    } else {
+        format!("    Merged by {}", merges.join(", "))
+    }
+}
+
+/// Print patches as a JSON array, with the state of each patch.
+fn list_json(
+    storage: &ReadOnly,
+    project: &project::Metadata,
+    sections: &[(patch::State, Vec<patch::Metadata>)],
+    options: &Options,
+) -> anyhow::Result<()> {
+    let mut output = Vec::new();
+    for (state, patches) in sections {
+        for patch in patches.iter().take(options.limit.unwrap_or(usize::MAX)) {
+            let mut value = serde_json::to_value(patch)?;
+            value["state"] = serde_json::Value::from(state.to_string());
+            value["project"] = project_json(project);
+            if options.verify {
+                value["verified"] = serde_json::Value::from(patch::verify(storage, patch));
+            }
+
+            output.push(value);
+        }
+    }
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    Ok(())
+}
+
+/// The project of a patch, as included in JSON output so that it is self-contained.
+fn project_json(project: &project::Metadata) -> serde_json::Value {
+    serde_json::json!({
+        "urn": project.urn.to_string(),
+        "name": project.name,
+    })
+}
+
+/// Print one patch per line: id, state, title and author.
+fn list_oneline(
+    sections: &[(patch::State, Vec<patch::Metadata>)],
+    id_length: usize,
+    options: &Options,
+) {
+    for (state, patches) in sections {
+        let glyph = match state {
+            patch::State::Open => term::format::positive("●"),
+            patch::State::Draft => term::format::dim("◌"),
+            patch::State::Merged => term::format::secondary("✓"),
+            patch::State::Closed => term::format::negative("✗"),
+        };
+        for patch in patches.iter().take(options.limit.unwrap_or(usize::MAX)) {
+            let title = patch.title();
+
+            println!(
+                "{} {} {} {}",
+                term::format::tertiary(patch.short_id(id_length)),
+                glyph,
+                title,
+                term::format::dim(patch.peer.name())
+            );
+        }
+    }
+}
+
+/// Create a patch for the current branch, push it to the monorepo and sync it with the seed.
 fn create(
+    frontend: &mut impl Frontend,
+    patches: &Patches,
     project: &project::Metadata,
     repo: &git::Repository,
-    verbose: bool,
+    options: &Options,
 ) -> anyhow::Result<()> {
+    propose(frontend, patches, project, repo, options)?;
+    let current_branch = patch::current_branch(repo)?;
+
+    let spinner = term::spinner("Pushing patch...");
+    push_patch(
+        repo,
+        spinner,
+        options.force || options.update,
+        options.verbose,
+    )?;
+
+    if options
+        .sync
+        .unwrap_or_else(|| !options.scripted() && frontend.confirm("Sync to seed?"))
+    {
+        let timeout = options.sync_timeout.unwrap_or(SYNC_TIMEOUT);
+
+        // Nb. This syncs the branch as pushed to storage, ie. up to the proposed commit.
+        if let Err(err) = sync::branch(repo, &current_branch, &options.seeds, timeout) {
+            return Err(Error::WithHint {
+                err,
+                hint: "The patch was created locally. Sync it later with `rad sync`.",
+            }
+            .into());
+        }
+    }
+
+    term::blank();
+    term::info!(
+        "🌱 {} patch {}",
+        if options.update { "Updated" } else { "Created" },
+        term::format::highlight(&current_branch)
+    );
+
+    Ok(())
+}
+
+/// Propose the current branch as a patch: ask the user for its title and description, and
+/// submit it locally, ie. create its COB and tag. Returns the id of the patch COB.
+fn propose(
+    frontend: &mut impl Frontend,
+    patches: &Patches,
+    project: &project::Metadata,
+    repo: &git::Repository,
+    options: &Options,
+) -> anyhow::Result<PatchId> {
     let head = repo.head()?;
-    let current_branch = head.shorthand().unwrap_or("HEAD (no branch)");
+    let current_branch = match patch::current_branch(repo) {
+        Ok(branch) => branch,
+        Err(err @ patch::Error::DetachedHead) => {
+            return Err(Error::WithHint {
+                err: err.into(),
+                hint: "Patches are created from branches: create or checkout a branch first, eg. with `git checkout -b <branch>`.",
+            }
+            .into())
+        }
+        Err(err) => return Err(err.into()),
+    };
+    // Fail early if the branch can't be tagged, rather than after prompting for a title.
+    patch::tag_name(repo, &current_branch)?;
+    let labels = create_labels(repo, options)?;
 
     term::headline(&format!(
         "🌱 Creating patch for {}.",
         term::format::highlight(&project.name)
     ));
 
-    let master = repo
-        .resolve_reference_from_short_name(&format!("rad/{}", &project.default_branch))?
-        .target();
-    let master_oid = master
-        .map(|h| format!("{:.7}", h.to_string()))
-        .unwrap_or_else(String::new);
+    if options.update {
+        if patch::patch_tag(repo, &current_branch)?.is_none() {
+            return Err(submit_error(patch::Error::NoPatch(current_branch)));
+        }
+    } else if !options.force {
+        patch::ensure_no_patch(repo, &current_branch).map_err(submit_error)?;
+    }
 
-    let head_ref = head.target();
-    let head_oid = head_ref
-        .map(|h| format!("{:.7}", h.to_string()))
-        .unwrap_or_else(String::new);
+    let target = options
+        .target
+        .as_deref()
+        .unwrap_or_else(|| project.default_branch.as_str());
+    let master = match patch::branch_head(repo, target)? {
+        Some(master) => master,
+        None if options.target.is_some() => {
+            return Err(Error::WithHint {
+                err: anyhow!("target branch 'rad/{}' was not found", target),
+                hint: "Push the target branch first, with `rad push`.",
+            }
+            .into())
+        }
+        None => {
+            return Err(Error::WithHint {
+                err: anyhow!("default branch 'rad/{}' was not found", target),
+                hint: "Push an initial commit to the default branch first, with `rad push`.",
+            }
+            .into())
+        }
+    };
+    let master_oid = git::short(master);
+
+    let head_ref = head
+        .target()
+        .ok_or_else(|| anyhow!("current branch '{}' has no commits", current_branch))?;
+    let head_ref = match &options.commit {
+        Some(rev) => match patch::find_commit(repo, rev, head_ref) {
+            Ok(commit) => commit,
+            Err(err @ patch::Error::Unreachable(_)) => {
+                return Err(Error::WithHint {
+                    err: err.into(),
+                    hint: "Only commits of the current branch can be proposed.",
+                }
+                .into())
+            }
+            Err(err) => return Err(err.into()),
+        },
+        None => head_ref,
+    };
+    let head_oid = git::short(head_ref);
 
     term::info!(
         "Proposing {} ({}) <= {} ({}).",
-        term::format::highlight(&project.default_branch.clone()),
+        term::format::highlight(target),
         term::format::secondary(&master_oid),
         term::format::highlight(&current_branch),
         term::format::secondary(&head_oid),
     );
 
-    let (ahead, behind) = repo.graph_ahead_behind(
-        head_ref.unwrap_or_else(git::Oid::zero),
-        master.unwrap_or_else(git::Oid::zero),
-    )?;
+    let (ahead, behind) = repo.graph_ahead_behind(head_ref, master)?;
     term::info!(
         "This branch is {} commit(s) ahead, {} commit(s) behind {}.",
         term::format::highlight(ahead),
         term::format::highlight(behind),
-        term::format::highlight(&project.default_branch)
+        term::format::highlight(target)
     );
 
-    let merge_base_ref = repo.merge_base(
-        master.unwrap_or_else(git::Oid::zero),
-        head_ref.unwrap_or_else(git::Oid::zero),
-    );
+    if ahead == 0 && !options.allow_empty {
+        return Err(Error::WithHint {
+            err: anyhow!(
+                "branch {} has no commits ahead of {}",
+                current_branch,
+                target
+            ),
+            hint: "Commit your changes first, or use `--allow-empty` to create an empty patch.",
+        }
+        .into());
+    }
 
-    term::patch::list_commits(repo, &merge_base_ref.unwrap(), &head_ref.unwrap(), true)?;
+    let merge_base_ref = match patch::merge_base_between(repo, master, head_ref)? {
+        Some(merge_base) => Some(*merge_base),
+        None => {
+            term::warning(&format!(
+                "Branch {} has no common history with {}.",
+                current_branch, target
+            ));
+            if !options.scripted() && !frontend.confirm("Propose the entire branch as a patch?") {
+                return Err(anyhow!("Canceled."));
+            }
+            None
+        }
+    };
+
+    term::patch::list_commits(repo, merge_base_ref.as_ref(), &head_ref, true)?;
     term::blank();
 
-    if term::confirm("View changes?") {
-        git::view_diff(repo, &master.unwrap(), &head_ref.unwrap())?;
+    let stats = patch::DiffStats::between(repo, merge_base_ref, head_ref)?;
+    term::info!(
+        "{} file(s) changed, {} insertion(s), {} deletion(s).",
+        term::format::highlight(stats.files),
+        term::format::positive(format!("+{}", stats.insertions)),
+        term::format::negative(format!("-{}", stats.deletions)),
+    );
+    term::blank();
+
+    if !options.scripted() && frontend.confirm("View changes?") {
+        if options.word_diff {
+            term::patch::word_diff(repo, &master, &head_ref, options.pager)?;
+        } else {
+            git::view_diff(repo, &master, &head_ref, options.pager)?;
+        }
     }
 
-    if !term::confirm("Create patch using commit(s) above?") {
+    if !options.scripted() && !frontend.confirm("Create patch using commit(s) above?") {
         return Err(anyhow!("Canceled."));
     }
 
-    let title: String = term::text_input("Title", None)?;
-    let description = match term::Editor::new().edit("").unwrap() {
-        Some(rv) => rv,
-        None => String::new(),
+    // A single commit's message usually makes a good patch title and description, so
+    // it is offered as a starting point.
+    let (subject, body) = if options.from_commit || ahead == 1 {
+        let commit = repo.find_commit(head_ref)?;
+        let message = commit.message().unwrap_or_default().trim();
+        let (subject, body) = message.split_once("\n\n").unwrap_or((message, ""));
+
+        (Some(subject.replace('\n', " ")), body.trim().to_owned())
+    } else {
+        (None, String::new())
+    };
+    let template = patch::description_template(repo, options.template.as_deref())
+        .context("couldn't read the patch description template")?;
+    let body = match &template {
+        Some(template) if body.is_empty() => template.clone(),
+        Some(template) => format!("{}\n\n{}", body, template),
+        None => body,
+    };
+
+    let title: String = match &options.title {
+        Some(title) => title.clone(),
+        None => frontend.text_input("Title", subject)?,
+    };
+    // Descriptions are piped in by scripts, which can't interact with an editor.
+    let piped = !atty::is(atty::Stream::Stdin);
+    if options.no_editor && !piped {
+        return Err(Error::WithHint {
+            err: anyhow!("`--no-editor` requires the patch description on stdin"),
+            hint: "Pipe the description to `rad patch`, or omit `--no-editor`.",
+        }
+        .into());
+    }
+    let description = if options.scripted() {
+        let mut description = String::new();
+        io::stdin()
+            .read_to_string(&mut description)
+            .context("couldn't read the patch description from stdin")?;
+        if description.trim().is_empty() {
+            anyhow::bail!("the patch description read from stdin is empty");
+        }
+        description
+    } else {
+        let mut text = body;
+        // Like git, an aborted edit cancels, and an empty description doesn't go unnoticed.
+        loop {
+            let edited = frontend.edit(&text)?.ok_or_else(|| anyhow!("Canceled."))?;
+            let description = if template.is_some() {
+                patch::strip_comments(&edited)
+            } else {
+                edited.clone()
+            };
+            if !description.trim().is_empty() {
+                break description;
+            }
+            term::warning("The patch description is empty.");
+            text = edited;
+
+            if !frontend.confirm("Edit the description again?") {
+                return Err(anyhow!("Canceled."));
+            }
+        }
     };
     term::success!(
         "{} {}",
@@ -185,71 +1564,301 @@ fn create(
     term::markdown(&description);
     term::blank();
 
-    if term::confirm("Submit using title and description?") {
-        term::blank();
+    if !options.scripted() && !frontend.confirm("Submit using title and description?") {
+        return Err(anyhow!("Canceled."));
+    }
+    term::blank();
 
-        let message = [title, description].join("\n");
-        create_patch(repo, &message, verbose)?;
+    let spinner = term::spinner("Adding patch...");
+    let result = if options.update {
+        patch::update_patch(
+            repo,
+            patches,
+            &project.urn,
+            &title,
+            &description,
+            target,
+            Some(head_ref),
+        )
+    } else {
+        patch::submit_patch(
+            repo,
+            patches,
+            &project.urn,
+            &title,
+            &description,
+            target,
+            &labels,
+            options.author.as_ref(),
+            Some(head_ref),
+            options.force,
+        )
+    };
+    match result {
+        Ok(patch_id) => {
+            spinner.finish();
+            Ok(patch_id)
+        }
+        Err(err) => {
+            spinner.failed();
+            Err(submit_error(err))
+        }
+    }
+}
 
-        if term::confirm("Sync to seed?") {
-            sync(current_branch.to_owned())?;
+/// Add a hint to patch submission errors that the user can act on.
+fn submit_error(err: patch::Error) -> anyhow::Error {
+    match err {
+        patch::Error::AlreadyExists(_) => Error::WithHint {
+            err: err.into(),
+            hint: "To add your changes to the existing patch, run `rad patch update`.",
         }
-    } else {
+        .into(),
+        patch::Error::NoPatch(_) => Error::WithHint {
+            err: err.into(),
+            hint: "To create a patch for the branch, run `rad patch`.",
+        }
+        .into(),
+        err => err.into(),
+    }
+}
+
+/// Get the labels to create a patch with. Labels given by the user replace the default
+/// labels of the working copy, which are otherwise applied unless disabled.
+fn create_labels(repo: &git::Repository, options: &Options) -> anyhow::Result<Vec<Label>> {
+    if !options.labels.is_empty() || options.no_default_labels {
+        return Ok(options.labels.clone());
+    }
+    patch::default_labels(repo).map_err(|err| {
+        Error::WithHint {
+            err: err.into(),
+            hint: "Fix the labels set with `git config rad.patch.defaultLabels`, or pass `--no-default-labels`.",
+        }
+        .into()
+    })
+}
+
+/// Delete a patch, either its COB from storage, or its tag locally and from the seed.
+fn delete(
+    patches: &Patches,
+    project: &project::Metadata,
+    repo: &git::Repository,
+    id: &str,
+    cob: bool,
+    confirm: bool,
+) -> anyhow::Result<()> {
+    if confirm && !term::confirm(format!("Delete patch {}?", term::format::highlight(id))) {
         return Err(anyhow!("Canceled."));
     }
 
-    term::blank();
-    term::info!(
-        "🌱 Created patch {}",
-        term::format::highlight(&current_branch)
-    );
+    if cob {
+        let patch_id = PatchId::from_str(id).map_err(|_| anyhow!("invalid patch id '{}'", id))?;
+
+        patches.remove(&project.urn, &patch_id)?;
+        term::warning("Peers who already fetched the patch may keep their copy of it.");
+    } else {
+        // Nb. Names that can't be the branch of a patch aren't found either.
+        let name = match patch::tag_name(repo, id) {
+            Ok(name) if patch::patch_tag(repo, id)?.is_some() => name,
+            Ok(_) | Err(patch::Error::InvalidTagName(_)) => {
+                return Err(Error::WithHint {
+                    err: anyhow!("patch '{}' was not found", id),
+                    hint: "To delete a patch COB, pass its id along with `--cob`.",
+                }
+                .into())
+            }
+            Err(err) => return Err(err.into()),
+        };
+        // Nb. The tag is only deleted locally once its deletion was pushed, so that a
+        // failed push can be retried.
+        let mut spinner = term::spinner("Deleting tag...");
+        if let Err(err) =
+            push_with_retry("tag deletion", &mut spinner, || git::push_tag_delete(&name))
+        {
+            spinner.failed();
+            return Err(err);
+        }
+        repo.tag_delete(&name)?;
+        spinner.finish();
+    }
+    term::success!("Deleted patch {}", term::format::highlight(id));
+
+    Ok(())
+}
+
+/// List the labels in use across all patches, most frequent first.
+fn labels(
+    patches: &PatchesReadOnly,
+    project: &project::Metadata,
+    repo: &git::Repository,
+) -> anyhow::Result<()> {
+    let mut counts: HashMap<Label, usize> = HashMap::new();
+    for (_, patch) in patches.all(&project.urn)?.patches {
+        for label in patch.labels {
+            *counts.entry(label).or_default() += 1;
+        }
+    }
+
+    if counts.is_empty() {
+        term::info!("No labels found.");
+        return Ok(());
+    }
+
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|(a, n), (b, m)| m.cmp(n).then_with(|| a.name().cmp(b.name())));
+
+    let mut table = term::Table::default();
+    for (label, count) in counts {
+        table.push([
+            term::format::label(&label, patch::label_color(repo, &label)),
+            term::format::dim(format!("{} patch(es)", count)),
+        ]);
+    }
+    table.render();
+
+    Ok(())
+}
+
+/// Print completion candidates, one per line.
+fn complete(
+    patches: &PatchesReadOnly,
+    project: &project::Metadata,
+    operation: Option<&str>,
+) -> anyhow::Result<()> {
+    match operation {
+        None => {
+            for op in OPERATIONS {
+                println!("{}", op);
+            }
+        }
+        Some("show") | Some("log") | Some("reactions") | Some("sync") | Some("merge")
+        | Some("review") | Some("delete") => {
+            for (id, _) in patches.all(&project.urn)?.patches {
+                println!("{}", id);
+            }
+        }
+        Some("label") => {
+            let mut labels = patches
+                .all(&project.urn)?
+                .patches
+                .into_iter()
+                .flat_map(|(_, patch)| patch.labels)
+                .map(String::from)
+                .collect::<Vec<_>>();
+            labels.sort();
+            labels.dedup();
 
+            for label in labels {
+                println!("{}", label);
+            }
+        }
+        Some(_) => {}
+    }
     Ok(())
 }
 
+/// Get the patches in the given state, filtered according to the options.
 fn list_by_state(
-    storage: &Storage,
+    storage: &ReadOnly,
     repo: &git::Repository,
+    cobs: &[(PatchId, Patch)],
     project: &project::Metadata,
-    table: &mut term::Table<2>,
     state: patch::State,
-) -> anyhow::Result<()> {
-    let mut patches: Vec<patch::Metadata> = patch::all(project, None, &storage)?;
+    options: &Options,
+) -> anyhow::Result<Vec<patch::Metadata>> {
+    let prefix = patch::tag_prefix(repo);
+    let mut patches: Vec<patch::Metadata> = patch::all(project, None, storage, &prefix)?;
 
     for (_, info) in project::tracked(project, storage)? {
-        let mut theirs = patch::all(project, Some(info), &storage)?;
+        let mut theirs = patch::all(project, Some(info), &storage, &prefix)?;
         patches.append(&mut theirs);
     }
-    patches.retain(|patch| state == patch::state(repo, patch));
+    patches.retain(|patch| self::state(repo, cobs, project, patch) == state);
+    if let Some(reviewer) = &options.reviewed_by {
+        // Reviews are only recorded in COBs, so patches without one are left out.
+        patches.retain(|patch| {
+            patch::find_cob(cobs, patch).map_or(false, |(_, cob)| cob.is_reviewed_by(reviewer))
+        });
+    }
+    if options.since.is_some() || options.until.is_some() {
+        // Patches without a known creation time can't be placed in the range.
+        patches.retain(|patch| match patch::timestamp(cobs, patch) {
+            Some(time) => {
+                options.since.map_or(true, |since| time >= since)
+                    && options.until.map_or(true, |until| time <= until)
+            }
+            None => false,
+        });
+    }
+    // Newest first, so that `--limit` keeps the most recent patches. Patches without a
+    // known creation time come last.
+    patches.sort_by_key(|patch| std::cmp::Reverse(patch::timestamp(cobs, patch)));
+    if options.reverse {
+        patches.reverse();
+    }
 
-    if !patches.is_empty() {
-        for patch in patches {
-            print(storage, &patch, table)?;
-        }
+    Ok(patches)
+}
+
+/// Get the state of a patch. The state recorded in its COB takes precedence over the local
+/// history, which may not include the merge yet. A merge in the local history overrides it
+/// however.
+fn state(
+    repo: &git::Repository,
+    cobs: &[(PatchId, Patch)],
+    project: &project::Metadata,
+    patch: &patch::Metadata,
+) -> patch::State {
+    let recorded = patch::state_from(patch::StateSource::Cobs(cobs), patch);
+    // Patches without a COB are meant to be merged into the default branch.
+    let head = match patch::find_cob(cobs, patch) {
+        Some((_, cob)) => cob.target_oid(repo),
+        None => patch::branch_head(repo, &project.default_branch),
+    };
+    let local = patch::state_at(repo, patch, head.ok().flatten());
+
+    if local == patch::State::Merged {
+        local
     } else {
-        table.push(["No patches found.".to_owned(), String::new()]);
+        recorded
     }
-
-    Ok(())
 }
 
 /// Create and push tag to monorepo.
+#[deprecated(note = "use `patch::submit_patch` followed by `push_patch` instead")]
 pub fn create_patch(repo: &git::Repository, message: &str, verbose: bool) -> anyhow::Result<()> {
-    let head = repo.head()?;
-    let current_branch = head.shorthand().unwrap_or("HEAD (no branch)");
-    let patch_tag_name = format!("{}{}", patch::TAG_PREFIX, &current_branch);
-    let mut spinner = term::spinner("Adding tag...");
+    let current_branch = patch::current_branch(repo)?;
+    let patch_tag_name = patch::tag_name(repo, &current_branch)?;
+    let head = repo.head()?.peel_to_commit()?.id();
+    let spinner = term::spinner("Adding tag...");
 
-    match git::add_tag(repo, message, &patch_tag_name) {
-        Ok(_) => {}
-        Err(err) => {
-            spinner.failed();
-            return Err(err);
+    if let Err(err) = git::add_tag(repo, message, &patch_tag_name, head, false) {
+        spinner.failed();
+        return Err(err.into());
+    }
+    push_patch(repo, spinner, false, verbose)
+}
+
+/// Push the tag of a submitted patch and its branch to the monorepo. The branch is only
+/// pushed up to the proposed commit, so that later commits, eg. when proposing a commit
+/// with `--commit`, aren't published.
+pub fn push_patch(
+    repo: &git::Repository,
+    mut spinner: term::Spinner,
+    force: bool,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    let current_branch = patch::current_branch(repo)?;
+    let patch_tag_name = patch::tag_name(repo, &current_branch)?;
+
+    let push_tag = || {
+        if force {
+            git::push_tag_force(&patch_tag_name)
+        } else {
+            git::push_tag(&patch_tag_name)
         }
     };
-
-    spinner.message("Pushing tag...".to_owned());
-    match git::push_tag(&patch_tag_name) {
+    match push_with_retry("tag", &mut spinner, push_tag) {
         Ok(output) => {
             if verbose {
                 term::blob(output);
@@ -261,8 +1870,8 @@ pub fn create_patch(repo: &git::Repository, message: &str, verbose: bool) -> any
         }
     };
 
-    spinner.message("Pushing branch...".to_owned());
-    match git::push_branch(current_branch) {
+    let refspec = branch_refspec(repo, &current_branch)?;
+    match push_with_retry("branch", &mut spinner, || git::push_refspec(&refspec)) {
         Ok(output) => {
             if verbose {
                 term::blob(output);
@@ -279,10 +1888,55 @@ pub fn create_patch(repo: &git::Repository, message: &str, verbose: bool) -> any
     Ok(())
 }
 
-/// Adds patch details as a new row to `table` and render later.
+/// The refspec that pushes a patch branch up to the commit of its patch tag.
+fn branch_refspec(repo: &git::Repository, branch: &str) -> anyhow::Result<String> {
+    let tag = patch::patch_tag(repo, branch)?
+        .ok_or_else(|| anyhow!("branch '{}' has no patch tag", branch))?;
+    let commit = repo.find_tag(tag)?.target_id();
+
+    Ok(format!("{}:refs/heads/{}", commit, branch))
+}
+
+/// Run a push, retrying with exponential backoff if it fails, eg. due to a flaky connection.
+/// The error of the last attempt is returned if none of them succeed.
+fn push_with_retry<F>(what: &str, spinner: &mut term::Spinner, push: F) -> anyhow::Result<String>
+where
+    F: Fn() -> anyhow::Result<String>,
+{
+    let mut backoff = PUSH_BACKOFF;
+    let mut attempt = 1;
+
+    loop {
+        if attempt == 1 {
+            spinner.message(format!("Pushing {}...", what));
+        } else {
+            spinner.message(format!(
+                "Pushing {} (attempt {}/{})...",
+                what, attempt, PUSH_ATTEMPTS
+            ));
+        }
+
+        match push() {
+            Ok(output) => return Ok(output),
+            Err(err) if attempt >= PUSH_ATTEMPTS => return Err(err),
+            Err(_) => {
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Adds patch details as a new row to `table` and render later. The patch's `labels` are
+/// shown after its author, if any. Whether its tag is `verified` is shown if known.
 pub fn print<S>(
     storage: &S,
     patch: &patch::Metadata,
+    unread: bool,
+    verified: Option<bool>,
+    labels: &str,
+    id_length: usize,
     table: &mut term::Table<2>,
 ) -> anyhow::Result<()>
 where
@@ -290,10 +1944,10 @@ where
 {
     let storage = storage.as_ref();
 
-    if let Some(message) = patch.message.clone() {
+    if patch.message.is_some() {
         let you = patch.peer.id == *storage.peer_id();
-        let title = message.lines().next().unwrap_or("");
-        let name = term::format::tertiary(&patch.id);
+        let title = patch.title();
+        let name = term::format::tertiary(patch.short_id(id_length));
 
         let mut author_info = vec![term::format::italic(format!(
             "└── Opened by {}",
@@ -303,20 +1957,516 @@ where
         if you {
             author_info.push(term::format::badge_secondary("you"));
         }
+        if patch.peer.delegate {
+            author_info.push(term::format::badge_secondary("delegate"));
+        }
+        match verified {
+            Some(true) => author_info.push(term::format::badge_positive("verified")),
+            Some(false) => author_info.push(term::format::dim("unverified")),
+            None => {}
+        }
+        if unread {
+            author_info.push(term::format::badge_primary("new activity"));
+        }
+        if !labels.is_empty() {
+            author_info.push(labels.to_owned());
+        }
+
+        let size = patch
+            .stats
+            .map(|stats| {
+                format!(
+                    "{} {}",
+                    term::format::positive(format!("+{}", stats.insertions)),
+                    term::format::negative(format!("-{}", stats.deletions)),
+                )
+            })
+            .unwrap_or_default();
+
+        // Only the title is truncated, not the rows with badges and labels.
+        let title = term::format::truncate(&title, TITLE_WIDTH);
 
-        table.push([term::format::bold(title), "".to_owned()]);
+        table.push([term::format::bold(&title), size]);
         table.push([author_info.join(" "), name]);
     }
     Ok(())
 }
 
-pub fn sync(current_branch: String) -> anyhow::Result<()> {
-    let sync_options = rad_sync::Options {
-        refs: rad_sync::Refs::Branch(current_branch),
-        verbose: false,
-        ..rad_sync::Options::default()
-    };
-    rad_sync::run(sync_options)?;
+/// Sync a patch branch with the given seed. Without one, `rad sync` picks the seed from the
+/// environment or configuration. Gives up after `timeout`, or when interrupted with Ctrl-C,
+/// in which case [`process::Error::Interrupted`] is returned.
+pub fn sync(
+    current_branch: String,
+    seed: Option<seed::Address>,
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    log::debug!(
+        "syncing branch {} with {}",
+        current_branch,
+        seed.as_ref()
+            .map_or_else(|| String::from("the default seed"), |s| s.to_string())
+    );
+    // Nb. Syncing runs in a separate process, so that it can be stopped, along with the
+    // git processes it runs, if it times out or is interrupted.
+    let mut cmd = Command::new("rad-sync");
+    cmd.arg("--branch").arg(&current_branch);
+    if let Some(seed) = &seed {
+        cmd.arg("--seed").arg(seed.to_string());
+    }
 
-    Ok(())
+    match process::run(cmd, timeout) {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(anyhow!("syncing with the seed failed ({})", status)),
+        Err(process::Error::Timeout(timeout)) => Err(anyhow!(
+            "syncing with the seed timed out after {} second(s)",
+            timeout.as_secs()
+        )),
+        Err(err @ process::Error::Interrupted) => Err(err.into()),
+        Err(err) => Err(anyhow!("couldn't run `rad sync`: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use librad::git::refs::Refs;
+
+    use radicle_common::test::{self, TempDir};
+
+    use super::frontend::Scripted;
+    use super::*;
+
+    /// Create a working copy in a temporary directory, on a `feature` branch one commit
+    /// ahead of the project's default branch.
+    fn working_copy() -> (TempDir, git::Repository) {
+        let (dir, repo) = test::setup::repository();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("user.name", "cloudhead").unwrap();
+            config
+                .set_str("user.email", "cloudhead@radicle.xyz")
+                .unwrap();
+        }
+        let sig = repo.signature().unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let base = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+        let feature = repo
+            .commit(
+                None,
+                &sig,
+                &sig,
+                "Fix the thing",
+                &tree,
+                &[&repo.find_commit(base).unwrap()],
+            )
+            .unwrap();
+
+        repo.reference("refs/remotes/rad/master", base, false, "test")
+            .unwrap();
+        repo.reference("refs/heads/feature", feature, false, "test")
+            .unwrap();
+        repo.set_head("refs/heads/feature").unwrap();
+
+        (dir, repo)
+    }
+
+    /// Answers of a user who creates a patch without viewing its changes.
+    fn answers(title: &str, description: &str) -> Scripted {
+        Scripted {
+            confirms: vec![false, true, true].into(),
+            inputs: vec![title.to_owned()].into(),
+            edits: vec![Some(description.to_owned())].into(),
+        }
+    }
+
+    #[test]
+    fn test_create() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let project = project::get(&storage, &project.urn()).unwrap().unwrap();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let (_dir, repo) = working_copy();
+        let head = repo.head().unwrap().target().unwrap();
+        let mut frontend = answers("Fix the thing", "It was broken.\n\nNow it works.");
+
+        let patch_id = propose(
+            &mut frontend,
+            &patches,
+            &project,
+            &repo,
+            &Options::default(),
+        )
+        .unwrap();
+
+        assert!(frontend.confirms.is_empty());
+        assert!(frontend.edits.is_empty());
+        let tag = patch::patch_tag(&repo, "feature").unwrap().unwrap();
+        assert_eq!(repo.find_tag(tag).unwrap().target_id(), head);
+
+        let cob = patches.get(&project.urn, &patch_id).unwrap().unwrap();
+        assert_eq!(cob.title, "Fix the thing");
+        assert_eq!(
+            cob.revisions.head.comment.body,
+            "It was broken.\n\nNow it works."
+        );
+        assert_eq!(*cob.revisions.head.commit, head);
+
+        // Declining to submit leaves no patch behind.
+        let (_dir, repo) = working_copy();
+        let mut frontend = answers("Fix the thing", "It was broken.");
+        frontend.confirms = vec![false, true, false].into();
+
+        assert!(propose(
+            &mut frontend,
+            &patches,
+            &project,
+            &repo,
+            &Options::default()
+        )
+        .is_err());
+        assert_eq!(patch::patch_tag(&repo, "feature").unwrap(), None);
+
+        // Aborting the edit of the description cancels, without asking again.
+        let mut frontend = answers("Fix the thing", "It was broken.");
+        frontend.confirms = vec![false, true].into();
+        frontend.edits = vec![None].into();
+
+        assert!(propose(
+            &mut frontend,
+            &patches,
+            &project,
+            &repo,
+            &Options::default()
+        )
+        .is_err());
+        assert!(frontend.confirms.is_empty());
+        assert_eq!(patch::patch_tag(&repo, "feature").unwrap(), None);
+    }
+
+    #[test]
+    fn test_create_commit() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let project = project::get(&storage, &project.urn()).unwrap().unwrap();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let (dir, repo) = working_copy();
+        let proposed = repo.head().unwrap().target().unwrap();
+        let later = {
+            let sig = repo.signature().unwrap();
+            let parent = repo.find_commit(proposed).unwrap();
+
+            repo.commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                "Work in progress",
+                &parent.tree().unwrap(),
+                &[&parent],
+            )
+            .unwrap()
+        };
+        let mut frontend = answers("Fix the thing", "It was broken.");
+        let options = Options {
+            commit: Some(proposed.to_string()),
+            ..Options::default()
+        };
+        propose(&mut frontend, &patches, &project, &repo, &options).unwrap();
+
+        // Pushing the patch publishes the branch up to the proposed commit only.
+        let remote = test::setup::tempdir();
+        let published = git::Repository::init_bare(remote.path()).unwrap();
+        let tag = format!("refs/tags/{}", patch::tag_name(&repo, "feature").unwrap());
+        let refspec = branch_refspec(&repo, "feature").unwrap();
+        git::git(
+            dir.path(),
+            vec![
+                "push",
+                remote.path().to_str().unwrap(),
+                tag.as_str(),
+                refspec.as_str(),
+            ],
+        )
+        .unwrap();
+
+        let branch = published.find_reference("refs/heads/feature").unwrap();
+        assert_eq!(branch.target(), Some(proposed));
+        assert!(published.find_commit(later).is_err());
+    }
+
+    #[test]
+    fn test_delete_not_found() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let project = project::get(&storage, &project.urn()).unwrap().unwrap();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let (_dir, repo) = working_copy();
+
+        // Unknown patches, and names that aren't valid tag names, point to `--cob`.
+        for id in ["feature", "feature..login"] {
+            let err = delete(&patches, &project, &repo, id, false, false)
+                .err()
+                .unwrap();
+
+            assert!(matches!(
+                err.downcast_ref::<Error>(),
+                Some(Error::WithHint { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_create_detached() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let project = project::get(&storage, &project.urn()).unwrap().unwrap();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let (_dir, repo) = working_copy();
+        let head = repo.head().unwrap().target().unwrap();
+        repo.set_head_detached(head).unwrap();
+
+        // Nothing is asked, and no tag is created.
+        let mut frontend = Scripted::default();
+        let err = propose(
+            &mut frontend,
+            &patches,
+            &project,
+            &repo,
+            &Options::default(),
+        )
+        .err()
+        .unwrap();
+
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::WithHint { .. })
+        ));
+        assert!(repo.tag_names(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let project = project::get(&storage, &project.urn()).unwrap().unwrap();
+        let patches = Patches::new(whoami, profile.paths(), &storage).unwrap();
+        let (_dir, repo) = working_copy();
+        let mut frontend = answers("Fix the thing", "It was broken.");
+
+        propose(
+            &mut frontend,
+            &patches,
+            &project,
+            &repo,
+            &Options::default(),
+        )
+        .unwrap();
+
+        // Publish the patch tag to storage, as pushing the patch does.
+        let tag = patch::tag_name(&repo, "feature").unwrap();
+        let mut remote = repo
+            .remote_anonymous(&storage.path().to_string_lossy())
+            .unwrap();
+        remote
+            .push(
+                &[format!(
+                    "refs/tags/{}:refs/namespaces/{}/refs/tags/{}",
+                    tag,
+                    project.urn.encode_id(),
+                    tag
+                )],
+                None,
+            )
+            .unwrap();
+        Refs::update(&storage, &project.urn).unwrap();
+
+        // Listing only needs read-only storage.
+        let read_only = profile::read_only(&profile).unwrap();
+        let cobs = patches.all(&project.urn).unwrap().patches;
+        let options = Options::default();
+        let open = list_by_state(
+            &read_only,
+            &repo,
+            &cobs,
+            &project,
+            patch::State::Open,
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].title(), "Fix the thing");
+        assert_eq!(open[0].description(), "It was broken.");
+        assert!(list_by_state(
+            &read_only,
+            &repo,
+            &cobs,
+            &project,
+            patch::State::Merged,
+            &options
+        )
+        .unwrap()
+        .is_empty());
+
+        let seen = Seen::open(profile.paths()).unwrap();
+        let options = Options {
+            format: ListFormat::Oneline,
+            ..Options::default()
+        };
+        list(&read_only, &patches, &seen, &project, &repo, &options).unwrap();
+    }
+
+    #[test]
+    fn test_target_option() {
+        let parse = |target: &str| Options::from_args(vec!["--target".into(), target.into()]);
+
+        let (options, _) = parse("release").unwrap();
+        assert_eq!(options.target.as_deref(), Some("release"));
+
+        let err = parse("feature/x").err().unwrap();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::WithHint { .. })
+        ));
+        assert!(parse("feature..x").is_err());
+    }
+
+    #[test]
+    fn test_create_labels() {
+        let (_dir, repo) = test::setup::repository();
+        let label = |name: &str| Label::new(name).unwrap();
+        repo.config()
+            .unwrap()
+            .set_str(patch::CONFIG_DEFAULT_LABELS_KEY, "needs-review")
+            .unwrap();
+
+        let options = Options::default();
+        assert_eq!(
+            create_labels(&repo, &options).unwrap(),
+            vec![label("needs-review")]
+        );
+
+        let options = Options {
+            labels: vec![label("bug")],
+            ..Options::default()
+        };
+        assert_eq!(create_labels(&repo, &options).unwrap(), vec![label("bug")]);
+
+        let options = Options {
+            no_default_labels: true,
+            ..Options::default()
+        };
+        assert!(create_labels(&repo, &options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reverse_option() {
+        let (options, _) = Options::from_args(vec![]).unwrap();
+        assert!(!options.reverse);
+
+        let (options, _) =
+            Options::from_args(vec!["--reverse".into(), "--limit".into(), "1".into()]).unwrap();
+        assert!(options.reverse);
+        assert_eq!(options.limit, Some(1));
+    }
+
+    #[test]
+    fn test_until_option() {
+        let (options, _) = Options::from_args(vec!["--until".into(), "2022-05-01".into()]).unwrap();
+        // The whole day is included.
+        assert_eq!(
+            options.until,
+            Some(Timestamp::new(1_651_363_200 + 24 * 60 * 60 - 1))
+        );
+        assert!(Options::from_args(vec!["--until".into(), "2022-02-31".into()]).is_err());
+    }
+
+    #[test]
+    fn test_verify_option() {
+        let (options, _) = Options::from_args(vec![]).unwrap();
+        assert!(!options.verify);
+
+        let (options, _) = Options::from_args(vec!["--list".into(), "--verify".into()]).unwrap();
+        assert!(matches!(
+            options.op,
+            Operation::Project(ProjectOperation::List)
+        ));
+        assert!(options.verify);
+    }
+
+    #[test]
+    fn test_author_option() {
+        let urn = Urn::new(git::Oid::zero().into()).to_string();
+        let parse =
+            |args: &[&str]| Options::from_args(args.iter().map(OsString::from).collect::<Vec<_>>());
+
+        let (options, _) = parse(&["--author", &urn]).unwrap();
+        assert!(matches!(
+            options.op,
+            Operation::Project(ProjectOperation::Create)
+        ));
+        assert!(options.author.is_some());
+
+        assert!(parse(&["--list", "--author", &urn]).is_err());
+        assert!(parse(&[
+            "merge",
+            &PatchId::from(git::Oid::zero()).to_string(),
+            "--author",
+            &urn
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_scripted() {
+        assert!(!Options::default().scripted());
+        assert!(Options {
+            no_editor: true,
+            ..Options::default()
+        }
+        .scripted());
+    }
+
+    #[test]
+    fn test_update_operation() {
+        let urn = Urn::new(git::Oid::zero().into()).to_string();
+        let parse =
+            |args: &[&str]| Options::from_args(args.iter().map(OsString::from).collect::<Vec<_>>());
+
+        let (options, _) = parse(&["update"]).unwrap();
+        assert!(matches!(
+            options.op,
+            Operation::Project(ProjectOperation::Create)
+        ));
+        assert!(options.update);
+        assert!(!parse(&[]).unwrap().0.update);
+        assert!(parse(&["update", "--author", &urn]).is_err());
+
+        let err = submit_error(patch::Error::AlreadyExists(String::from("feature")));
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::WithHint { hint, .. }) if hint.contains("rad patch update")
+        ));
+    }
+
+    #[test]
+    fn test_list_option_values() {
+        let parse = |flag: &str, value: &str| Options::from_args(vec![flag.into(), value.into()]);
+
+        assert_eq!(
+            parse("--format", "oneline").unwrap().0.format,
+            ListFormat::Oneline
+        );
+        assert_eq!(parse("--revision", "2").unwrap().0.revision, Some(2));
+
+        let (options, _) = Options::from_args(vec!["--json".into()]).unwrap();
+        assert_eq!(options.format, ListFormat::Json);
+
+        for (flag, value) in [
+            ("--limit", "-1"),
+            ("--format", "yaml"),
+            ("--revision", "latest"),
+            ("--sync-timeout", "1m"),
+        ] {
+            let err = parse(flag, value).err().unwrap().to_string();
+            assert!(err.contains(flag), "{}", err);
+        }
+    }
 }
@@ -0,0 +1,108 @@
+//! Merging of patches into their target branch.
+use anyhow::anyhow;
+
+use librad::git::Storage;
+
+use radicle_common::args::Error;
+use radicle_common::cobs::patch::{PatchId, Patches};
+use radicle_common::{git, patch, project};
+use radicle_terminal as term;
+
+/// Merge the latest revision of a patch into its target branch, push the branch, and record
+/// the merge in the patch. The target branch must be checked out.
+pub fn run(
+    storage: &Storage,
+    patches: &Patches,
+    project: &project::Metadata,
+    repo: &git::Repository,
+    patch_id: &PatchId,
+    confirm: bool,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    let patch = patches
+        .get(&project.urn, patch_id)?
+        .ok_or_else(|| anyhow!("patch {} was not found in {}", patch_id, project.name))?;
+    let revision = patch.latest_revision();
+    let target = patch.target.to_string();
+
+    if project::get_local_head(storage, &project.urn, &target)?.is_none() {
+        anyhow::bail!(
+            "target branch '{}' of patch {} was not found in {}",
+            target,
+            patch_id,
+            project.name
+        );
+    }
+    let head = repo.head()?;
+    if !head.is_branch() || head.shorthand() != Some(target.as_str()) {
+        return Err(Error::WithHint {
+            err: anyhow!("the target branch '{}' is not checked out", target),
+            hint: "Check out the target branch first, eg. with `git checkout`.",
+        }
+        .into());
+    }
+    if !patch::revision_is_local(repo, revision) {
+        return Err(Error::WithHint {
+            err: anyhow!("patch commit {} was not found", revision.commit),
+            hint: "Fetch the changes of the patch author first, eg. with `rad pull`.",
+        }
+        .into());
+    }
+    // Fail before asking for confirmation if there is nothing to merge. The target branch
+    // is checked out, so this is the branch the patch would be merged into.
+    let head = head.peel_to_commit()?.id();
+    if head == *revision.commit || repo.graph_descendant_of(head, *revision.commit)? {
+        return Err(patch::Error::AlreadyMerged(*revision.commit).into());
+    }
+
+    term::info!(
+        "Merging {} {} into {}.",
+        term::format::bold(&patch.title),
+        term::format::secondary(git::short(revision.commit)),
+        term::format::highlight(&target),
+    );
+    if confirm && !term::confirm("Merge patch?") {
+        return Err(anyhow!("Canceled."));
+    }
+
+    let message = format!("Merge patch {}\n\n{}", patch_id, patch.title);
+    let merged = match patch::merge(repo, *revision.commit, &message) {
+        Ok(oid) => oid,
+        Err(patch::Error::Conflicts(paths)) => {
+            for path in &paths {
+                term::warning(&format!("Conflict in {}", path));
+            }
+            return Err(Error::WithHint {
+                err: anyhow!("patch {} can't be merged cleanly into {}", patch_id, target),
+                hint: "Ask the patch author to rebase their changes on the target branch.",
+            }
+            .into());
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut spinner = term::spinner("Pushing branch...");
+    match super::push_with_retry("branch", &mut spinner, || git::push_branch(&target)) {
+        Ok(output) => {
+            if verbose {
+                term::blob(output);
+            }
+            spinner.finish();
+        }
+        Err(err) => {
+            spinner.failed();
+            return Err(err);
+        }
+    }
+    patches.merge(&project.urn, patch_id, revision.version, merged.into())?;
+
+    term::blank();
+    term::success!(
+        "Merged patch {} into {} at {}",
+        term::format::highlight(patch_id),
+        term::format::highlight(&target),
+        term::format::secondary(git::short(merged)),
+    );
+
+    Ok(())
+}
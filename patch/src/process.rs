@@ -0,0 +1,168 @@
+//! Running of commands that can be stopped along with the processes they spawn, eg. when
+//! they time out or are interrupted with Ctrl-C.
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval at which a running command is checked on.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Reason a command was stopped before it completed.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("timed out after {} second(s)", .0.as_secs())]
+    Timeout(Duration),
+    #[error("interrupted")]
+    Interrupted,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Run a command in its own process group, and wait for it to complete. If it takes longer
+/// than `timeout`, or is interrupted with Ctrl-C, the whole group is killed, including the
+/// processes spawned by the command, eg. git.
+///
+/// The terminal, if any, is handed to the group while it runs, so that the command can
+/// prompt the user, eg. for a passphrase.
+pub fn run(mut cmd: Command, timeout: Duration) -> Result<ExitStatus, Error> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let sigint = signal_hook::flag::register(signal_hook::consts::SIGINT, interrupted.clone())?;
+
+    // Safety: `setpgid` is async-signal-safe, so it may be called between fork and exec.
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setpgid(0, 0) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        });
+    }
+    let result = cmd
+        .spawn()
+        .map_err(Error::from)
+        .and_then(|child| wait(child, timeout, &interrupted));
+
+    signal_hook::low_level::unregister(sigint);
+
+    result
+}
+
+fn wait(
+    mut child: std::process::Child,
+    timeout: Duration,
+    interrupted: &AtomicBool,
+) -> Result<ExitStatus, Error> {
+    let pgid = child.id() as libc::pid_t;
+    let _foreground = Foreground::give(pgid);
+    let start = Instant::now();
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if status.signal() == Some(libc::SIGINT) {
+                return Err(Error::Interrupted);
+            }
+            return Ok(status);
+        }
+        let stop = if interrupted.load(Ordering::Relaxed) {
+            Some(Error::Interrupted)
+        } else if start.elapsed() >= timeout {
+            Some(Error::Timeout(timeout))
+        } else {
+            None
+        };
+        if let Some(err) = stop {
+            // Safety: only signals the process group created for the child.
+            unsafe {
+                libc::killpg(pgid, libc::SIGKILL);
+            }
+            child.wait()?;
+
+            return Err(err);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// The terminal, given to a process group for as long as this is alive.
+struct Foreground {
+    tty: File,
+}
+
+impl Foreground {
+    /// Give the controlling terminal to the given process group, if we are in the
+    /// foreground of one.
+    fn give(pgid: libc::pid_t) -> Option<Self> {
+        let tty = File::open("/dev/tty").ok()?;
+
+        // Safety: these only operate on the terminal and process groups, with valid
+        // descriptors and ids.
+        unsafe {
+            if libc::tcgetpgrp(tty.as_raw_fd()) != libc::getpgrp() {
+                return None;
+            }
+            // Nb. The child does so too, but may not have yet. Whichever runs second fails.
+            libc::setpgid(pgid, pgid);
+
+            if libc::tcsetpgrp(tty.as_raw_fd(), pgid) != 0 {
+                return None;
+            }
+        }
+        Some(Self { tty })
+    }
+}
+
+impl Drop for Foreground {
+    fn drop(&mut self) {
+        // Safety: see above. While in the background, taking the terminal back stops us
+        // with `SIGTTOU`, unless it is ignored.
+        unsafe {
+            let handler = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+            libc::tcsetpgrp(self.tty.as_raw_fd(), libc::getpgrp());
+            libc::signal(libc::SIGTTOU, handler);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let status = run(Command::new("true"), Duration::from_secs(10)).unwrap();
+        assert!(status.success());
+
+        let status = run(Command::new("false"), Duration::from_secs(10)).unwrap();
+        assert!(!status.success());
+    }
+
+    #[test]
+    fn test_run_timeout() {
+        let dir = radicle_common::test::setup::tempdir();
+        let marker = dir.path().join("marker");
+
+        // The grand-child is killed along with the child, so the marker is never created.
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg("sh -c 'sleep 1; touch \"$1\"' sh \"$1\"; sleep 10")
+            .arg("sh")
+            .arg(&marker);
+
+        let start = Instant::now();
+        assert!(matches!(
+            run(cmd, Duration::from_millis(200)),
+            Err(Error::Timeout(_))
+        ));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        thread::sleep(Duration::from_millis(1500));
+        assert!(!marker.exists());
+    }
+}
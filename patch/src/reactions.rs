@@ -0,0 +1,61 @@
+//! Summary of the reactions to a patch, as a lightweight signal of sentiment.
+use anyhow::anyhow;
+
+use librad::git::storage::ReadOnly;
+
+use radicle_common::cobs::patch::{PatchId, PatchesReadOnly};
+use radicle_common::cobs::Author;
+use radicle_common::project;
+use radicle_terminal as term;
+
+/// Show the reactions to the comments of a patch, most frequent first, with the names of
+/// those who reacted.
+pub fn run(
+    storage: &ReadOnly,
+    patches: &PatchesReadOnly,
+    project: &project::Metadata,
+    patch_id: &PatchId,
+) -> anyhow::Result<()> {
+    let reactions = patches
+        .reactions(&project.urn, patch_id)?
+        .ok_or_else(|| anyhow!("patch {} was not found in {}", patch_id, project.name))?;
+
+    term::headline(&format!(
+        "🌱 Reactions to patch {}",
+        term::format::highlight(patch_id)
+    ));
+    if reactions.is_empty() {
+        term::info!("No reactions yet.");
+        return Ok(());
+    }
+
+    let mut reactions = reactions.into_iter().collect::<Vec<_>>();
+    reactions.sort_by(|(a, a_urns), (b, b_urns)| {
+        b_urns.len().cmp(&a_urns.len()).then(a.emoji.cmp(&b.emoji))
+    });
+
+    let mut table = term::Table::default();
+    for (reaction, urns) in reactions {
+        let count = urns.len();
+        // Nb. The same person may have reacted this way to several comments.
+        let mut names = urns
+            .into_iter()
+            .map(|urn| {
+                let mut author = Author::Urn { urn };
+                author.resolve(storage).ok();
+                author.to_string()
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+
+        table.push([
+            format!("{} {}", reaction.emoji, term::format::bold(count)),
+            term::format::dim(names.join(", ")),
+        ]);
+    }
+    table.render();
+    term::blank();
+
+    Ok(())
+}
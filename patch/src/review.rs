@@ -0,0 +1,141 @@
+//! Reviewing of patches, including patches shared out-of-band that aren't available locally.
+use anyhow::anyhow;
+
+use librad::git::{Storage, Urn};
+use librad::profile::Profile;
+
+use radicle_common::cobs::patch::{Patches, Verdict};
+use radicle_common::cobs::seen::Seen;
+use radicle_common::patch::{revision_is_local, PatchRef};
+use radicle_common::seed::{self, Scope};
+use radicle_common::{git, keys, person, project, Url};
+use radicle_terminal as term;
+
+use super::show;
+
+/// Review the latest revision of a patch. If the patch isn't available locally, it is fetched
+/// first. Prompts for the verdict and comment if they aren't given.
+pub fn run(
+    profile: &Profile,
+    patch: &PatchRef,
+    verdict: Option<Verdict>,
+    message: Option<String>,
+) -> anyhow::Result<()> {
+    let storage = keys::storage(profile, term::signer(profile)?)?;
+    let urn = match &patch.origin {
+        Some(origin) => origin.urn.clone(),
+        None => match project::cwd_checked() {
+            Ok((urn, _)) => urn,
+            Err(project::CwdError::Other(err)) => return Err(err),
+            Err(_) => anyhow::bail!(
+                "outside of a project, the patch must be given as <project-urn>:<patch-id>"
+            ),
+        },
+    };
+    let whoami = person::local(&storage)?;
+    let patches = Patches::new(whoami, profile.paths(), &storage)?;
+
+    // The patch is fetched if it's missing, or if its code is, eg. because it was only
+    // fetched along with the patch author's identity.
+    let available = project::get(&storage, &urn)?.is_some()
+        && match patches.get(&urn, &patch.id)? {
+            Some(cob) => {
+                let repo = git::Repository::open_bare(storage.path())?;
+                revision_is_local(&repo, cob.latest_revision())
+            }
+            None => false,
+        };
+    if !available {
+        let seed = match patch.origin.as_ref().and_then(|o| o.seed_url()) {
+            Some(url) => url,
+            None => match seed::env_seed()? {
+                Some(addr) => addr.url(),
+                None => seed::get_seed(Scope::Any)?,
+            },
+        };
+        fetch(&storage, patch, &urn, &seed)?;
+    }
+
+    let project = project::get(&storage, &urn)?
+        .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?;
+    let cob = patches
+        .get(&urn, &patch.id)?
+        .ok_or_else(|| anyhow!("patch {} was not found in {}", patch.id, project.name))?;
+    let revision = cob.latest_revision();
+
+    // Label colors are taken from the working copy, if reviewing from one.
+    let repo = git::repository().ok();
+    show::run(
+        storage.as_ref(),
+        &patches,
+        &project,
+        repo.as_ref(),
+        &patch.id,
+        None,
+        false,
+        false,
+        None,
+    )?;
+
+    let verdict = match verdict {
+        Some(verdict) => verdict,
+        None => term::select_one("Verdict", &Verdict::ALL)?,
+    };
+    let comment = match message {
+        Some(message) => message,
+        None => term::text_input_optional::<String, _>("Comment", None)?.unwrap_or_default(),
+    };
+    patches.review(&urn, &patch.id, revision.version, verdict, &comment)?;
+
+    // The patch was shown, and the review added since isn't new to the reviewer.
+    if let Some(tips) = patches.snapshot(&urn)?.tips(&patch.id) {
+        let mut seen = Seen::open(profile.paths())?;
+        seen.mark(&patch.id, tips.clone());
+        seen.save()?;
+    }
+
+    term::success!(
+        "Reviewed revision {} of patch {}: {}",
+        revision.version,
+        term::format::highlight(&patch.id),
+        show::verdict(verdict)
+    );
+
+    Ok(())
+}
+
+/// Fetch a patch and its commits from the seed. The project identity is fetched too, if
+/// it isn't available locally. The remotes of all peers known to the seed are fetched,
+/// since the patch author needn't be a delegate or tracked peer. Everything fetched is
+/// kept in storage, so the patch needn't be fetched again.
+fn fetch(storage: &Storage, patch: &PatchRef, urn: &Urn, seed: &Url) -> anyhow::Result<()> {
+    if project::get(storage, urn)?.is_none() {
+        rad_sync::run(rad_sync::Options {
+            origin: Some(project::Origin {
+                urn: urn.clone(),
+                seed: patch.origin.as_ref().and_then(|o| o.seed.clone()),
+            }),
+            fetch: true,
+            identity: true,
+            ..rad_sync::Options::default()
+        })?;
+    }
+
+    let mut spinner = term::spinner(&format!("Fetching patch {}...", patch.id));
+    let peers = seed::get_remotes(seed.clone(), urn)?;
+    match term::sync::fetch_remotes(
+        storage,
+        seed,
+        urn,
+        peers.iter().map(|p| &p.id),
+        &mut spinner,
+    ) {
+        Ok(_) => spinner.finish(),
+        Err(err) => {
+            spinner.failed();
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
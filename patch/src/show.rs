@@ -0,0 +1,285 @@
+//! Patch display, including revisions and their reviews.
+use std::collections::{HashMap, HashSet};
+
+use anyhow::anyhow;
+
+use librad::git::storage::ReadOnly;
+use librad::git::Urn;
+
+use radicle_common as common;
+use radicle_common::cobs::patch::{
+    Patch, PatchId, PatchesReadOnly, Revision, RevisionId, State, Verdict,
+};
+use radicle_common::cobs::{Author, Label};
+use radicle_common::{git, patch, project};
+use radicle_terminal as term;
+
+/// How to show the changes of a revision, see [`run`].
+#[derive(Debug, Clone, Copy)]
+pub struct Diff {
+    /// Highlight changed words rather than lines.
+    pub words: bool,
+    /// Page long diffs.
+    pub pager: bool,
+}
+
+/// Show a patch, with the given revision and its reviews, or the latest revision if none is
+/// given. With `json`, the full patch is printed as JSON instead, along with its id. With
+/// `resolve_ens`, authors are shown by their ENS name, if their identity has one. With a
+/// `diff`, the changes of the revision are shown last, if they are available locally.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    storage: &ReadOnly,
+    patches: &PatchesReadOnly,
+    project: &project::Metadata,
+    repo: Option<&git::Repository>,
+    patch_id: &PatchId,
+    revision: Option<RevisionId>,
+    json: bool,
+    resolve_ens: bool,
+    diff: Option<Diff>,
+) -> anyhow::Result<()> {
+    let mut patch = patches
+        .get(&project.urn, patch_id)?
+        .ok_or_else(|| anyhow!("patch {} was not found in {}", patch_id, project.name))?;
+
+    // Authors whose identity isn't known locally are displayed by URN. The JSON output
+    // includes ENS names as they are, so they don't replace author names there.
+    let mut authors = Authors::new(resolve_ens && !json);
+    authors.resolve(&mut patch.author, storage);
+    for revision in patch.revisions.iter_mut() {
+        authors.resolve(&mut revision.author, storage);
+
+        for review in revision.reviews.values_mut() {
+            authors.resolve(&mut review.author, storage);
+        }
+        for comment in revision.discussion.iter_mut() {
+            authors.resolve(&mut comment.author, storage);
+
+            for reply in comment.replies.iter_mut() {
+                authors.resolve(&mut reply.author, storage);
+            }
+        }
+    }
+
+    if json {
+        let mut value = serde_json::to_value(&patch)?;
+        value["id"] = serde_json::Value::from(patch_id.to_string());
+        value["project"] = super::project_json(project);
+        println!("{}", serde_json::to_string_pretty(&value)?);
+
+        return Ok(());
+    }
+
+    term::headline(&format!(
+        "🌱 {} {}",
+        term::format::bold(&patch.title),
+        verdicts(&patch)
+    ));
+
+    let mut table = term::Table::default();
+    table.push([String::from("ID"), term::format::tertiary(patch_id)]);
+    table.push([
+        String::from("Author"),
+        term::format::tertiary(&patch.author),
+    ]);
+    table.push([String::from("State"), state(patch.state)]);
+    table.push([
+        String::from("Target"),
+        term::format::highlight(&patch.target),
+    ]);
+    if !patch.labels.is_empty() {
+        table.push([String::from("Labels"), labels(repo, &patch.labels)]);
+    }
+    table.push([
+        String::from("Revisions"),
+        term::format::dim(patch.revision_count()),
+    ]);
+    table.render_tree();
+    term::blank();
+
+    let revision = match revision {
+        Some(version) => patch.revisions.get(version).ok_or_else(|| {
+            anyhow!(
+                "revision {} of patch {} was not found, it has {} revision(s)",
+                version,
+                patch_id,
+                patch.revision_count()
+            )
+        })?,
+        None => patch.latest_revision(),
+    };
+    // Show the full range of the revision when its base is known. Revisions that didn't
+    // record their base are compared against the current head of the target branch.
+    let base = match (revision.base, repo) {
+        (Some(base), _) => Some(base),
+        (None, Some(repo)) if patch::revision_is_local(repo, revision) => {
+            match patch.target_oid(repo)? {
+                Some(target) => patch::merge_base_between(repo, target, *revision.commit)?,
+                None => None,
+            }
+        }
+        (None, _) => None,
+    };
+    let range = match base {
+        Some(base) => format!("{}..{}", git::short(base), git::short(revision.commit)),
+        None => git::short(revision.commit),
+    };
+    // Revisions may be authored and published by others than the patch author, so
+    // both are shown.
+    let verified = if is_verified(storage, project, repo, revision)? {
+        term::format::badge_positive("verified")
+    } else {
+        String::new()
+    };
+    term::info!(
+        "{} {} {} {} {}",
+        term::format::bold(format!("Revision {}", revision.version)),
+        term::format::secondary(range),
+        term::format::italic(format!("by {}", revision.author)),
+        term::format::dim(format!("via {}", common::fmt::peer(&revision.peer))),
+        verified,
+    );
+    if repo.map_or(false, |repo| !patch::revision_is_local(repo, revision)) {
+        term::warning("The code of this revision isn't available locally.");
+        term::tip!("Fetch the changes of the revision author first, eg. with `rad pull`.");
+    }
+    term::discussion::body(&revision.comment);
+
+    for review in revision.reviews.values() {
+        term::indented(&format!(
+            "{} {} {}",
+            verdict(review.verdict),
+            term::format::tertiary(&review.author),
+            term::format::italic(&review.comment.body),
+        ));
+    }
+    term::blank();
+    term::discussion(&revision.discussion);
+
+    match (diff, repo, base) {
+        (Some(diff), Some(repo), Some(base)) if patch::revision_is_local(repo, revision) => {
+            term::blank();
+            if diff.words {
+                term::patch::word_diff(repo, &base, &revision.commit, diff.pager)?;
+            } else {
+                git::view_diff(repo, &base, &revision.commit, diff.pager)?;
+            }
+        }
+        (Some(_), Some(repo), None) if patch::revision_is_local(repo, revision) => {
+            term::warning("The changes of this revision can't be shown, its base is unknown.");
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Whether the patch tag of a revision, as published by its peer, is signed by that peer.
+/// Only the revision shown is verified, since verifying a signature runs `ssh-keygen`.
+fn is_verified(
+    storage: &ReadOnly,
+    project: &project::Metadata,
+    repo: Option<&git::Repository>,
+    revision: &Revision,
+) -> anyhow::Result<bool> {
+    let prefix = repo.map_or_else(|| patch::TAG_PREFIX.to_owned(), patch::tag_prefix);
+    let peer = if revision.peer == *storage.peer_id() {
+        None
+    } else {
+        Some(project::PeerInfo::get(&revision.peer, project, storage))
+    };
+    let tag = patch::all(project, peer, storage, &prefix)?
+        .into_iter()
+        .find(|tag| tag.commit == revision.commit);
+
+    Ok(tag.map_or(false, |tag| patch::verify(storage, &tag)))
+}
+
+/// Resolution of authors to their identities, looked up once per author, since the same
+/// authors tend to appear throughout a patch.
+struct Authors {
+    /// Show authors by their ENS name rather than their identity name.
+    ens: bool,
+    cache: HashMap<Urn, Option<project::PeerIdentity>>,
+}
+
+impl Authors {
+    fn new(ens: bool) -> Self {
+        Self {
+            ens,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Resolve the identity of an author. Authors whose identity can't be found are left
+    /// as they are.
+    fn resolve(&mut self, author: &mut Author, storage: &ReadOnly) {
+        let urn = author.urn().clone();
+        let ens = self.ens;
+        let identity = self.cache.entry(urn).or_insert_with_key(|urn| {
+            let mut identity = project::PeerIdentity::get(urn, storage).ok().flatten()?;
+            if let Some(name) = identity.ens.as_ref().filter(|_| ens) {
+                identity.name = name.name.clone();
+            }
+            Some(identity)
+        });
+
+        if let Some(identity) = identity {
+            *author = Author::Resolved(identity.clone());
+        }
+    }
+}
+
+/// Render a patch state.
+pub fn state(state: State) -> String {
+    match state {
+        State::Open => term::format::positive(state),
+        State::Draft => term::format::dim(state),
+        State::Closed => term::format::negative(state),
+    }
+}
+
+/// Render a review verdict, colored according to its outcome.
+pub fn verdict(verdict: Verdict) -> String {
+    match verdict {
+        Verdict::Accept => term::format::positive("✓ accept"),
+        Verdict::Reject => term::format::negative("✗ reject"),
+        Verdict::Pass => term::format::dim("- pass"),
+    }
+}
+
+/// Render labels in their colors, sorted by name. Colors set in the working copy are used,
+/// if there is one.
+pub fn labels(repo: Option<&git::Repository>, labels: &HashSet<Label>) -> String {
+    let mut labels = labels.iter().collect::<Vec<_>>();
+    labels.sort_by(|a, b| a.name().cmp(b.name()));
+
+    labels
+        .into_iter()
+        .map(|label| {
+            let color = match repo {
+                Some(repo) => patch::label_color(repo, label),
+                None => label.default_color(),
+            };
+            term::format::label(label, color)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Compact summary of the verdicts given on the latest revision, eg. `✓2 ✗1`.
+pub fn verdicts(patch: &Patch) -> String {
+    let reviews = &patch.latest_revision().reviews;
+    let count = |v: Verdict| reviews.values().filter(|r| r.verdict == v).count();
+    let (accepted, rejected) = (count(Verdict::Accept), count(Verdict::Reject));
+    let mut summary = Vec::new();
+
+    if accepted > 0 {
+        summary.push(term::format::positive(format!("✓{}", accepted)));
+    }
+    if rejected > 0 {
+        summary.push(term::format::negative(format!("✗{}", rejected)));
+    }
+    summary.join(" ")
+}
@@ -0,0 +1,82 @@
+//! Status of the patch of the current branch.
+use anyhow::anyhow;
+
+use librad::git::storage::ReadOnly;
+
+use radicle_common::cobs::patch::PatchesReadOnly;
+use radicle_common::{git, patch, project};
+use radicle_terminal as term;
+
+use super::show;
+
+/// Show the state of the patch created for the checked out branch, or suggest creating one.
+pub fn run(
+    storage: &ReadOnly,
+    patches: &PatchesReadOnly,
+    project: &project::Metadata,
+    repo: &git::Repository,
+) -> anyhow::Result<()> {
+    let branch = patch::current_branch(repo)?;
+    let branch = branch.as_str();
+
+    let tag = match patch::patch_tag(repo, branch)? {
+        Some(oid) => repo.find_tag(oid)?,
+        None => {
+            term::info!(
+                "There is no patch for branch {}.",
+                term::format::highlight(branch)
+            );
+            term::tip!("To create one, run `rad patch`.");
+
+            return Ok(());
+        }
+    };
+    let info = project::PeerInfo::get(storage.peer_id(), project, storage);
+    let metadata = patch::from_tag(tag, info, &patch::tag_prefix(repo))?
+        .ok_or_else(|| anyhow!("patch tag of branch '{}' is invalid", branch))?;
+    // Only the COB of this patch is needed, so the others aren't constructed.
+    let cobs = patches
+        .iter(&project.urn)?
+        .filter_map(Result::ok)
+        .find(|(_, cob)| cob.revisions.iter().any(|r| r.commit == metadata.commit))
+        .into_iter()
+        .collect::<Vec<_>>();
+    let cob = patch::find_cob(&cobs, &metadata);
+    let state = super::state(repo, &cobs, project, &metadata);
+
+    let title = metadata.title();
+    let summary = cob.map(|(_, cob)| show::verdicts(cob)).unwrap_or_default();
+    term::headline(&format!("🌱 {} {}", term::format::bold(title), summary));
+
+    let mut table = term::Table::default();
+    table.push([String::from("Branch"), term::format::highlight(branch)]);
+    if let Some((id, _)) = cob {
+        table.push([String::from("ID"), term::format::tertiary(id)]);
+    }
+    table.push([
+        String::from("State"),
+        match state {
+            patch::State::Open => term::format::positive(state),
+            patch::State::Draft => term::format::dim(state),
+            patch::State::Merged => term::format::secondary(state),
+            patch::State::Closed => term::format::negative(state),
+        },
+    ]);
+    table.push([
+        String::from("Commit"),
+        term::format::secondary(git::short(metadata.commit)),
+    ]);
+    if let Some((_, cob)) = cob {
+        table.push([
+            String::from("Revisions"),
+            term::format::dim(cob.revision_count()),
+        ]);
+        table.push([
+            String::from("Reviews"),
+            term::format::dim(cob.latest_revision().reviews.len()),
+        ]);
+    }
+    table.render_tree();
+
+    Ok(())
+}
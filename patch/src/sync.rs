@@ -0,0 +1,133 @@
+//! Syncing of an existing patch with seeds, eg. after pushing a new revision.
+use std::time::Duration;
+
+use anyhow::anyhow;
+
+use librad::git::storage::ReadOnly;
+
+use radicle_common::args::Error;
+use radicle_common::cobs::patch::{PatchId, PatchesReadOnly};
+use radicle_common::seed::{self, Scope};
+use radicle_common::{git, patch, project};
+use radicle_terminal as term;
+
+/// Sync the branch of a patch with each of the given seeds, or with the default seed if
+/// none are given. The patch must have been pushed to storage, eg. with `rad push`.
+pub fn run(
+    storage: &ReadOnly,
+    patches: &PatchesReadOnly,
+    project: &project::Metadata,
+    repo: &git::Repository,
+    patch_id: &PatchId,
+    seeds: &[seed::Address],
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    let cob = patches
+        .get(&project.urn, patch_id)?
+        .ok_or_else(|| anyhow!("patch {} was not found in {}", patch_id, project.name))?;
+    // The branch of a patch is the name of the patch tag of one of its revisions.
+    let prefix = patch::tag_prefix(repo);
+    let branch = patch::all(project, None, &storage, &prefix)?
+        .into_iter()
+        .find(|metadata| cob.revisions.iter().any(|r| r.commit == metadata.commit))
+        .map(|metadata| metadata.id)
+        .ok_or_else(|| Error::WithHint {
+            err: anyhow!("patch {} has no tag in your storage", patch_id),
+            hint: "Only your own patches can be synced, once pushed with `rad push`.",
+        })?;
+
+    term::headline(&format!(
+        "🌱 Syncing patch {} ({})",
+        term::format::highlight(patch_id),
+        term::format::highlight(&branch)
+    ));
+    self::branch(repo, &branch, seeds, timeout)
+}
+
+/// Sync a patch branch with each of the given seeds, and report the result for each seed.
+/// Fails if syncing with any seed failed.
+///
+/// If no seeds are given, the seed last synced with from this working copy is used, unless
+/// one is set in the environment, otherwise the default seed. The seed last synced with is
+/// remembered under `rad.seed`, in the local git configuration.
+pub fn branch(
+    repo: &git::Repository,
+    branch: &str,
+    seeds: &[seed::Address],
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    let seeds = if !seeds.is_empty() {
+        seeds.iter().cloned().map(Some).collect()
+    } else if seed::env_seed()?.is_some() {
+        vec![None]
+    } else {
+        vec![last_seed(repo)]
+    };
+    let total = seeds.len();
+    let mut failed = 0;
+
+    for seed in seeds {
+        let name = seed
+            .as_ref()
+            .map_or_else(|| String::from("the default seed"), |s| s.to_string());
+
+        match super::sync(branch.to_owned(), seed.clone(), timeout) {
+            Ok(()) => {
+                term::success!("Synced with {}", term::format::highlight(&name));
+                if let Some(seed) = &seed {
+                    remember_seed(repo, seed)?;
+                }
+            }
+            Err(err) => {
+                // The user wants to stop syncing altogether, not just with this seed.
+                if let Some(super::process::Error::Interrupted) = err.downcast_ref() {
+                    anyhow::bail!("syncing with {} was interrupted", name);
+                }
+                failed += 1;
+                term::warning(&format!("Failed to sync with {}: {}", name, err));
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("syncing failed with {} of {} seed(s)", failed, total);
+    }
+    Ok(())
+}
+
+/// Get the seed last synced with from the given working copy, if any.
+pub fn last_seed(repo: &git::Repository) -> Option<seed::Address> {
+    let url = seed::get_seed(Scope::Local(workdir(repo))).ok()?;
+
+    seed::Address::from_url(&url)
+}
+
+/// Remember the seed synced with from the given working copy, see [`last_seed`].
+pub fn remember_seed(repo: &git::Repository, seed: &seed::Address) -> anyhow::Result<()> {
+    seed::set_seed(&seed.url(), Scope::Local(workdir(repo)))
+}
+
+fn workdir(repo: &git::Repository) -> &std::path::Path {
+    repo.workdir().unwrap_or_else(|| repo.path())
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_last_seed() {
+        let (_dir, repo) = radicle_common::test::setup::repository();
+
+        assert_eq!(last_seed(&repo), None);
+
+        let seed = seed::Address::from_str("willow.radicle.garden").unwrap();
+        remember_seed(&repo, &seed).unwrap();
+        assert_eq!(last_seed(&repo), Some(seed));
+
+        let seed = seed::Address::from_str("127.0.0.1:8778").unwrap();
+        remember_seed(&repo, &seed).unwrap();
+        assert_eq!(last_seed(&repo), Some(seed));
+    }
+}
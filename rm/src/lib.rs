@@ -72,9 +72,16 @@ pub fn run(options: Options) -> anyhow::Result<()> {
     }
     term::warning("Warning: experimental tool; use at your own risk!");
 
-    rad_untrack::execute(&options.urn, rad_untrack::Options { peer: None })?;
-
-    let monorepo = profile.paths().git_dir();
+    rad_untrack::execute(
+        &options.urn,
+        rad_untrack::Options {
+            peer: None,
+            prune: true,
+            confirm: false,
+        },
+    )?;
+
+    let monorepo = profile::git_dir(&profile);
     let namespace = monorepo
         .join("refs")
         .join("namespaces")
@@ -1,9 +1,11 @@
 use std::ffi::OsString;
 
 use radicle_common::args::{Args, Error, Help};
-use radicle_common::{keys, person, profile};
+use radicle_common::{keys, person, profile, project};
 use radicle_terminal as term;
 
+use serde_json::json;
+
 pub const HELP: Help = Help {
     name: "self",
     description: env!("CARGO_PKG_DESCRIPTION"),
@@ -11,21 +13,38 @@ pub const HELP: Help = Help {
     usage: r#"
 Usage
 
-    rad self [--help]
+    rad self [--json [--full]] [--help]
+
+Options
+
+    --json      Output information as JSON
+    --full      Include a summary of tracked projects and peers (requires --json)
+    --help      Print help
 "#,
 };
 
 #[derive(Default, Eq, PartialEq)]
-pub struct Options {}
+pub struct Options {
+    pub json: bool,
+    pub full: bool,
+}
 
 impl Args for Options {
     fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
         use lexopt::prelude::*;
 
         let mut parser = lexopt::Parser::from_args(args);
+        let mut json = false;
+        let mut full = false;
 
-        if let Some(arg) = parser.next()? {
+        while let Some(arg) = parser.next()? {
             match arg {
+                Long("json") => {
+                    json = true;
+                }
+                Long("full") => {
+                    full = true;
+                }
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
@@ -33,11 +52,18 @@ impl Args for Options {
             }
         }
 
-        Ok((Options {}, vec![]))
+        if full && !json {
+            anyhow::bail!("'--full' can only be used together with '--json'");
+        }
+
+        Ok((Options { json, full }, vec![]))
     }
 }
 
-pub fn run(_options: Options) -> anyhow::Result<()> {
+pub fn run(options: Options) -> anyhow::Result<()> {
+    if options.json {
+        return json(options.full);
+    }
     let mut table = term::Table::default();
 
     let profile = profile::default()?;
@@ -71,3 +97,49 @@ pub fn run(_options: Options) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Print identity information as JSON. If `full` is set, the tracked projects and
+/// peers are included. Only identities and tracking configuration are read, not COBs.
+fn json(full: bool) -> anyhow::Result<()> {
+    let profile = profile::default()?;
+    let storage = profile::read_only(&profile)?;
+    let peer_id = storage.peer_id();
+
+    let (urn, name) = match storage.config()?.user()? {
+        Some(urn) => {
+            let name = person::get(&storage, &urn)?.map(|p| p.subject().name.to_string());
+            (Some(urn), name)
+        }
+        None => (None, None),
+    };
+
+    let mut doc = json!({
+        "profile": profile.id().to_string(),
+        "name": name,
+        "urn": urn.map(|u| u.to_string()),
+        "peerId": peer_id.to_string(),
+        "sshKey": keys::to_ssh_key(peer_id)?,
+        "sshFingerprint": keys::to_ssh_fingerprint(peer_id)?,
+    });
+
+    if full {
+        let mut projects = Vec::new();
+
+        for (urn, meta, _) in project::list(&storage)? {
+            let peers = project::tracked(&meta, &storage)?
+                .into_keys()
+                .map(|peer| peer.to_string())
+                .collect::<Vec<_>>();
+
+            projects.push(json!({
+                "urn": urn.to_string(),
+                "name": meta.name,
+                "trackedPeers": peers,
+            }));
+        }
+        doc["projects"] = json!(projects);
+    }
+    println!("{}", serde_json::to_string_pretty(&doc)?);
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use std::ffi::OsString;
-use std::{io::ErrorKind, iter, process};
+use std::{env, io::ErrorKind, iter, process};
 
 use anyhow::anyhow;
 
@@ -40,6 +40,13 @@ fn parse_args() -> anyhow::Result<Command> {
             Long("version") => {
                 command = Some(Command::Version);
             }
+            // Nb. This is inherited by the sub-command, which loads the given profile.
+            Long("profile") if command.is_none() => {
+                env::set_var(radicle_common::args::PROFILE_ENV, parser.value()?);
+            }
+            Long("quiet") if command.is_none() => {
+                env::set_var(radicle_common::args::QUIET_ENV, "1");
+            }
             Value(val) if command.is_none() => {
                 if val == *"." {
                     command = Some(Command::External(vec![OsString::from("inspect")]));
@@ -49,6 +49,9 @@ Options
     --all               Sync all branches, not just the default branch (default: false)
     --branch <name>     Sync only the given branch
     --help              Print help
+
+    Unless `--seed` is given, the seed is read from the `RAD_SEED` environment
+    variable, and then from the `rad.seed` git configuration.
 "#,
 };
 
@@ -217,6 +220,8 @@ pub fn run(options: Options) -> anyhow::Result<()> {
         seed
     } else if let Some(seed) = &options.seed {
         seed.url()
+    } else if let Some(seed) = seed::env_seed()? {
+        seed.url()
     } else if let Ok(seed) = seed::get_seed(Scope::Any) {
         seed
     } else {
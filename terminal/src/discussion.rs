@@ -0,0 +1,42 @@
+//! Rendering of discussions, ie. comment threads in issues and patches.
+use radicle_common::cobs::{Comment, Discussion};
+
+use crate as term;
+
+/// Shown in place of the body of a deleted comment.
+pub const DELETED: &str = "[deleted]";
+
+/// Render a discussion. Replies are indented under the comment they reply to.
+pub fn discussion(discussion: &Discussion) {
+    for comment in discussion {
+        header(comment, "");
+        body(comment);
+
+        for reply in &comment.replies {
+            header(reply, &format!("{}└─ ", term::TAB));
+            for line in reply.body.lines() {
+                println!("{}{}{}", term::TAB, term::TAB, line);
+            }
+            term::blank();
+        }
+    }
+}
+
+/// Render the body of a comment, or a placeholder if it was deleted.
+pub fn body<R>(comment: &Comment<R>) {
+    if comment.deleted {
+        term::blank();
+        term::blob(DELETED);
+    } else {
+        term::markdown(&comment.body);
+    }
+}
+
+fn header<R>(comment: &Comment<R>, prefix: &str) {
+    println!(
+        "{}{} {}",
+        prefix,
+        term::format::tertiary(&comment.author),
+        term::format::dim(term::format::timeago(comment.timestamp)),
+    );
+}
@@ -0,0 +1,74 @@
+//! Editing of text in the user's editor, like git does.
+use std::env;
+use std::fs;
+use std::io;
+use std::process::{self, Command};
+
+use radicle_common::git;
+
+/// Git configuration key of the editor used by `rad`. Takes precedence over `VISUAL`
+/// and `EDITOR`.
+pub const CONFIG_EDITOR: &str = "rad.editor";
+/// Editor used if none is configured.
+pub const DEFAULT_EDITOR: &str = "vi";
+
+/// Get the user's editor: the `rad.editor` setting of the repository, if any, otherwise
+/// `VISUAL` or `EDITOR`, in that order. Empty settings are skipped.
+pub fn command(repo: Option<&git::Repository>) -> String {
+    let non_empty = |editor: &String| !editor.trim().is_empty();
+
+    repo.and_then(|repo| repo.config().ok())
+        .and_then(|config| config.get_string(CONFIG_EDITOR).ok())
+        .filter(non_empty)
+        .or_else(|| env::var("VISUAL").ok().filter(non_empty))
+        .or_else(|| env::var("EDITOR").ok().filter(non_empty))
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_owned())
+}
+
+/// Edit `text` with the given editor command, and return the edited text. Returns `None`
+/// if the editor exited with an error, eg. because the user aborted the edit.
+///
+/// Like git, the command is run by the shell, so that it may contain arguments, quoted
+/// or not, eg. `code --wait` or `"/Applications/Sublime Text.app/.../subl" -w`.
+pub fn edit(editor: &str, text: &str) -> io::Result<Option<String>> {
+    if editor.trim().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no editor configured",
+        ));
+    }
+    let path = env::temp_dir().join(format!("RAD_EDITMSG-{}.md", process::id()));
+    fs::write(&path, text)?;
+
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(editor)
+        .arg(&path)
+        .status();
+    let result = match status {
+        Ok(status) if status.success() => fs::read_to_string(&path).map(Some),
+        Ok(_) => Ok(None),
+        Err(err) => Err(err),
+    };
+    fs::remove_file(&path).ok();
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_edit() {
+        assert_eq!(edit("true", "text").unwrap(), Some(String::from("text")));
+        assert_eq!(edit("false", "text").unwrap(), None);
+        // Commands are run by the shell, with the path of the file as last argument.
+        assert_eq!(
+            edit("printf '%s' 'edited text' >", "text").unwrap(),
+            Some(String::from("edited text"))
+        );
+        assert!(edit(" ", "text").is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use dialoguer::console::style;
 
+use radicle_common::cobs::{Color, Label, Timestamp};
+
 pub fn negative<D: std::fmt::Display>(msg: D) -> String {
     style(msg).red().bright().to_string()
 }
@@ -36,6 +38,30 @@ pub fn badge_secondary<D: std::fmt::Display>(input: D) -> String {
     style(input).blue().reverse().to_string()
 }
 
+pub fn badge_positive<D: std::fmt::Display>(input: D) -> String {
+    style(input).green().reverse().to_string()
+}
+
+/// Render a label on a background of its color, approximated with the 256-color palette.
+pub fn label(label: &Label, color: Color) -> String {
+    // Index of the closest color in the palette's 6x6x6 color cube.
+    let cube = |c: u8| (c as u16 * 5 + 127) / 255;
+    let background = 16 + 36 * cube(color.r) + 6 * cube(color.g) + cube(color.b);
+    // Light colors are given dark text, and dark colors light text.
+    let luma = 299 * color.r as u32 + 587 * color.g as u32 + 114 * color.b as u32;
+    let foreground = if luma > 128_000 { 16 } else { 231 };
+
+    style(format!(" {} ", label.name()))
+        .color256(foreground)
+        .on_color256(background as u8)
+        .to_string()
+}
+
+/// Truncate `input` to `width` columns, ending it with an ellipsis if it was cut.
+pub fn truncate(input: &str, width: usize) -> String {
+    console::truncate_str(input, width, "…").into_owned()
+}
+
 pub fn bold<D: std::fmt::Display>(input: D) -> String {
     style(input).bold().to_string()
 }
@@ -47,3 +73,29 @@ pub fn dim<D: std::fmt::Display>(input: D) -> String {
 pub fn italic<D: std::fmt::Display>(input: D) -> String {
     style(input).italic().dim().to_string()
 }
+
+/// Format a timestamp relative to now, eg. "3 hours ago".
+pub fn timeago(timestamp: Timestamp) -> String {
+    let elapsed = Timestamp::now()
+        .as_secs()
+        .saturating_sub(timestamp.as_secs());
+    let units = [
+        (60 * 60 * 24 * 365, "year"),
+        (60 * 60 * 24 * 30, "month"),
+        (60 * 60 * 24 * 7, "week"),
+        (60 * 60 * 24, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ];
+
+    for (secs, unit) in units {
+        let n = elapsed / secs;
+
+        if n == 1 {
+            return format!("1 {} ago", unit);
+        } else if n > 1 {
+            return format!("{} {}s ago", n, unit);
+        }
+    }
+    String::from("just now")
+}
@@ -41,6 +41,9 @@ macro_rules! tip {
 }
 
 pub fn success_args(args: fmt::Arguments) {
+    if radicle_common::args::quiet() {
+        return;
+    }
     println!("{} {}", style("ok").green().reverse(), args);
 }
 
@@ -61,7 +64,15 @@ pub fn width() -> usize {
     rows as usize
 }
 
+pub fn height() -> usize {
+    let (rows, _) = console::Term::stdout().size();
+    rows as usize
+}
+
 pub fn headline(headline: &str) {
+    if radicle_common::args::quiet() {
+        return;
+    }
     println!();
     println!("{}", style(headline).bold());
     println!();
@@ -168,7 +179,8 @@ pub fn abort<D: fmt::Display>(prompt: D) -> bool {
 /// Get the signer. First we try getting it from ssh-agent, otherwise we prompt the user.
 pub fn signer(profile: &Profile) -> anyhow::Result<BoxedSigner> {
     let signer = if let Ok(sock) = keys::ssh_auth_sock() {
-        sock.to_signer(profile)?
+        sock.to_signer(profile)
+            .map_err(|err| keys::signer_error(profile, err))?
     } else {
         secret_key(profile)?.to_signer(profile)?
     };
@@ -314,6 +326,23 @@ where
     result.map(|i| &options[i])
 }
 
+/// Ask the user to pick one of `options` with the arrow keys, eg. a verdict or a state. The
+/// first option is selected by default. Fails if the selection is canceled.
+pub fn select_one<T>(prompt: &str, options: &[T]) -> anyhow::Result<T>
+where
+    T: fmt::Display + Clone,
+{
+    let selection = dialoguer::Select::with_theme(&theme())
+        .with_prompt(prompt)
+        .items(&options.iter().map(|o| o.to_string()).collect::<Vec<_>>())
+        .default(0)
+        .interact_opt()?;
+
+    selection
+        .map(|i| options[i].clone())
+        .ok_or_else(|| anyhow::anyhow!("Canceled."))
+}
+
 pub fn profile_select<'a>(profiles: &'a [Profile], active: &Profile) -> Option<&'a Profile> {
     let active = profiles.iter().position(|p| p.id() == active.id()).unwrap();
     let selection = dialoguer::Select::with_theme(&theme())
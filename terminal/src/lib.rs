@@ -1,9 +1,13 @@
 pub mod command;
+pub mod discussion;
+pub mod editor;
 #[cfg(feature = "ethereum")]
 pub mod ethereum;
 pub mod format;
 pub mod io;
 pub mod keys;
+pub mod logger;
+pub mod pager;
 pub mod patch;
 pub mod spinner;
 pub mod sync;
@@ -15,10 +19,10 @@ use std::process;
 use dialoguer::console::style;
 use radicle_common::args::{Args, Error, Help};
 
-pub use dialoguer::Editor;
+pub use discussion::discussion;
 pub use io::*;
 pub use spinner::{spinner, Spinner};
-pub use table::Table;
+pub use table::{Alignment, Table};
 pub use textbox::TextBox;
 
 pub fn run_command<A, F>(help: Help, action: &str, run: F) -> !
@@ -28,6 +32,9 @@ where
 {
     use crate::io as term;
 
+    if let Err(err) = logger::init() {
+        term::warning(&format!("Logging is disabled: {}", err));
+    }
     let options = match A::from_env() {
         Ok(opts) => opts,
         Err(err) => {
@@ -53,6 +60,13 @@ where
         }
     };
 
+    if let Some(id) = radicle_common::profile::overridden() {
+        term::warning(&format!(
+            "Using profile {}, which is not the active profile",
+            id
+        ));
+    }
+
     match run(options) {
         Ok(()) => process::exit(0),
         Err(err) => {
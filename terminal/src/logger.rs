@@ -0,0 +1,48 @@
+//! Diagnostic logging, enabled with the `RAD_LOG` environment variable, eg. `RAD_LOG=debug`.
+//! Logs are written to stderr, so that they don't mix with the output of commands.
+use std::str::FromStr;
+
+use dialoguer::console::style;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use radicle_common::args::LOG_ENV;
+
+struct Logger {
+    level: LevelFilter,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let prefix = format!("{:<5} {}", record.level(), record.target());
+        let prefix = match record.level() {
+            Level::Error => style(prefix).red(),
+            Level::Warn => style(prefix).yellow(),
+            Level::Info => style(prefix).blue(),
+            Level::Debug | Level::Trace => style(prefix).dim(),
+        };
+        eprintln!("{} {}", prefix, record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger, if a level is set under [`LOG_ENV`]. Otherwise, logging stays off,
+/// and log statements are skipped after a single level check.
+pub fn init() -> anyhow::Result<()> {
+    let level = match std::env::var(LOG_ENV) {
+        Ok(level) if !level.is_empty() => LevelFilter::from_str(&level)
+            .map_err(|_| anyhow::anyhow!("`{}` is not set to a valid log level", LOG_ENV))?,
+        _ => return Ok(()),
+    };
+    log::set_boxed_logger(Box::new(Logger { level }))?;
+    log::set_max_level(level);
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+//! Paging of long output, like git does.
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate as term;
+
+/// Pager used if `PAGER` isn't set.
+pub const DEFAULT_PAGER: &str = "less -R";
+
+/// Show the given output through the user's pager, if stdout is a terminal and the
+/// output doesn't fit on it. Otherwise, or if the pager can't be started, print it.
+pub fn page(output: &str) -> anyhow::Result<()> {
+    if !console::Term::stdout().is_term() || output.lines().count() < term::height() {
+        print!("{}", output);
+        return Ok(());
+    }
+
+    let pager = env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_owned());
+    let mut args = pager.split_whitespace();
+    let child = args.next().and_then(|program| {
+        Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .spawn()
+            .ok()
+    });
+
+    match child {
+        Some(mut child) => {
+            if let Some(stdin) = child.stdin.as_mut() {
+                // The pager may exit before reading everything, eg. if the user quits.
+                stdin.write_all(output.as_bytes()).ok();
+            }
+            child.wait()?;
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
@@ -1,41 +1,39 @@
+use std::fmt::Write as _;
+
 use radicle_common::git;
 
 use crate as term;
 
 /// List all commits between `left` and `right` in the given repository.
+/// If `left` is `None`, all commits reachable from `right` are listed.
 pub fn list_commits(
     repo: &git::Repository,
-    left: &git::Oid,
+    left: Option<&git::Oid>,
     right: &git::Oid,
     show_header: bool,
 ) -> anyhow::Result<()> {
     let mut table = term::Table::default();
 
-    let left_short = format!("{:.7}", left.to_string());
-    let right_short = format!("{:.7}", right.to_string());
-
     let mut revwalk = repo.revwalk()?;
-    revwalk.push_range(&format!("{}..{}", left_short, right_short))?;
+    match left {
+        Some(left) => revwalk.push_range(&format!("{}..{}", left, right))?,
+        None => revwalk.push(*right)?,
+    }
+    let revs = revwalk.collect::<Result<Vec<_>, _>>()?;
 
     if show_header {
         term::blank();
-        term::info!(
-            "Found {} commit(s).",
-            term::format::highlight(revwalk.count())
-        );
+        term::info!("Found {} commit(s).", term::format::highlight(revs.len()));
         term::blank();
     }
 
-    let mut revwalk = repo.revwalk()?;
-    revwalk.push_range(&format!("{}..{}", left_short, right_short))?;
-
-    for rev in revwalk {
-        let commit = repo.find_commit(rev?)?;
+    for rev in revs {
+        let commit = repo.find_commit(rev)?;
         let message = commit
             .summary_bytes()
             .unwrap_or_else(|| commit.message_bytes());
         table.push([
-            term::format::secondary(format!("{:.7}", commit.id().to_string())),
+            term::format::secondary(git::short(commit.id())),
             term::format::italic(String::from_utf8_lossy(message)),
         ]);
     }
@@ -43,3 +41,46 @@ pub fn list_commits(
 
     Ok(())
 }
+
+/// Show the changes between `left` and `right` by word, see [`git::word_diff`]. Removed
+/// and added words are marked like git does, eg. `[-old-]{+new+}`, and colored. Long diffs
+/// are paged, unless `pager` is `false`.
+pub fn word_diff(
+    repo: &git::Repository,
+    left: &git::Oid,
+    right: &git::Oid,
+    pager: bool,
+) -> anyhow::Result<()> {
+    let mut output = String::new();
+
+    for file in git::word_diff(repo, left, right)? {
+        writeln!(output, "{}", term::format::bold(file.path.display()))?;
+
+        for (header, lines) in file.hunks {
+            writeln!(output, "{}", term::format::tertiary(header))?;
+
+            for line in lines {
+                for word in line {
+                    match word {
+                        git::Word::Unchanged(word) => output.push_str(&word),
+                        git::Word::Added(word) => {
+                            output.push_str(&term::format::positive(format!("{{+{}+}}", word)))
+                        }
+                        git::Word::Removed(word) => {
+                            output.push_str(&term::format::negative(format!("[-{}-]", word)))
+                        }
+                    }
+                }
+                output.push('\n');
+            }
+        }
+        output.push('\n');
+    }
+
+    if pager {
+        term::pager::page(&output)
+    } else {
+        print!("{}", output);
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use dialoguer::console::style;
 use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
 
+use radicle_common::args;
+
 use crate as term;
 
 pub struct Spinner {
@@ -24,6 +26,9 @@ impl Spinner {
 
     pub fn done(self) {
         self.progress.finish_and_clear();
+        if args::quiet() {
+            return;
+        }
         term::info!("{}", &self.message);
     }
 
@@ -48,8 +53,16 @@ impl Spinner {
     }
 }
 
+/// Show a spinner with the given message, until it is finished. With `--quiet`, the spinner
+/// is hidden, and only failures are reported.
 pub fn spinner(message: &str) -> Spinner {
     let message = message.to_owned();
+    if args::quiet() {
+        return Spinner {
+            message,
+            progress: ProgressBar::hidden(),
+        };
+    }
     let style = ProgressStyle::default_spinner()
         .tick_strings(&[
             &style("\\ ").yellow().to_string(),
@@ -1,7 +1,10 @@
+use std::borrow::Cow;
 use std::fmt::Write;
 
 use crate as term;
 
+pub use console::Alignment;
+
 #[derive(Debug, Default)]
 pub struct TableOptions {
     pub overflow: bool,
@@ -12,6 +15,8 @@ pub struct Table<const W: usize> {
     rows: Vec<[String; W]>,
     widths: [usize; W],
     opts: TableOptions,
+    alignments: [Alignment; W],
+    max_widths: [Option<usize>; W],
 }
 
 impl<const W: usize> Table<W> {
@@ -20,6 +25,8 @@ impl<const W: usize> Table<W> {
             rows: Vec::new(),
             widths: [0; W],
             opts,
+            alignments: [Alignment::Left; W],
+            max_widths: [None; W],
         }
     }
 
@@ -28,9 +35,23 @@ impl<const W: usize> Table<W> {
             rows: Vec::new(),
             widths: [0; W],
             opts: TableOptions::default(),
+            alignments: [Alignment::Left; W],
+            max_widths: [None; W],
         }
     }
 
+    /// Set the alignment of a column. Columns are left-aligned by default.
+    pub fn align(&mut self, column: usize, alignment: Alignment) -> &mut Self {
+        self.alignments[column] = alignment;
+        self
+    }
+
+    /// Cap the width of a column. Longer cells are truncated with an ellipsis.
+    pub fn max_width(&mut self, column: usize, width: usize) -> &mut Self {
+        self.max_widths[column] = Some(width);
+        self
+    }
+
     pub fn push(&mut self, row: [String; W]) {
         for (i, cell) in row.iter().enumerate() {
             self.widths[i] = self.widths[i].max(console::measure_text_width(cell));
@@ -39,26 +60,44 @@ impl<const W: usize> Table<W> {
     }
 
     pub fn render(self) {
+        for line in self.lines() {
+            println!("{}", line);
+        }
+    }
+
+    /// Render the table through the pager, if it doesn't fit on the terminal.
+    /// See [`term::pager::page`].
+    pub fn render_paged(self) -> anyhow::Result<()> {
+        let mut output = self.lines().join("\n");
+        output.push('\n');
+
+        term::pager::page(&output)
+    }
+
+    fn lines(&self) -> Vec<String> {
         let width = term::width(); // Terminal width.
+        let mut lines = Vec::with_capacity(self.rows.len());
 
         for row in &self.rows {
             let mut output = String::new();
             let cells = row.len();
 
             for (i, cell) in row.iter().enumerate() {
-                if i == cells - 1 || self.opts.overflow {
+                let cell = self.truncate(i, cell);
+
+                if self.opts.overflow
+                    || (i == cells - 1 && matches!(self.alignments[i], Alignment::Left))
+                {
                     write!(output, "{}", cell).ok();
+                } else if i == cells - 1 {
+                    write!(output, "{}", self.pad(i, &cell)).ok();
                 } else {
-                    write!(
-                        output,
-                        "{} ",
-                        console::pad_str(cell, self.widths[i], console::Alignment::Left, None)
-                    )
-                    .ok();
+                    write!(output, "{} ", self.pad(i, &cell)).ok();
                 }
             }
-            println!("{}", console::truncate_str(&output, width - 1, "…"));
+            lines.push(console::truncate_str(&output, width - 1, "…").into_owned());
         }
+        lines
     }
 
     pub fn render_tree(self) {
@@ -69,12 +108,53 @@ impl<const W: usize> Table<W> {
                 print!("└── ");
             }
             for (i, cell) in row.iter().enumerate() {
-                print!(
-                    "{} ",
-                    console::pad_str(cell, self.widths[i], console::Alignment::Left, None)
-                );
+                print!("{} ", self.pad(i, &self.truncate(i, cell)));
             }
             println!();
         }
     }
+
+    /// Width of a column, taking its maximum width into account.
+    fn width(&self, column: usize) -> usize {
+        match self.max_widths[column] {
+            Some(max) => self.widths[column].min(max),
+            None => self.widths[column],
+        }
+    }
+
+    fn truncate<'a>(&self, column: usize, cell: &'a str) -> Cow<'a, str> {
+        match self.max_widths[column] {
+            Some(max) => console::truncate_str(cell, max, "…"),
+            None => Cow::Borrowed(cell),
+        }
+    }
+
+    fn pad<'a>(&self, column: usize, cell: &'a str) -> Cow<'a, str> {
+        console::pad_str(cell, self.width(column), self.alignments[column], None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_align() {
+        let mut table = Table::<2>::default();
+        table.align(1, Alignment::Right);
+        table.push(["a".to_owned(), "1".to_owned()]);
+        table.push(["bbb".to_owned(), "100".to_owned()]);
+
+        assert_eq!(table.lines(), ["a     1", "bbb 100"]);
+    }
+
+    #[test]
+    fn test_max_width() {
+        let mut table = Table::<2>::default();
+        table.max_width(0, 4);
+        table.push(["abcdefgh".to_owned(), "x".to_owned()]);
+        table.push(["ab".to_owned(), "y".to_owned()]);
+
+        assert_eq!(table.lines(), ["abc… x", "ab   y"]);
+    }
 }
@@ -10,7 +10,7 @@ use librad::git::tracking;
 use librad::profile::Profile;
 use librad::PeerId;
 
-use radicle_common::args::Help;
+use radicle_common::args::{Error, Help};
 use radicle_common::project::PeerInfo;
 use radicle_common::Url;
 use radicle_common::{git, keys, profile, project, seed};
@@ -41,7 +41,7 @@ Usage
 
     rad track           [--local | --remote]
     rad track           [--seed <host>]
-    rad track <peer-id> [--seed <host>] [--no-sync] [--no-upstream] [--no-fetch]
+    rad track <peer-id> [--seed <host>] [--sync | --no-sync] [--no-upstream] [--no-fetch]
 
     If a peer id is supplied, track this peer in the context of the current project. By default,
     a remote is created in the repository and an upstream tracking branch is setup. If a seed
@@ -55,8 +55,12 @@ Options
     --remote               Show the remote project tracking graph from a seed
     --seed <host>          Seed host to fetch refs from
     --no-upstream          Don't setup a tracking branch for the remote
+    --sync                 Sync the peer's refs from the seed right away, failing if
+                           there is no seed to sync with (default: sync if a seed is known)
     --no-sync              Don't sync the peer's refs
     --no-fetch             Don't fetch the peer's refs into the working copy
+    --quiet                Only print essential output, without headlines, spinners or banners.
+                           Must be given before the <peer-id>
     --help                 Print help
 "#,
 };
@@ -123,22 +127,38 @@ pub fn track(
         .map(|s| s.url())
         .or_else(|| seed::get_seed(seed::Scope::Any).ok());
 
-    if let Some(seed) = seed {
-        if options.sync {
+    match seed {
+        Some(seed) if options.sync != Some(false) => {
             // Fetch refs from seed...
             let mut spinner = term::spinner(&format!(
                 "Syncing peer refs from {}...",
                 term::format::highlight(seed.host_str().unwrap_or("seed"))
             ));
-            if let Err(e) = term::sync::fetch_remotes(&storage, &seed, urn, [&peer], &mut spinner) {
-                spinner.failed();
-                term::blank();
-
-                return Err(e);
+            match term::sync::fetch_remotes(&storage, &seed, urn, [&peer], &mut spinner) {
+                Ok(output) => {
+                    spinner.finish();
+                    term::success!(
+                        "Fetched {} ref(s) of {}",
+                        git::fetched_refs(&output),
+                        term::format::tertiary(radicle_common::fmt::peer(&peer))
+                    );
+                }
+                Err(e) => {
+                    spinner.failed();
+                    term::blank();
+
+                    return Err(e);
+                }
             }
-
-            spinner.finish();
         }
+        None if options.sync == Some(true) => {
+            return Err(Error::WithHint {
+                err: anyhow!("there is no seed to sync the peer's refs from"),
+                hint: "Specify a seed with `--seed`, or configure one with `rad sync --seed`.",
+            }
+            .into());
+        }
+        _ => {}
     }
 
     // If a seed is explicitly specified, associate it with the peer being tracked.
@@ -14,7 +14,9 @@ use radicle_common::seed::{Address, SeedOptions};
 pub struct Options {
     pub peer: Option<PeerId>,
     pub upstream: bool,
-    pub sync: bool,
+    /// Whether to fetch the peer's refs from the seed into storage. If `None`, they're
+    /// fetched only if a seed is known.
+    pub sync: Option<bool>,
     pub fetch: bool,
     pub local: bool,
     pub seed: Option<Address>,
@@ -29,7 +31,7 @@ impl Args for Options {
         let mut peer: Option<PeerId> = None;
         let mut local: Option<bool> = None;
         let mut upstream = true;
-        let mut sync = true;
+        let mut sync = None;
         let mut fetch = true;
 
         while let Some(arg) = parser.next()? {
@@ -45,7 +47,8 @@ impl Args for Options {
                 Long("local") => local = Some(true),
                 Long("remote") => local = Some(false),
                 Long("no-upstream") => upstream = false,
-                Long("no-sync") => sync = false,
+                Long("no-sync") => sync = Some(false),
+                Long("sync") => sync = Some(true),
                 Long("no-fetch") => fetch = false,
 
                 Long("help") => {
@@ -4,12 +4,13 @@ use std::str::FromStr;
 use anyhow::anyhow;
 use anyhow::Context as _;
 
+use librad::git::storage::Storage;
 use librad::git::tracking::git::tracking;
 use librad::git::Urn;
 use librad::PeerId;
 
 use radicle_common::args::{Args, Error, Help};
-use radicle_common::{keys, profile, project};
+use radicle_common::{git, keys, patch, profile, project};
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -19,20 +20,32 @@ pub const HELP: Help = Help {
     usage: r#"
 Usage
 
-    rad untrack [<peer-id>] [--all]
+    rad untrack [<peer-id>] [--all] [--prune] [--no-confirm]
 
     Must be run within a project working copy.
 
+    Before untracking, the branches and patches of the peer that will no longer
+    be replicated are shown. Unless --prune is given, the peer's refs that were
+    already fetched are kept in local storage.
+
 Options
 
-    --help   Print help
+    --all           Untrack all peers of the project
+    --prune         Also remove the peer's fetched refs from local storage
+    --no-confirm    Do not ask for confirmation before untracking
+    --help          Print help
 "#,
 };
 
 /// Tool options.
 #[derive(Debug)]
 pub struct Options {
+    /// Peer to untrack, or all peers if `None`.
     pub peer: Option<PeerId>,
+    /// Whether to remove the refs of untracked peers from local storage.
+    pub prune: bool,
+    /// Whether to ask for confirmation before untracking.
+    pub confirm: bool,
 }
 
 impl Args for Options {
@@ -42,12 +55,20 @@ impl Args for Options {
         let mut parser = lexopt::Parser::from_args(args);
         let mut peer: Option<PeerId> = None;
         let mut all = false;
+        let mut prune = false;
+        let mut confirm = true;
 
         while let Some(arg) = parser.next()? {
             match arg {
                 Long("all") if peer.is_none() => {
                     all = true;
                 }
+                Long("prune") => {
+                    prune = true;
+                }
+                Long("no-confirm") => {
+                    confirm = false;
+                }
                 Value(val) if peer.is_none() => {
                     let val = val.to_string_lossy();
 
@@ -70,7 +91,14 @@ impl Args for Options {
             return Err(Error::Usage.into());
         }
 
-        Ok((Options { peer }, vec![]))
+        Ok((
+            Options {
+                peer,
+                prune,
+                confirm,
+            },
+            vec![],
+        ))
     }
 }
 
@@ -93,6 +121,28 @@ pub fn execute(urn: &Urn, options: Options) -> anyhow::Result<()> {
     let profile = profile::default()?;
     let signer = term::signer(&profile)?;
     let storage = keys::storage(&profile, signer)?;
+    let project = project::get(&storage, urn)?
+        .ok_or_else(|| anyhow!("project {} not found in local storage", urn))?;
+
+    let peers = match options.peer {
+        Some(peer) => vec![project::PeerInfo::get(&peer, &project, &storage)],
+        None => project::tracked(&project, &storage)?
+            .into_values()
+            .collect(),
+    };
+    if !peers.is_empty() {
+        summary(&project, &peers, &storage)?;
+    }
+    if options.confirm {
+        let prompt = if options.prune {
+            "Stop replicating these peers, and remove their refs from local storage?"
+        } else {
+            "Stop replicating these peers?"
+        };
+        if !term::confirm(prompt) {
+            return Err(anyhow!("Canceled."));
+        }
+    }
 
     if let Some(peer) = options.peer {
         tracking::untrack(
@@ -101,7 +151,7 @@ pub fn execute(urn: &Urn, options: Options) -> anyhow::Result<()> {
             peer,
             tracking::UntrackArgs {
                 policy: tracking::policy::Untrack::MustExist,
-                prune: true,
+                prune: options.prune,
             },
         )??;
         term::success!(
@@ -115,7 +165,7 @@ pub fn execute(urn: &Urn, options: Options) -> anyhow::Result<()> {
             urn,
             tracking::UntrackAllArgs {
                 policy: tracking::policy::UntrackAll::Any,
-                prune: true,
+                prune: options.prune,
             },
         )?;
         term::success!(
@@ -124,5 +174,45 @@ pub fn execute(urn: &Urn, options: Options) -> anyhow::Result<()> {
         );
     }
 
+    if options.prune {
+        term::success!("Removed the fetched refs from local storage");
+    } else {
+        term::tip!("The fetched refs were kept in local storage. To remove them, pass `--prune`.");
+    }
+
+    Ok(())
+}
+
+/// Show the branches and patches of the given peers, which will stop being replicated.
+fn summary(
+    project: &project::Metadata,
+    peers: &[project::PeerInfo],
+    storage: &Storage,
+) -> anyhow::Result<()> {
+    let repo = git::Repository::open_bare(storage.path())?;
+    // The tag prefix may be configured in the working copy.
+    let prefix = project::cwd()
+        .map(|(_, repo)| patch::tag_prefix(&repo))
+        .unwrap_or_else(|_| patch::TAG_PREFIX.to_owned());
+    let mut table = term::Table::default();
+
+    for peer in peers {
+        let glob = format!(
+            "refs/namespaces/{}/refs/remotes/{}/heads/*",
+            project.urn.encode_id(),
+            peer.id
+        );
+        let branches = repo.references_glob(&glob)?.count();
+        let patches = patch::all(project, Some(peer.clone()), storage, &prefix)?.len();
+
+        table.push([
+            term::format::tertiary(peer.name()),
+            term::format::dim(format!("{} branch(es)", branches)),
+            term::format::dim(format!("{} patch(es)", patches)),
+        ]);
+    }
+    table.render();
+    term::blank();
+
     Ok(())
 }